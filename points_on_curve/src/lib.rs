@@ -286,8 +286,158 @@ where
     }
 }
 
+/// How the spline through the points of [`catmull_rom_to_bezier`] is parameterized, which
+/// decides how it behaves around points that are unevenly spaced.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum CurveParameterization {
+    /// Every segment takes the same time, the classic Catmull-Rom spline. It overshoots
+    /// next to short segments and may form cusps and loops.
+    #[default]
+    Uniform,
+    /// Segments take the square root of their length, which never forms cusps or loops
+    /// within a segment.
+    Centripetal,
+    /// Segments take their length, which follows the points most loosely.
+    Chordal,
+}
+
+impl CurveParameterization {
+    fn knot_interval<F: Float>(&self, from: Point2D<F>, to: Point2D<F>) -> F {
+        let length = (to - from).length();
+        match self {
+            CurveParameterization::Uniform => F::one(),
+            CurveParameterization::Centripetal => length.sqrt(),
+            CurveParameterization::Chordal => length,
+        }
+    }
+}
+
+/// Computes the two control points of the cubic Bezier curve from `p1` to `p2` on a
+/// Catmull-Rom spline, where `p0` and `p3` are the neighbouring points. The tangents are
+/// scaled by `1 - curve_tightness`.
+///
+/// # examples
+/// ```
+/// use euclid::point2;
+/// use points_on_curve::{catmull_rom_segment, CurveParameterization};
+/// let (cp1, cp2) = catmull_rom_segment(
+///     point2(0.0, 0.0),
+///     point2(1.0, 0.0),
+///     point2(2.0, 0.0),
+///     point2(3.0, 0.0),
+///     0.0,
+///     CurveParameterization::Uniform,
+/// );
+/// assert_eq!(cp1, point2(1.0 + 1.0 / 3.0, 0.0));
+/// assert_eq!(cp2, point2(2.0 - 1.0 / 3.0, 0.0));
+/// ```
+pub fn catmull_rom_segment<F>(
+    p0: Point2D<F>,
+    p1: Point2D<F>,
+    p2: Point2D<F>,
+    p3: Point2D<F>,
+    curve_tightness: F,
+    parameterization: CurveParameterization,
+) -> (Point2D<F>, Point2D<F>)
+where
+    F: Float,
+{
+    let s = F::one() - curve_tightness;
+    let six = F::from(6).unwrap();
+    if parameterization == CurveParameterization::Uniform {
+        return (
+            point2(
+                p1.x + (s * p2.x - s * p0.x) / six,
+                p1.y + (s * p2.y - s * p0.y) / six,
+            ),
+            point2(
+                p2.x + (s * p1.x - s * p3.x) / six,
+                p2.y + (s * p1.y - s * p3.y) / six,
+            ),
+        );
+    }
+
+    let d1 = parameterization.knot_interval(p1, p2);
+    if d1 <= F::epsilon() {
+        return (p1, p2);
+    }
+    // Duplicated points at the ends of open curves get the interval of the segment itself.
+    let d0 = Some(parameterization.knot_interval(p0, p1))
+        .filter(|d| *d > F::epsilon())
+        .unwrap_or(d1);
+    let d2 = Some(parameterization.knot_interval(p2, p3))
+        .filter(|d| *d > F::epsilon())
+        .unwrap_or(d1);
+    let three = F::from(3).unwrap();
+    let m1 = (p1 - p0) / d0 - (p2 - p0) / (d0 + d1) + (p2 - p1) / d1;
+    let m2 = (p2 - p1) / d1 - (p3 - p1) / (d1 + d2) + (p3 - p2) / d2;
+    (p1 + m1 * (s * d1 / three), p2 - m2 * (s * d1 / three))
+}
+
+/// Generates Bezier Curve parameters passing through given points, like [`curve_to_bezier`],
+/// with a choice of parameterization. Closed curves continue from the last point back to
+/// the first one without a kink, a repeated first point at the end is ignored.
+///
+/// # examples
+/// ```
+/// use euclid::point2;
+/// use points_on_curve::{catmull_rom_to_bezier, CurveParameterization};
+/// let square = [
+///     point2(0.0, 0.0),
+///     point2(1.0, 0.0),
+///     point2(1.0, 1.0),
+///     point2(0.0, 1.0),
+/// ];
+/// let curve =
+///     catmull_rom_to_bezier(&square, 0.0, true, CurveParameterization::Centripetal).unwrap();
+/// assert_eq!(curve.len(), 1 + 4 * 3);
+/// assert_eq!(curve.first(), curve.last());
+/// ```
+pub fn catmull_rom_to_bezier<F>(
+    points_in: &[Point2D<F>],
+    curve_tightness: F,
+    closed: bool,
+    parameterization: CurveParameterization,
+) -> Option<Vec<Point2D<F>>>
+where
+    F: Float,
+{
+    let mut points = points_in.to_vec();
+    if closed && points.len() > 1 && points.first() == points.last() {
+        points.pop();
+    }
+    if points.len() < 3 {
+        return None;
+    }
+    let len = points.len();
+    let neighbour = |i: isize| {
+        if closed {
+            points[i.rem_euclid(len as isize) as usize]
+        } else {
+            points[i.clamp(0, len as isize - 1) as usize]
+        }
+    };
+    let segments = if closed { len } else { len - 1 };
+    let mut out = vec![points[0]];
+    for i in 0..segments as isize {
+        let (cp1, cp2) = catmull_rom_segment(
+            neighbour(i - 1),
+            neighbour(i),
+            neighbour(i + 1),
+            neighbour(i + 2),
+            curve_tightness,
+            parameterization,
+        );
+        out.push(cp1);
+        out.push(cp2);
+        out.push(neighbour(i + 1));
+    }
+    Some(out)
+}
+
 #[cfg(test)]
 mod tests {
+    use euclid::default::Point2D;
     use euclid::point2;
 
     #[test]
@@ -524,4 +674,54 @@ mod tests {
         let result = super::curve_to_bezier(&input, 0.0).unwrap();
         assert_eq!(expected, result);
     }
+
+    #[test]
+    fn catmull_rom_to_bezier() {
+        use super::CurveParameterization::{Centripetal, Chordal, Uniform};
+
+        let input: Vec<Point2D<f64>> = vec![
+            point2(20.0, 240.0),
+            point2(95.0, 69.0),
+            point2(225.0, 90.0),
+            point2(250.0, 180.0),
+            point2(290.0, 220.0),
+            point2(380.0, 80.0),
+        ];
+        assert_eq!(
+            super::catmull_rom_to_bezier(&input, 0.0, false, Uniform),
+            super::curve_to_bezier(&input, 0.0)
+        );
+
+        // the closed curve leaves the first point in the direction it arrives in
+        for parameterization in [Uniform, Centripetal, Chordal] {
+            let closed = super::catmull_rom_to_bezier(&input, 0.0, true, parameterization).unwrap();
+            assert_eq!(closed.len(), 1 + input.len() * 3);
+            assert_eq!(closed[closed.len() - 1], input[0]);
+            let incoming = input[0] - closed[closed.len() - 2];
+            let outgoing = closed[1] - input[0];
+            assert!(incoming.cross(outgoing).abs() < 1e-9);
+            assert!(incoming.dot(outgoing) > 0.0);
+        }
+    }
+
+    #[test]
+    fn centripetal_catmull_rom_does_not_overshoot() {
+        use super::CurveParameterization::{Centripetal, Uniform};
+
+        // two points close together between far away ones
+        let input = vec![
+            point2(0.0, 0.0),
+            point2(100.0, 0.0),
+            point2(101.0, 1.0),
+            point2(101.0, 100.0),
+        ];
+        let overshoot = |parameterization| {
+            let bezier =
+                super::catmull_rom_to_bezier(&input, 0.0, false, parameterization).unwrap();
+            let points = super::points_on_bezier_curves(&bezier, 0.01, None);
+            points.iter().fold(0.0f64, |max, p| max.max(p.x - 101.0))
+        };
+        assert!(overshoot(Uniform) > 5.0);
+        assert!(overshoot(Centripetal) < overshoot(Uniform) / 4.0);
+    }
 }
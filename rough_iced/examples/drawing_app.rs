@@ -7,7 +7,7 @@ use iced_widget::{checkbox, pick_list, scrollable, slider, text};
 use num_traits::FloatConst;
 use palette::Srgba;
 use rough_iced::IcedGenerator;
-use roughr::core::{FillStyle, OptionsBuilder};
+use roughr::core::{ArcMode, FillStyle, OptionsBuilder};
 use svg_path_ops::pt::PathTransformer;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -322,6 +322,16 @@ impl DrawingApp {
             FillStyle::Dots,
             FillStyle::Dashed,
             FillStyle::ZigZagLine,
+            FillStyle::Stipple,
+            FillStyle::Wavy,
+            FillStyle::Scribble,
+            FillStyle::HatchLayers,
+            FillStyle::ToneHatch,
+            FillStyle::Motif,
+            FillStyle::Flow,
+            FillStyle::Hilbert,
+            FillStyle::Serpentine,
+            FillStyle::Watercolor,
         ];
         let fill_style_controls = pick_list(
             fill_styles,
@@ -666,7 +676,7 @@ impl<Message> canvas::Program<Message> for DrawingApp {
                     bounds.height / 2.0,
                     -f32::PI() / 2.0,
                     f32::PI() / 2.0,
-                    false,
+                    ArcMode::Open,
                 );
                 arc_path.draw(&mut frame);
             }
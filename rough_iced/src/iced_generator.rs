@@ -1,18 +1,20 @@
 use std::fmt::Display;
 use std::ops::MulAssign;
 
-use euclid::default::Point2D;
+use euclid::default::{Point2D, Vector2D};
 use euclid::Trig;
-
-use iced_core::{Color, Point};
+use iced_core::{Color, Point, Radians, Vector};
 use iced_widget::canvas::fill::Rule;
+use iced_widget::canvas::path::arc::Elliptical;
 use iced_widget::canvas::path::lyon_path::PathEvent;
+use iced_widget::canvas::path::Builder;
 use iced_widget::canvas::{Fill, Frame, LineDash, Path, Stroke, Style};
 use num_traits::{Float, FromPrimitive};
 use palette::rgb::Rgba;
 use palette::Srgba;
-use roughr::core::{Drawable, OpSet, OpSetType, OpType, Options};
+use roughr::core::{ArcMode, Drawable, FillRule, OpSet, OpSetStyle, OpSetType, Options, ShapeKind};
 use roughr::generator::Generator;
+use roughr::sink::{stream_ops, PathSink};
 use roughr::PathSegment;
 
 #[derive(Default)]
@@ -27,6 +29,7 @@ pub struct IcedOpset<F: Float + Trig> {
     pub ops: Path,
     pub size: Option<Point2D<F>>,
     pub path: Option<String>,
+    pub style: Option<OpSetStyle>,
 }
 
 pub trait ToIcedOpset<F: Float + Trig> {
@@ -39,13 +42,14 @@ impl<F: Float + Trig + FromPrimitive> ToIcedOpset<F> for OpSet<F> {
             op_set_type: self.op_set_type.clone(),
             size: self.size,
             path: self.path.clone(),
+            style: self.style.clone(),
             ops: opset_to_shape(&self),
         }
     }
 }
 
 pub struct IcedDrawable<F: Float + Trig> {
-    pub shape: String,
+    pub shape: ShapeKind<F>,
     pub options: Options,
     pub sets: Vec<IcedOpset<F>>,
 }
@@ -56,11 +60,24 @@ pub trait ToIcedDrawable<F: Float + Trig> {
 
 impl<F: Float + Trig + FromPrimitive> ToIcedDrawable<F> for Drawable<F> {
     fn to_iced_drawable(self) -> IcedDrawable<F> {
-        IcedDrawable {
-            shape: self.shape,
-            options: self.options,
-            sets: self.sets.into_iter().map(|s| s.to_iced_opset()).collect(),
-        }
+        iced_drawable(|sink| {
+            self.stream_into(sink);
+            self
+        })
+    }
+}
+
+/// Iced drawable of the drawable `draw` returns, built from the sets it streams.
+fn iced_drawable<F: Float + Trig>(
+    draw: impl FnOnce(&mut IcedOpsetSink<F>) -> Drawable<F>,
+) -> IcedDrawable<F> {
+    let mut sink = IcedOpsetSink { sets: vec![], set: None };
+    let drawable = draw(&mut sink);
+    sink.end_set();
+    IcedDrawable {
+        shape: drawable.shape,
+        options: drawable.options,
+        sets: sink.sets,
     }
 }
 
@@ -74,9 +91,8 @@ impl<F: Float + Trig> IcedDrawable<F> {
     pub fn draw(&self, frame: &mut Frame) {
         let stroke_line_dash = self
             .options
-            .stroke_line_dash
-            .clone()
-            .unwrap_or(Vec::new())
+            .native_stroke_line_dash()
+            .unwrap_or_default()
             .iter()
             .map(|&a| a as f32)
             .collect::<Vec<f32>>();
@@ -95,7 +111,7 @@ impl<F: Float + Trig> IcedDrawable<F> {
                 OpSetType::Path => {
                     //ctx.save().expect("Failed to save render context");
                     frame.with_save(|f| {
-                        if self.options.stroke_line_dash.is_some() {
+                        if self.options.native_stroke_line_dash().is_some() {
                             let mut stroke_style = Stroke::default();
                             stroke_style.line_dash = LineDash {
                                 segments: stroke_line_dash.as_slice(),
@@ -142,36 +158,29 @@ impl<F: Float + Trig> IcedDrawable<F> {
                     })
                 }
                 OpSetType::FillPath => {
-                    frame.with_save(|f| match self.shape.as_str() {
-                        "curve" | "polygon" | "path" => {
-                            let fill_color =
-                                self.options.fill.unwrap_or(Rgba::new(1.0, 1.0, 1.0, 1.0));
-                            let rgb: (f32, f32, f32, f32) = fill_color.into_components();
-
-                            f.fill(
-                                &set.ops,
-                                Fill {
-                                    style: Style::Solid(Color::from_rgba(
-                                        rgb.0, rgb.1, rgb.2, rgb.3,
-                                    )),
-                                    rule: Rule::EvenOdd,
-                                },
-                            )
-                        }
-                        _ => {
-                            let fill_color =
-                                self.options.fill.unwrap_or(Rgba::new(1.0, 1.0, 1.0, 1.0));
-                            let rgb: (f32, f32, f32, f32) = fill_color.into_components();
-                            f.fill(
-                                &set.ops,
-                                Fill {
-                                    style: Style::Solid(Color::from_rgba(
-                                        rgb.0, rgb.1, rgb.2, rgb.3,
-                                    )),
-                                    rule: Rule::EvenOdd,
-                                },
-                            )
-                        }
+                    frame.with_save(|f| {
+                        let fill_color = set
+                            .style
+                            .as_ref()
+                            .and_then(|style| style.color)
+                            .or(self.options.fill)
+                            .unwrap_or(Rgba::new(1.0, 1.0, 1.0, 1.0));
+                        let rgb: (f32, f32, f32, f32) = fill_color.into_components();
+                        let rule = match &self.shape {
+                            ShapeKind::PolygonWithHoles { .. } | ShapeKind::MultiPolygon { .. }
+                                if self.shape.fill_rule(&self.options) == FillRule::NonZero =>
+                            {
+                                Rule::NonZero
+                            }
+                            _ => Rule::EvenOdd,
+                        };
+                        f.fill(
+                            &set.ops,
+                            Fill {
+                                style: Style::Solid(Color::from_rgba(rgb.0, rgb.1, rgb.2, rgb.3)),
+                                rule,
+                            },
+                        )
                     });
                 }
                 OpSetType::FillSketch => {
@@ -179,14 +188,23 @@ impl<F: Float + Trig> IcedDrawable<F> {
                     if fweight < 0.0 {
                         fweight = self.options.stroke_width.unwrap_or(1.0) / 2.0;
                     }
+                    let style = set.style.clone().unwrap_or_default();
+                    if let Some(weight) = style.weight {
+                        fweight = weight;
+                    }
+                    let set_line_dash = style
+                        .line_dash
+                        .as_ref()
+                        .map(|d| d.iter().map(|&a| a as f32).collect::<Vec<f32>>())
+                        .unwrap_or_else(|| fill_line_dash.clone());
                     frame.with_save(|f| {
-                        if self.options.fill_line_dash.is_some() {
+                        if style.line_dash.is_some() || self.options.fill_line_dash.is_some() {
                             let mut stroke_style = Stroke::default();
                             stroke_style.line_dash = LineDash {
-                                segments: fill_line_dash.as_slice(),
-                                offset: self
-                                    .options
-                                    .fill_line_dash_offset
+                                segments: set_line_dash.as_slice(),
+                                offset: style
+                                    .line_dash_offset
+                                    .or(self.options.fill_line_dash_offset)
                                     .map(|a| a as usize)
                                     .unwrap_or(0),
                             };
@@ -197,9 +215,9 @@ impl<F: Float + Trig> IcedDrawable<F> {
                                 .with_line_join(convert_line_join_from_roughr_to_iced(
                                     self.options.line_join,
                                 ));
-                            let fill_color = self
-                                .options
-                                .fill
+                            let fill_color = style
+                                .color
+                                .or(self.options.fill)
                                 .unwrap_or_else(|| Rgba::new(1.0, 1.0, 1.0, 1.0));
                             let rgb: (f32, f32, f32, f32) = fill_color.into_components();
                             f.stroke(
@@ -209,9 +227,9 @@ impl<F: Float + Trig> IcedDrawable<F> {
                                     .with_width(fweight),
                             );
                         } else {
-                            let fill_color = self
-                                .options
-                                .fill
+                            let fill_color = style
+                                .color
+                                .or(self.options.fill)
                                 .unwrap_or_else(|| Rgba::new(1.0, 1.0, 1.0, 1.0));
                             let rgb: (f32, f32, f32, f32) = fill_color.into_components();
                             let stroke_style = Stroke::default();
@@ -229,39 +247,108 @@ impl<F: Float + Trig> IcedDrawable<F> {
     }
 }
 
-fn opset_to_shape<F: Trig + Float + FromPrimitive>(op_set: &OpSet<F>) -> Path {
-    let path: Path = Path::new(|b| {
-        for item in op_set.ops.iter() {
-            match item.op {
-                OpType::Move => b.move_to(Point::new(
-                    item.data[0].to_f32().unwrap(),
-                    item.data[1].to_f32().unwrap(),
-                )),
-                OpType::BCurveTo => b.bezier_curve_to(
-                    Point::new(
-                        item.data[0].to_f32().unwrap(),
-                        item.data[1].to_f32().unwrap(),
-                    ),
-                    Point::new(
-                        item.data[2].to_f32().unwrap(),
-                        item.data[3].to_f32().unwrap(),
-                    ),
-                    Point::new(
-                        item.data[4].to_f32().unwrap(),
-                        item.data[5].to_f32().unwrap(),
-                    ),
-                ),
-                OpType::LineTo => {
-                    b.line_to(Point::new(
-                        item.data[0].to_f32().unwrap(),
-                        item.data[1].to_f32().unwrap(),
-                    ));
-                }
-            }
+/// [`PathSink`] adding to an iced path [`Builder`].
+pub struct IcedPathSink<'a> {
+    pub builder: &'a mut Builder,
+}
+
+impl<F: Float + Trig> PathSink<F> for IcedPathSink<'_> {
+    fn move_to(&mut self, to: Point2D<F>) {
+        self.builder.move_to(iced_point(to));
+    }
+
+    fn line_to(&mut self, to: Point2D<F>) {
+        self.builder.line_to(iced_point(to));
+    }
+
+    fn curve_to(&mut self, cp1: Point2D<F>, cp2: Point2D<F>, to: Point2D<F>) {
+        self.builder
+            .bezier_curve_to(iced_point(cp1), iced_point(cp2), iced_point(to));
+    }
+
+    fn quad_to(&mut self, _from: Point2D<F>, cp: Point2D<F>, to: Point2D<F>) {
+        self.builder
+            .quadratic_curve_to(iced_point(cp), iced_point(to));
+    }
+
+    fn close(&mut self) {
+        self.builder.close();
+    }
+
+    fn ellipse(&mut self, center: Point2D<F>, radii: Vector2D<F>) {
+        self.builder.ellipse(Elliptical {
+            center: iced_point(center),
+            radii: Vector::new(radii.x.to_f32().unwrap(), radii.y.to_f32().unwrap()),
+            rotation: Radians(0.0),
+            start_angle: Radians(0.0),
+            end_angle: Radians(2.0 * std::f32::consts::PI),
+        });
+        // the ellipse is left open by iced
+        self.builder.close();
+    }
+}
+
+fn iced_point<F: Float>(p: Point2D<F>) -> Point {
+    Point::new(p.x.to_f32().unwrap(), p.y.to_f32().unwrap())
+}
+
+/// [`PathSink`] building an [`IcedOpset`] for every set streamed into it. Ops streamed
+/// outside of a set go into an [`OpSetType::Path`] set of their own.
+struct IcedOpsetSink<F: Float + Trig> {
+    sets: Vec<IcedOpset<F>>,
+    set: Option<(OpSetType, Option<OpSetStyle>, Builder)>,
+}
+
+impl<F: Float + Trig> IcedOpsetSink<F> {
+    fn path(&mut self) -> IcedPathSink<'_> {
+        let (_, _, builder) = self
+            .set
+            .get_or_insert_with(|| (OpSetType::Path, None, Builder::new()));
+        IcedPathSink { builder }
+    }
+}
+
+impl<F: Float + Trig> PathSink<F> for IcedOpsetSink<F> {
+    fn begin_set(&mut self, op_set_type: &OpSetType, style: Option<&OpSetStyle>) {
+        self.end_set();
+        self.set = Some((op_set_type.clone(), style.cloned(), Builder::new()));
+    }
+
+    fn move_to(&mut self, to: Point2D<F>) {
+        self.path().move_to(to);
+    }
+
+    fn line_to(&mut self, to: Point2D<F>) {
+        self.path().line_to(to);
+    }
+
+    fn curve_to(&mut self, cp1: Point2D<F>, cp2: Point2D<F>, to: Point2D<F>) {
+        self.path().curve_to(cp1, cp2, to);
+    }
+
+    fn quad_to(&mut self, from: Point2D<F>, cp: Point2D<F>, to: Point2D<F>) {
+        self.path().quad_to(from, cp, to);
+    }
+
+    fn close(&mut self) {
+        PathSink::<F>::close(&mut self.path());
+    }
+
+    fn ellipse(&mut self, center: Point2D<F>, radii: Vector2D<F>) {
+        self.path().ellipse(center, radii);
+    }
+
+    fn end_set(&mut self) {
+        if let Some((op_set_type, style, builder)) = self.set.take() {
+            let ops = builder.build();
+            self.sets
+                .push(IcedOpset { op_set_type, ops, size: None, path: None, style });
         }
-    });
+    }
+}
 
-    path
+fn opset_to_shape<F: Trig + Float + FromPrimitive>(op_set: &OpSet<F>) -> Path {
+    Path::new(|builder| stream_ops(&op_set.ops, &mut IcedPathSink { builder }))
 }
 
 impl IcedGenerator {
@@ -272,8 +359,7 @@ impl IcedGenerator {
         x2: F,
         y2: F,
     ) -> IcedDrawable<F> {
-        let drawable = self.gen.line(x1, y1, x2, y2, &self.options);
-        drawable.to_iced_drawable()
+        iced_drawable(|sink| self.gen.line_into(x1, y1, x2, y2, &self.options, sink))
     }
 
     pub fn rectangle<F: Trig + Float + FromPrimitive>(
@@ -283,8 +369,10 @@ impl IcedGenerator {
         width: F,
         height: F,
     ) -> IcedDrawable<F> {
-        let drawable = self.gen.rectangle(x, y, width, height, &self.options);
-        drawable.to_iced_drawable()
+        iced_drawable(|sink| {
+            self.gen
+                .rectangle_into(x, y, width, height, &self.options, sink)
+        })
     }
 
     pub fn ellipse<F: Trig + Float + FromPrimitive>(
@@ -294,8 +382,10 @@ impl IcedGenerator {
         width: F,
         height: F,
     ) -> IcedDrawable<F> {
-        let drawable = self.gen.ellipse(x, y, width, height, &self.options);
-        drawable.to_iced_drawable()
+        iced_drawable(|sink| {
+            self.gen
+                .ellipse_into(x, y, width, height, &self.options, sink)
+        })
     }
 
     pub fn circle<F: Trig + Float + FromPrimitive>(
@@ -304,8 +394,7 @@ impl IcedGenerator {
         y: F,
         diameter: F,
     ) -> IcedDrawable<F> {
-        let drawable = self.gen.circle(x, y, diameter, &self.options);
-        drawable.to_iced_drawable()
+        iced_drawable(|sink| self.gen.circle_into(x, y, diameter, &self.options, sink))
     }
 
     pub fn linear_path<F: Trig + Float + FromPrimitive>(
@@ -313,16 +402,35 @@ impl IcedGenerator {
         points: &[Point2D<F>],
         close: bool,
     ) -> IcedDrawable<F> {
-        let drawable = self.gen.linear_path(points, close, &self.options);
-        drawable.to_iced_drawable()
+        iced_drawable(|sink| {
+            self.gen
+                .linear_path_into(points, close, &self.options, sink)
+        })
     }
 
     pub fn polygon<F: Trig + Float + FromPrimitive + MulAssign + Display>(
         &self,
         points: &[Point2D<F>],
     ) -> IcedDrawable<F> {
-        let drawable = self.gen.polygon(points, &self.options);
-        drawable.to_iced_drawable()
+        iced_drawable(|sink| self.gen.polygon_into(points, &self.options, sink))
+    }
+
+    pub fn polygon_with_holes<F: Trig + Float + FromPrimitive + MulAssign + Display>(
+        &self,
+        outer: &[Point2D<F>],
+        holes: &[Vec<Point2D<F>>],
+    ) -> IcedDrawable<F> {
+        iced_drawable(|sink| {
+            self.gen
+                .polygon_with_holes_into(outer, holes, &self.options, sink)
+        })
+    }
+
+    pub fn multi_polygon<F: Trig + Float + FromPrimitive + MulAssign + Display>(
+        &self,
+        polygons: &[Vec<Vec<Point2D<F>>>],
+    ) -> IcedDrawable<F> {
+        iced_drawable(|sink| self.gen.multi_polygon_into(polygons, &self.options, sink))
     }
 
     pub fn arc<F: Trig + Float + FromPrimitive>(
@@ -333,12 +441,12 @@ impl IcedGenerator {
         height: F,
         start: F,
         stop: F,
-        closed: bool,
+        mode: ArcMode,
     ) -> IcedDrawable<F> {
-        let drawable = self
-            .gen
-            .arc(x, y, width, height, start, stop, closed, &self.options);
-        drawable.to_iced_drawable()
+        iced_drawable(|sink| {
+            self.gen
+                .arc_into(x, y, width, height, start, stop, mode, &self.options, sink)
+        })
     }
 
     pub fn bezier_quadratic<F: Trig + Float + FromPrimitive + MulAssign + Display>(
@@ -347,8 +455,10 @@ impl IcedGenerator {
         cp: Point2D<F>,
         end: Point2D<F>,
     ) -> IcedDrawable<F> {
-        let drawable = self.gen.bezier_quadratic(start, cp, end, &self.options);
-        drawable.to_iced_drawable()
+        iced_drawable(|sink| {
+            self.gen
+                .bezier_quadratic_into(start, cp, end, &self.options, sink)
+        })
     }
 
     pub fn bezier_cubic<F: Trig + Float + FromPrimitive + MulAssign + Display>(
@@ -358,24 +468,24 @@ impl IcedGenerator {
         cp2: Point2D<F>,
         end: Point2D<F>,
     ) -> IcedDrawable<F> {
-        let drawable = self.gen.bezier_cubic(start, cp1, cp2, end, &self.options);
-        drawable.to_iced_drawable()
+        iced_drawable(|sink| {
+            self.gen
+                .bezier_cubic_into(start, cp1, cp2, end, &self.options, sink)
+        })
     }
 
     pub fn curve<F: Trig + Float + FromPrimitive + MulAssign + Display>(
         &self,
         points: &[Point2D<F>],
     ) -> IcedDrawable<F> {
-        let drawable = self.gen.curve(points, &self.options);
-        drawable.to_iced_drawable()
+        iced_drawable(|sink| self.gen.curve_into(points, &self.options, sink))
     }
 
     pub fn path<F: Trig + Float + FromPrimitive + MulAssign + Display>(
         &self,
         svg_path: String,
     ) -> IcedDrawable<F> {
-        let drawable = self.gen.path(svg_path, &self.options);
-        drawable.to_iced_drawable()
+        iced_drawable(|sink| self.gen.path_into(svg_path, &self.options, sink))
     }
 
     pub fn bez_path<F: Trig + Float + FromPrimitive + MulAssign + Display>(
@@ -383,9 +493,10 @@ impl IcedGenerator {
         path: Path,
     ) -> IcedDrawable<F> {
         let segments = path_to_svg_segments(&path);
-        self.gen
-            .path_from_segments(segments, &self.options)
-            .to_iced_drawable()
+        iced_drawable(|sink| {
+            self.gen
+                .path_from_segments_into(segments, &self.options, sink)
+        })
     }
 }
 
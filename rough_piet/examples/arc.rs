@@ -7,7 +7,7 @@ use piet::{Color, RenderContext};
 use piet_common::kurbo::Rect;
 use piet_common::Device;
 use rough_piet::KurboGenerator;
-use roughr::core::{FillStyle, OptionsBuilder};
+use roughr::core::{ArcMode, FillStyle, OptionsBuilder};
 
 const WIDTH: usize = 100;
 const HEIGHT: usize = 50;
@@ -35,7 +35,7 @@ fn main() {
         HEIGHT as f32,
         -f32::PI() / 2.0,
         f32::PI() / 2.0,
-        false,
+        ArcMode::Open,
     );
     let background_color = Color::from_hex_str("96C0B7").unwrap();
 
@@ -1,15 +1,16 @@
 use std::fmt::Display;
 use std::ops::MulAssign;
 
-use euclid::default::Point2D;
+use euclid::default::{Point2D, Vector2D};
 use euclid::Trig;
 use num_traits::{Float, FromPrimitive};
 use palette::rgb::Rgba;
 use palette::Srgba;
-use piet::kurbo::{self, BezPath, PathEl, Point};
+use piet::kurbo::{self, BezPath, Ellipse, PathEl, Point, Shape};
 use piet::{Color, LineJoin, RenderContext, StrokeStyle};
-use roughr::core::{Drawable, OpSet, OpSetType, OpType, Options};
+use roughr::core::{ArcMode, Drawable, FillRule, OpSet, OpSetStyle, OpSetType, Options, ShapeKind};
 use roughr::generator::Generator;
+use roughr::sink::{stream_ops, PathSink};
 use roughr::PathSegment;
 
 #[derive(Default)]
@@ -24,6 +25,7 @@ pub struct KurboOpset<F: Float + Trig> {
     pub ops: BezPath,
     pub size: Option<Point2D<F>>,
     pub path: Option<String>,
+    pub style: Option<OpSetStyle>,
 }
 
 pub trait ToKurboOpset<F: Float + Trig> {
@@ -36,13 +38,14 @@ impl<F: Float + Trig + FromPrimitive> ToKurboOpset<F> for OpSet<F> {
             op_set_type: self.op_set_type.clone(),
             size: self.size,
             path: self.path.clone(),
+            style: self.style.clone(),
             ops: opset_to_shape(&self),
         }
     }
 }
 
 pub struct KurboDrawable<F: Float + Trig> {
-    pub shape: String,
+    pub shape: ShapeKind<F>,
     pub options: Options,
     pub sets: Vec<KurboOpset<F>>,
 }
@@ -53,11 +56,24 @@ pub trait ToKurboDrawable<F: Float + Trig> {
 
 impl<F: Float + Trig + FromPrimitive> ToKurboDrawable<F> for Drawable<F> {
     fn to_kurbo_drawable(self) -> KurboDrawable<F> {
-        KurboDrawable {
-            shape: self.shape,
-            options: self.options,
-            sets: self.sets.into_iter().map(|s| s.to_kurbo_opset()).collect(),
-        }
+        kurbo_drawable(|sink| {
+            self.stream_into(sink);
+            self
+        })
+    }
+}
+
+/// Kurbo drawable of the drawable `draw` returns, built from the sets it streams.
+fn kurbo_drawable<F: Float + Trig>(
+    draw: impl FnOnce(&mut KurboOpsetSink<F>) -> Drawable<F>,
+) -> KurboDrawable<F> {
+    let mut sink = KurboOpsetSink { sets: vec![], set: None };
+    let drawable = draw(&mut sink);
+    sink.end_set();
+    KurboDrawable {
+        shape: drawable.shape,
+        options: drawable.options,
+        sets: sink.sets,
     }
 }
 
@@ -73,11 +89,9 @@ impl<F: Float + Trig> KurboDrawable<F> {
             match set.op_set_type {
                 OpSetType::Path => {
                     ctx.save().expect("Failed to save render context");
-                    if self.options.stroke_line_dash.is_some() {
-                        let stroke_line_dash =
-                            self.options.stroke_line_dash.clone().unwrap_or(Vec::new());
+                    if let Some(stroke_line_dash) = self.options.native_stroke_line_dash() {
                         let mut ss = StrokeStyle::new();
-                        ss.set_dash_pattern(stroke_line_dash.as_slice());
+                        ss.set_dash_pattern(stroke_line_dash);
                         ss.set_dash_offset(self.options.stroke_line_dash_offset.unwrap_or(1.0f64));
                         ss.set_line_cap(convert_line_cap_from_roughr_to_piet(
                             self.options.line_cap,
@@ -114,10 +128,14 @@ impl<F: Float + Trig> KurboDrawable<F> {
                 }
                 OpSetType::FillPath => {
                     ctx.save().expect("Failed to save render context");
-                    match self.shape.as_str() {
-                        "curve" | "polygon" | "path" => {
-                            let fill_color =
-                                self.options.fill.unwrap_or(Rgba::new(1.0, 1.0, 1.0, 1.0));
+                    match self.shape.fill_rule(&self.options) {
+                        FillRule::EvenOdd => {
+                            let fill_color = set
+                                .style
+                                .as_ref()
+                                .and_then(|style| style.color)
+                                .or(self.options.fill)
+                                .unwrap_or(Rgba::new(1.0, 1.0, 1.0, 1.0));
                             let rgb: (f32, f32, f32, f32) = fill_color.into_components();
                             ctx.fill_even_odd(
                                 set.ops.clone(),
@@ -129,9 +147,13 @@ impl<F: Float + Trig> KurboDrawable<F> {
                                 ),
                             )
                         }
-                        _ => {
-                            let fill_color =
-                                self.options.fill.unwrap_or(Rgba::new(1.0, 1.0, 1.0, 1.0));
+                        FillRule::NonZero => {
+                            let fill_color = set
+                                .style
+                                .as_ref()
+                                .and_then(|style| style.color)
+                                .or(self.options.fill)
+                                .unwrap_or(Rgba::new(1.0, 1.0, 1.0, 1.0));
                             let rgb: (f32, f32, f32, f32) = fill_color.into_components();
                             ctx.fill(
                                 set.ops.clone(),
@@ -151,23 +173,34 @@ impl<F: Float + Trig> KurboDrawable<F> {
                     if fweight < 0.0 {
                         fweight = self.options.stroke_width.unwrap_or(1.0) / 2.0;
                     }
+                    let style = set.style.clone().unwrap_or_default();
+                    if let Some(weight) = style.weight {
+                        fweight = weight;
+                    }
                     ctx.save().expect("Failed to save render context");
 
-                    if self.options.fill_line_dash.is_some() {
-                        let fill_line_dash =
-                            self.options.fill_line_dash.clone().unwrap_or_default();
+                    if style.line_dash.is_some() || self.options.fill_line_dash.is_some() {
+                        let fill_line_dash = style
+                            .line_dash
+                            .or_else(|| self.options.fill_line_dash.clone())
+                            .unwrap_or_default();
                         let mut ss = StrokeStyle::new();
                         ss.set_dash_pattern(fill_line_dash.as_slice());
-                        ss.set_dash_offset(self.options.fill_line_dash_offset.unwrap_or(0.0f64));
+                        ss.set_dash_offset(
+                            style
+                                .line_dash_offset
+                                .or(self.options.fill_line_dash_offset)
+                                .unwrap_or(0.0f64),
+                        );
                         ss.set_line_cap(convert_line_cap_from_roughr_to_piet(
                             self.options.line_cap,
                         ));
                         ss.set_line_join(convert_line_join_from_roughr_to_piet(
                             self.options.line_join,
                         ));
-                        let fill_color = self
-                            .options
-                            .fill
+                        let fill_color = style
+                            .color
+                            .or(self.options.fill)
                             .unwrap_or_else(|| Rgba::new(1.0, 1.0, 1.0, 1.0));
                         let rgb: (f32, f32, f32, f32) = fill_color.into_components();
                         ctx.stroke_styled(
@@ -177,9 +210,9 @@ impl<F: Float + Trig> KurboDrawable<F> {
                             &ss,
                         );
                     } else {
-                        let fill_color = self
-                            .options
-                            .fill
+                        let fill_color = style
+                            .color
+                            .or(self.options.fill)
                             .unwrap_or_else(|| Rgba::new(1.0, 1.0, 1.0, 1.0));
                         let rgb: (f32, f32, f32, f32) = fill_color.into_components();
                         ctx.stroke(
@@ -195,37 +228,108 @@ impl<F: Float + Trig> KurboDrawable<F> {
     }
 }
 
-fn opset_to_shape<F: Trig + Float + FromPrimitive>(op_set: &OpSet<F>) -> BezPath {
-    let mut path: BezPath = BezPath::new();
-    for item in op_set.ops.iter() {
-        match item.op {
-            OpType::Move => path.extend([PathEl::MoveTo(Point::new(
-                item.data[0].to_f64().unwrap(),
-                item.data[1].to_f64().unwrap(),
-            ))]),
-            OpType::BCurveTo => path.extend([PathEl::CurveTo(
-                Point::new(
-                    item.data[0].to_f64().unwrap(),
-                    item.data[1].to_f64().unwrap(),
-                ),
-                Point::new(
-                    item.data[2].to_f64().unwrap(),
-                    item.data[3].to_f64().unwrap(),
-                ),
-                Point::new(
-                    item.data[4].to_f64().unwrap(),
-                    item.data[5].to_f64().unwrap(),
-                ),
-            )]),
-            OpType::LineTo => {
-                path.extend([PathEl::LineTo(Point::new(
-                    item.data[0].to_f64().unwrap(),
-                    item.data[1].to_f64().unwrap(),
-                ))]);
-            }
+/// [`PathSink`] building a kurbo [`BezPath`].
+#[derive(Default)]
+pub struct BezPathSink {
+    pub path: BezPath,
+}
+
+impl<F: Float + Trig> PathSink<F> for BezPathSink {
+    fn move_to(&mut self, to: Point2D<F>) {
+        self.path.move_to(kurbo_point(to));
+    }
+
+    fn line_to(&mut self, to: Point2D<F>) {
+        self.path.line_to(kurbo_point(to));
+    }
+
+    fn curve_to(&mut self, cp1: Point2D<F>, cp2: Point2D<F>, to: Point2D<F>) {
+        self.path
+            .curve_to(kurbo_point(cp1), kurbo_point(cp2), kurbo_point(to));
+    }
+
+    fn quad_to(&mut self, _from: Point2D<F>, cp: Point2D<F>, to: Point2D<F>) {
+        self.path.quad_to(kurbo_point(cp), kurbo_point(to));
+    }
+
+    fn close(&mut self) {
+        self.path.close_path();
+    }
+
+    fn ellipse(&mut self, center: Point2D<F>, radii: Vector2D<F>) {
+        let radii = kurbo_point(radii.to_point()).to_vec2();
+        self.path
+            .extend(Ellipse::new(kurbo_point(center), radii, 0.0).path_elements(0.1));
+    }
+}
+
+fn kurbo_point<F: Float>(p: Point2D<F>) -> Point {
+    Point::new(p.x.to_f64().unwrap(), p.y.to_f64().unwrap())
+}
+
+/// [`PathSink`] building a [`KurboOpset`] for every set streamed into it. Ops streamed
+/// outside of a set go into an [`OpSetType::Path`] set of their own.
+struct KurboOpsetSink<F: Float + Trig> {
+    sets: Vec<KurboOpset<F>>,
+    set: Option<(OpSetType, Option<OpSetStyle>, BezPathSink)>,
+}
+
+impl<F: Float + Trig> KurboOpsetSink<F> {
+    fn path(&mut self) -> &mut BezPathSink {
+        &mut self
+            .set
+            .get_or_insert_with(|| (OpSetType::Path, None, BezPathSink::default()))
+            .2
+    }
+}
+
+impl<F: Float + Trig> PathSink<F> for KurboOpsetSink<F> {
+    fn begin_set(&mut self, op_set_type: &OpSetType, style: Option<&OpSetStyle>) {
+        self.end_set();
+        self.set = Some((op_set_type.clone(), style.cloned(), BezPathSink::default()));
+    }
+
+    fn move_to(&mut self, to: Point2D<F>) {
+        self.path().move_to(to);
+    }
+
+    fn line_to(&mut self, to: Point2D<F>) {
+        self.path().line_to(to);
+    }
+
+    fn curve_to(&mut self, cp1: Point2D<F>, cp2: Point2D<F>, to: Point2D<F>) {
+        self.path().curve_to(cp1, cp2, to);
+    }
+
+    fn quad_to(&mut self, from: Point2D<F>, cp: Point2D<F>, to: Point2D<F>) {
+        self.path().quad_to(from, cp, to);
+    }
+
+    fn close(&mut self) {
+        PathSink::<F>::close(self.path());
+    }
+
+    fn ellipse(&mut self, center: Point2D<F>, radii: Vector2D<F>) {
+        self.path().ellipse(center, radii);
+    }
+
+    fn end_set(&mut self) {
+        if let Some((op_set_type, style, sink)) = self.set.take() {
+            self.sets.push(KurboOpset {
+                op_set_type,
+                ops: sink.path,
+                size: None,
+                path: None,
+                style,
+            });
         }
     }
-    path
+}
+
+fn opset_to_shape<F: Trig + Float + FromPrimitive>(op_set: &OpSet<F>) -> BezPath {
+    let mut sink = BezPathSink::default();
+    stream_ops(&op_set.ops, &mut sink);
+    sink.path
 }
 
 impl KurboGenerator {
@@ -236,8 +340,7 @@ impl KurboGenerator {
         x2: F,
         y2: F,
     ) -> KurboDrawable<F> {
-        let drawable = self.gen.line(x1, y1, x2, y2, &self.options);
-        drawable.to_kurbo_drawable()
+        kurbo_drawable(|sink| self.gen.line_into(x1, y1, x2, y2, &self.options, sink))
     }
 
     pub fn rectangle<F: Trig + Float + FromPrimitive>(
@@ -247,8 +350,10 @@ impl KurboGenerator {
         width: F,
         height: F,
     ) -> KurboDrawable<F> {
-        let drawable = self.gen.rectangle(x, y, width, height, &self.options);
-        drawable.to_kurbo_drawable()
+        kurbo_drawable(|sink| {
+            self.gen
+                .rectangle_into(x, y, width, height, &self.options, sink)
+        })
     }
 
     pub fn ellipse<F: Trig + Float + FromPrimitive>(
@@ -258,8 +363,10 @@ impl KurboGenerator {
         width: F,
         height: F,
     ) -> KurboDrawable<F> {
-        let drawable = self.gen.ellipse(x, y, width, height, &self.options);
-        drawable.to_kurbo_drawable()
+        kurbo_drawable(|sink| {
+            self.gen
+                .ellipse_into(x, y, width, height, &self.options, sink)
+        })
     }
 
     pub fn circle<F: Trig + Float + FromPrimitive>(
@@ -268,8 +375,7 @@ impl KurboGenerator {
         y: F,
         diameter: F,
     ) -> KurboDrawable<F> {
-        let drawable = self.gen.circle(x, y, diameter, &self.options);
-        drawable.to_kurbo_drawable()
+        kurbo_drawable(|sink| self.gen.circle_into(x, y, diameter, &self.options, sink))
     }
 
     pub fn linear_path<F: Trig + Float + FromPrimitive>(
@@ -277,16 +383,35 @@ impl KurboGenerator {
         points: &[Point2D<F>],
         close: bool,
     ) -> KurboDrawable<F> {
-        let drawable = self.gen.linear_path(points, close, &self.options);
-        drawable.to_kurbo_drawable()
+        kurbo_drawable(|sink| {
+            self.gen
+                .linear_path_into(points, close, &self.options, sink)
+        })
     }
 
     pub fn polygon<F: Trig + Float + FromPrimitive + MulAssign + Display>(
         &self,
         points: &[Point2D<F>],
     ) -> KurboDrawable<F> {
-        let drawable = self.gen.polygon(points, &self.options);
-        drawable.to_kurbo_drawable()
+        kurbo_drawable(|sink| self.gen.polygon_into(points, &self.options, sink))
+    }
+
+    pub fn polygon_with_holes<F: Trig + Float + FromPrimitive + MulAssign + Display>(
+        &self,
+        outer: &[Point2D<F>],
+        holes: &[Vec<Point2D<F>>],
+    ) -> KurboDrawable<F> {
+        kurbo_drawable(|sink| {
+            self.gen
+                .polygon_with_holes_into(outer, holes, &self.options, sink)
+        })
+    }
+
+    pub fn multi_polygon<F: Trig + Float + FromPrimitive + MulAssign + Display>(
+        &self,
+        polygons: &[Vec<Vec<Point2D<F>>>],
+    ) -> KurboDrawable<F> {
+        kurbo_drawable(|sink| self.gen.multi_polygon_into(polygons, &self.options, sink))
     }
 
     pub fn arc<F: Trig + Float + FromPrimitive>(
@@ -297,12 +422,12 @@ impl KurboGenerator {
         height: F,
         start: F,
         stop: F,
-        closed: bool,
+        mode: ArcMode,
     ) -> KurboDrawable<F> {
-        let drawable = self
-            .gen
-            .arc(x, y, width, height, start, stop, closed, &self.options);
-        drawable.to_kurbo_drawable()
+        kurbo_drawable(|sink| {
+            self.gen
+                .arc_into(x, y, width, height, start, stop, mode, &self.options, sink)
+        })
     }
 
     pub fn bezier_quadratic<F: Trig + Float + FromPrimitive + MulAssign + Display>(
@@ -311,8 +436,10 @@ impl KurboGenerator {
         cp: Point2D<F>,
         end: Point2D<F>,
     ) -> KurboDrawable<F> {
-        let drawable = self.gen.bezier_quadratic(start, cp, end, &self.options);
-        drawable.to_kurbo_drawable()
+        kurbo_drawable(|sink| {
+            self.gen
+                .bezier_quadratic_into(start, cp, end, &self.options, sink)
+        })
     }
 
     pub fn bezier_cubic<F: Trig + Float + FromPrimitive + MulAssign + Display>(
@@ -322,24 +449,24 @@ impl KurboGenerator {
         cp2: Point2D<F>,
         end: Point2D<F>,
     ) -> KurboDrawable<F> {
-        let drawable = self.gen.bezier_cubic(start, cp1, cp2, end, &self.options);
-        drawable.to_kurbo_drawable()
+        kurbo_drawable(|sink| {
+            self.gen
+                .bezier_cubic_into(start, cp1, cp2, end, &self.options, sink)
+        })
     }
 
     pub fn curve<F: Trig + Float + FromPrimitive + MulAssign + Display>(
         &self,
         points: &[Point2D<F>],
     ) -> KurboDrawable<F> {
-        let drawable = self.gen.curve(points, &self.options);
-        drawable.to_kurbo_drawable()
+        kurbo_drawable(|sink| self.gen.curve_into(points, &self.options, sink))
     }
 
     pub fn path<F: Trig + Float + FromPrimitive + MulAssign + Display>(
         &self,
         svg_path: String,
     ) -> KurboDrawable<F> {
-        let drawable = self.gen.path(svg_path, &self.options);
-        drawable.to_kurbo_drawable()
+        kurbo_drawable(|sink| self.gen.path_into(svg_path, &self.options, sink))
     }
 
     pub fn bez_path<F: Trig + Float + FromPrimitive + MulAssign + Display>(
@@ -347,9 +474,10 @@ impl KurboGenerator {
         bezier_path: BezPath,
     ) -> KurboDrawable<F> {
         let segments = bezpath_to_svg_segments(&bezier_path);
-        self.gen
-            .path_from_segments(segments, &self.options)
-            .to_kurbo_drawable()
+        kurbo_drawable(|sink| {
+            self.gen
+                .path_from_segments_into(segments, &self.options, sink)
+        })
     }
 }
 
@@ -1,12 +1,13 @@
 use std::fmt::Display;
 use std::ops::MulAssign;
 
-use euclid::default::Point2D;
+use euclid::default::{Point2D, Vector2D};
 use euclid::Trig;
 use num_traits::{Float, FromPrimitive};
 use palette::Srgba;
-use roughr::core::{Drawable, OpSet, OpSetType, OpType, Options};
+use roughr::core::{ArcMode, Drawable, OpSet, OpSetStyle, OpSetType, Options, ShapeKind};
 use roughr::generator::Generator;
+use roughr::sink::{stream_ops, PathSink};
 use tiny_skia::{
     FillRule,
     LineCap,
@@ -15,6 +16,7 @@ use tiny_skia::{
     Path,
     PathBuilder,
     PixmapMut,
+    Rect,
     Stroke,
     StrokeDash,
     Transform,
@@ -32,6 +34,7 @@ pub struct SkiaOpset<F: Float + Trig> {
     pub ops: Path,
     pub size: Option<Point2D<F>>,
     pub path: Option<String>,
+    pub style: Option<OpSetStyle>,
 }
 
 pub trait ToSkiaOpset<F: Float + Trig> {
@@ -44,13 +47,14 @@ impl<F: Float + Trig + FromPrimitive> ToSkiaOpset<F> for OpSet<F> {
             op_set_type: self.op_set_type.clone(),
             size: self.size,
             path: self.path.clone(),
+            style: self.style.clone(),
             ops: opset_to_shape(&self),
         }
     }
 }
 
 pub struct SkiaDrawable<F: Float + Trig> {
-    pub shape: String,
+    pub shape: ShapeKind<F>,
     pub options: Options,
     pub sets: Vec<SkiaOpset<F>>,
 }
@@ -61,11 +65,24 @@ pub trait ToSkiaDrawable<F: Float + Trig> {
 
 impl<F: Float + Trig + FromPrimitive> ToSkiaDrawable<F> for Drawable<F> {
     fn to_skia_drawable(self) -> SkiaDrawable<F> {
-        SkiaDrawable {
-            shape: self.shape,
-            options: self.options,
-            sets: self.sets.into_iter().map(|s| s.to_skia_opset()).collect(),
-        }
+        skia_drawable(|sink| {
+            self.stream_into(sink);
+            self
+        })
+    }
+}
+
+/// Skia drawable of the drawable `draw` returns, built from the sets it streams.
+fn skia_drawable<F: Float + Trig>(
+    draw: impl FnOnce(&mut SkiaOpsetSink<F>) -> Drawable<F>,
+) -> SkiaDrawable<F> {
+    let mut sink = SkiaOpsetSink { sets: vec![], set: None };
+    let drawable = draw(&mut sink);
+    sink.end_set();
+    SkiaDrawable {
+        shape: drawable.shape,
+        options: drawable.options,
+        sets: sink.sets,
     }
 }
 
@@ -80,7 +97,7 @@ impl<F: Float + Trig> SkiaDrawable<F> {
         for set in self.sets.iter() {
             match set.op_set_type {
                 OpSetType::Path => {
-                    if self.options.stroke_line_dash.is_some() {
+                    if let Some(stroke_line_dash) = self.options.native_stroke_line_dash() {
                         let mut stroke = Stroke {
                             width: self.options.stroke_width.unwrap_or(1.0),
                             line_cap: convert_line_cap_from_roughr_to_piet(self.options.line_cap),
@@ -89,14 +106,7 @@ impl<F: Float + Trig> SkiaDrawable<F> {
                             ),
                             ..Stroke::default()
                         };
-                        let stroke_line_dash = self
-                            .options
-                            .stroke_line_dash
-                            .clone()
-                            .unwrap_or(Vec::new())
-                            .iter()
-                            .map(|&a| a as f32)
-                            .collect();
+                        let stroke_line_dash = stroke_line_dash.iter().map(|&a| a as f32).collect();
 
                         stroke.dash = StrokeDash::new(
                             stroke_line_dash,
@@ -144,9 +154,11 @@ impl<F: Float + Trig> SkiaDrawable<F> {
                     }
                 }
                 OpSetType::FillPath => {
-                    let fill_color = self
-                        .options
-                        .fill
+                    let fill_color = set
+                        .style
+                        .as_ref()
+                        .and_then(|style| style.color)
+                        .or(self.options.fill)
                         .unwrap_or(Srgba::from_components((1.0, 1.0, 1.0, 1.0)));
                     let fill_color_components: (u8, u8, u8, u8) =
                         fill_color.into_format().into_components();
@@ -159,44 +171,29 @@ impl<F: Float + Trig> SkiaDrawable<F> {
                         fill_color_components.3,
                     );
                     paint.anti_alias = true;
-                    match self.shape.as_str() {
-                        "curve" | "polygon" | "path" => {
-                            ctx.fill_path(
-                                &set.ops,
-                                &paint,
-                                FillRule::EvenOdd,
-                                Transform::identity(),
-                                None,
-                            );
-                        }
-                        _ => {
-                            ctx.fill_path(
-                                &set.ops,
-                                &paint,
-                                FillRule::Winding,
-                                Transform::identity(),
-                                None,
-                            );
-                        }
-                    }
+                    let fill_rule = match self.shape.fill_rule(&self.options) {
+                        roughr::core::FillRule::EvenOdd => FillRule::EvenOdd,
+                        roughr::core::FillRule::NonZero => FillRule::Winding,
+                    };
+                    ctx.fill_path(&set.ops, &paint, fill_rule, Transform::identity(), None);
                 }
                 OpSetType::FillSketch => {
                     let mut fweight = self.options.fill_weight.unwrap_or_default();
                     if fweight < 0.0 {
                         fweight = self.options.stroke_width.unwrap_or(1.0) / 2.0;
                     }
+                    let style = set.style.clone().unwrap_or_default();
 
-                    if self.options.fill_line_dash.is_some() {
+                    if style.line_dash.is_some() || self.options.fill_line_dash.is_some() {
                         let mut stroke = Stroke::default();
-                        stroke.width = self.options.fill_weight.unwrap_or(1.0);
+                        stroke.width = style.weight.or(self.options.fill_weight).unwrap_or(1.0);
                         stroke.line_cap =
                             convert_line_cap_from_roughr_to_piet(self.options.line_cap);
                         stroke.line_join =
                             convert_line_join_from_roughr_to_piet(self.options.line_join);
-                        let fill_line_dash = self
-                            .options
-                            .fill_line_dash
-                            .clone()
+                        let fill_line_dash = style
+                            .line_dash
+                            .or_else(|| self.options.fill_line_dash.clone())
                             .unwrap_or(Vec::new())
                             .iter()
                             .map(|&a| a as f32)
@@ -204,12 +201,15 @@ impl<F: Float + Trig> SkiaDrawable<F> {
 
                         stroke.dash = StrokeDash::new(
                             fill_line_dash,
-                            self.options.fill_line_dash_offset.unwrap_or(1.0f64) as f32,
+                            style
+                                .line_dash_offset
+                                .or(self.options.fill_line_dash_offset)
+                                .unwrap_or(1.0f64) as f32,
                         );
 
-                        let fill_color = self
-                            .options
-                            .fill
+                        let fill_color = style
+                            .color
+                            .or(self.options.fill)
                             .unwrap_or(Srgba::from_components((1.0, 1.0, 1.0, 1.0)));
                         let fill_color_components: (u8, u8, u8, u8) =
                             fill_color.into_format().into_components();
@@ -225,15 +225,15 @@ impl<F: Float + Trig> SkiaDrawable<F> {
                         ctx.stroke_path(&set.ops, &paint, &stroke, Transform::identity(), None);
                     } else {
                         let mut stroke = Stroke::default();
-                        stroke.width = self.options.fill_weight.unwrap_or(1.0);
+                        stroke.width = style.weight.or(self.options.fill_weight).unwrap_or(1.0);
                         stroke.line_cap =
                             convert_line_cap_from_roughr_to_piet(self.options.line_cap);
                         stroke.line_join =
                             convert_line_join_from_roughr_to_piet(self.options.line_join);
 
-                        let fill_color = self
-                            .options
-                            .fill
+                        let fill_color = style
+                            .color
+                            .or(self.options.fill)
                             .unwrap_or(Srgba::from_components((1.0, 1.0, 1.0, 1.0)));
                         let fill_color_components: (u8, u8, u8, u8) =
                             fill_color.into_format().into_components();
@@ -254,31 +254,129 @@ impl<F: Float + Trig> SkiaDrawable<F> {
     }
 }
 
-fn opset_to_shape<F: Trig + Float + FromPrimitive>(op_set: &OpSet<F>) -> Path {
-    let mut path: PathBuilder = PathBuilder::new();
-    for item in op_set.ops.iter() {
-        match item.op {
-            OpType::Move => path.move_to(
-                item.data[0].to_f32().unwrap(),
-                item.data[1].to_f32().unwrap(),
-            ),
-            OpType::BCurveTo => path.cubic_to(
-                item.data[0].to_f32().unwrap(),
-                item.data[1].to_f32().unwrap(),
-                item.data[2].to_f32().unwrap(),
-                item.data[3].to_f32().unwrap(),
-                item.data[4].to_f32().unwrap(),
-                item.data[5].to_f32().unwrap(),
-            ),
-            OpType::LineTo => {
-                path.line_to(
-                    item.data[0].to_f32().unwrap(),
-                    item.data[1].to_f32().unwrap(),
-                );
+/// [`PathSink`] building a tiny-skia path.
+#[derive(Default)]
+pub struct SkiaPathSink {
+    pub builder: PathBuilder,
+}
+
+impl SkiaPathSink {
+    /// The built path, or `None` if nothing was drawn.
+    pub fn finish(self) -> Option<Path> {
+        self.builder.finish()
+    }
+}
+
+impl<F: Float + Trig> PathSink<F> for SkiaPathSink {
+    fn move_to(&mut self, to: Point2D<F>) {
+        self.builder
+            .move_to(to.x.to_f32().unwrap(), to.y.to_f32().unwrap());
+    }
+
+    fn line_to(&mut self, to: Point2D<F>) {
+        self.builder
+            .line_to(to.x.to_f32().unwrap(), to.y.to_f32().unwrap());
+    }
+
+    fn curve_to(&mut self, cp1: Point2D<F>, cp2: Point2D<F>, to: Point2D<F>) {
+        self.builder.cubic_to(
+            cp1.x.to_f32().unwrap(),
+            cp1.y.to_f32().unwrap(),
+            cp2.x.to_f32().unwrap(),
+            cp2.y.to_f32().unwrap(),
+            to.x.to_f32().unwrap(),
+            to.y.to_f32().unwrap(),
+        );
+    }
+
+    fn quad_to(&mut self, _from: Point2D<F>, cp: Point2D<F>, to: Point2D<F>) {
+        self.builder.quad_to(
+            cp.x.to_f32().unwrap(),
+            cp.y.to_f32().unwrap(),
+            to.x.to_f32().unwrap(),
+            to.y.to_f32().unwrap(),
+        );
+    }
+
+    fn close(&mut self) {
+        self.builder.close();
+    }
+
+    fn ellipse(&mut self, center: Point2D<F>, radii: Vector2D<F>) {
+        let cx = center.x.to_f32().unwrap();
+        let cy = center.y.to_f32().unwrap();
+        let rx = radii.x.to_f32().unwrap();
+        let ry = radii.y.to_f32().unwrap();
+        // degenerate ellipses have no outline to add
+        if let Some(oval) = Rect::from_ltrb(cx - rx, cy - ry, cx + rx, cy + ry) {
+            self.builder.push_oval(oval);
+        }
+    }
+}
+
+/// [`PathSink`] building a [`SkiaOpset`] for every set streamed into it. Ops streamed
+/// outside of a set go into an [`OpSetType::Path`] set of their own, and sets without any
+/// ops are dropped, as tiny-skia has no empty paths.
+struct SkiaOpsetSink<F: Float + Trig> {
+    sets: Vec<SkiaOpset<F>>,
+    set: Option<(OpSetType, Option<OpSetStyle>, SkiaPathSink)>,
+}
+
+impl<F: Float + Trig> SkiaOpsetSink<F> {
+    fn path(&mut self) -> &mut SkiaPathSink {
+        &mut self
+            .set
+            .get_or_insert_with(|| (OpSetType::Path, None, SkiaPathSink::default()))
+            .2
+    }
+}
+
+impl<F: Float + Trig> PathSink<F> for SkiaOpsetSink<F> {
+    fn begin_set(&mut self, op_set_type: &OpSetType, style: Option<&OpSetStyle>) {
+        self.end_set();
+        self.set = Some((op_set_type.clone(), style.cloned(), SkiaPathSink::default()));
+    }
+
+    fn move_to(&mut self, to: Point2D<F>) {
+        self.path().move_to(to);
+    }
+
+    fn line_to(&mut self, to: Point2D<F>) {
+        self.path().line_to(to);
+    }
+
+    fn curve_to(&mut self, cp1: Point2D<F>, cp2: Point2D<F>, to: Point2D<F>) {
+        self.path().curve_to(cp1, cp2, to);
+    }
+
+    fn quad_to(&mut self, from: Point2D<F>, cp: Point2D<F>, to: Point2D<F>) {
+        self.path().quad_to(from, cp, to);
+    }
+
+    fn close(&mut self) {
+        PathSink::<F>::close(self.path());
+    }
+
+    fn ellipse(&mut self, center: Point2D<F>, radii: Vector2D<F>) {
+        self.path().ellipse(center, radii);
+    }
+
+    fn end_set(&mut self) {
+        if let Some((op_set_type, style, path)) = self.set.take() {
+            if let Some(ops) = path.finish() {
+                self.sets
+                    .push(SkiaOpset { op_set_type, ops, size: None, path: None, style });
             }
         }
     }
-    path.finish().unwrap()
+}
+
+fn opset_to_shape<F: Trig + Float + FromPrimitive>(op_set: &OpSet<F>) -> Path {
+    let mut sink = SkiaPathSink {
+        builder: PathBuilder::with_capacity(op_set.ops.len(), op_set.ops.len() * 3),
+    };
+    stream_ops(&op_set.ops, &mut sink);
+    sink.finish().unwrap()
 }
 
 impl SkiaGenerator {
@@ -289,8 +387,7 @@ impl SkiaGenerator {
         x2: F,
         y2: F,
     ) -> SkiaDrawable<F> {
-        let drawable = self.gen.line(x1, y1, x2, y2, &self.options);
-        drawable.to_skia_drawable()
+        skia_drawable(|sink| self.gen.line_into(x1, y1, x2, y2, &self.options, sink))
     }
 
     pub fn rectangle<F: Trig + Float + FromPrimitive>(
@@ -300,8 +397,10 @@ impl SkiaGenerator {
         width: F,
         height: F,
     ) -> SkiaDrawable<F> {
-        let drawable = self.gen.rectangle(x, y, width, height, &self.options);
-        drawable.to_skia_drawable()
+        skia_drawable(|sink| {
+            self.gen
+                .rectangle_into(x, y, width, height, &self.options, sink)
+        })
     }
 
     pub fn ellipse<F: Trig + Float + FromPrimitive>(
@@ -311,8 +410,10 @@ impl SkiaGenerator {
         width: F,
         height: F,
     ) -> SkiaDrawable<F> {
-        let drawable = self.gen.ellipse(x, y, width, height, &self.options);
-        drawable.to_skia_drawable()
+        skia_drawable(|sink| {
+            self.gen
+                .ellipse_into(x, y, width, height, &self.options, sink)
+        })
     }
 
     pub fn circle<F: Trig + Float + FromPrimitive>(
@@ -321,8 +422,7 @@ impl SkiaGenerator {
         y: F,
         diameter: F,
     ) -> SkiaDrawable<F> {
-        let drawable = self.gen.circle(x, y, diameter, &self.options);
-        drawable.to_skia_drawable()
+        skia_drawable(|sink| self.gen.circle_into(x, y, diameter, &self.options, sink))
     }
 
     pub fn linear_path<F: Trig + Float + FromPrimitive>(
@@ -330,16 +430,35 @@ impl SkiaGenerator {
         points: &[Point2D<F>],
         close: bool,
     ) -> SkiaDrawable<F> {
-        let drawable = self.gen.linear_path(points, close, &self.options);
-        drawable.to_skia_drawable()
+        skia_drawable(|sink| {
+            self.gen
+                .linear_path_into(points, close, &self.options, sink)
+        })
     }
 
     pub fn polygon<F: Trig + Float + FromPrimitive + MulAssign + Display>(
         &self,
         points: &[Point2D<F>],
     ) -> SkiaDrawable<F> {
-        let drawable = self.gen.polygon(points, &self.options);
-        drawable.to_skia_drawable()
+        skia_drawable(|sink| self.gen.polygon_into(points, &self.options, sink))
+    }
+
+    pub fn polygon_with_holes<F: Trig + Float + FromPrimitive + MulAssign + Display>(
+        &self,
+        outer: &[Point2D<F>],
+        holes: &[Vec<Point2D<F>>],
+    ) -> SkiaDrawable<F> {
+        skia_drawable(|sink| {
+            self.gen
+                .polygon_with_holes_into(outer, holes, &self.options, sink)
+        })
+    }
+
+    pub fn multi_polygon<F: Trig + Float + FromPrimitive + MulAssign + Display>(
+        &self,
+        polygons: &[Vec<Vec<Point2D<F>>>],
+    ) -> SkiaDrawable<F> {
+        skia_drawable(|sink| self.gen.multi_polygon_into(polygons, &self.options, sink))
     }
 
     pub fn arc<F: Trig + Float + FromPrimitive>(
@@ -350,12 +469,12 @@ impl SkiaGenerator {
         height: F,
         start: F,
         stop: F,
-        closed: bool,
+        mode: ArcMode,
     ) -> SkiaDrawable<F> {
-        let drawable = self
-            .gen
-            .arc(x, y, width, height, start, stop, closed, &self.options);
-        drawable.to_skia_drawable()
+        skia_drawable(|sink| {
+            self.gen
+                .arc_into(x, y, width, height, start, stop, mode, &self.options, sink)
+        })
     }
 
     pub fn bezier_quadratic<F: Trig + Float + FromPrimitive + MulAssign + Display>(
@@ -364,8 +483,10 @@ impl SkiaGenerator {
         cp: Point2D<F>,
         end: Point2D<F>,
     ) -> SkiaDrawable<F> {
-        let drawable = self.gen.bezier_quadratic(start, cp, end, &self.options);
-        drawable.to_skia_drawable()
+        skia_drawable(|sink| {
+            self.gen
+                .bezier_quadratic_into(start, cp, end, &self.options, sink)
+        })
     }
 
     pub fn bezier_cubic<F: Trig + Float + FromPrimitive + MulAssign + Display>(
@@ -375,24 +496,24 @@ impl SkiaGenerator {
         cp2: Point2D<F>,
         end: Point2D<F>,
     ) -> SkiaDrawable<F> {
-        let drawable = self.gen.bezier_cubic(start, cp1, cp2, end, &self.options);
-        drawable.to_skia_drawable()
+        skia_drawable(|sink| {
+            self.gen
+                .bezier_cubic_into(start, cp1, cp2, end, &self.options, sink)
+        })
     }
 
     pub fn curve<F: Trig + Float + FromPrimitive + MulAssign + Display>(
         &self,
         points: &[Point2D<F>],
     ) -> SkiaDrawable<F> {
-        let drawable = self.gen.curve(points, &self.options);
-        drawable.to_skia_drawable()
+        skia_drawable(|sink| self.gen.curve_into(points, &self.options, sink))
     }
 
     pub fn path<F: Trig + Float + FromPrimitive + MulAssign + Display>(
         &self,
         svg_path: String,
     ) -> SkiaDrawable<F> {
-        let drawable = self.gen.path(svg_path, &self.options);
-        drawable.to_skia_drawable()
+        skia_drawable(|sink| self.gen.path_into(svg_path, &self.options, sink))
     }
 }
 
@@ -1,15 +1,16 @@
 use std::fmt::Display;
 use std::ops::MulAssign;
 
-use euclid::default::Point2D;
+use euclid::default::{Point2D, Vector2D};
 use euclid::Trig;
 use num_traits::{Float, FromPrimitive};
 use palette::rgb::Rgba;
 use palette::Srgba;
-use roughr::core::{Drawable, OpSet, OpSetType, OpType, Options};
+use roughr::core::{ArcMode, Drawable, OpSet, OpSetStyle, OpSetType, Options, ShapeKind};
 use roughr::generator::Generator;
+use roughr::sink::{stream_ops, PathSink};
 use roughr::PathSegment;
-use vello::kurbo::{Affine, BezPath, Cap, Join, PathEl, Point, Stroke};
+use vello::kurbo::{Affine, BezPath, Cap, Ellipse, Join, PathEl, Point, Shape, Stroke};
 use vello::peniko::{Brush, Color, Fill};
 use vello::Scene;
 
@@ -25,6 +26,7 @@ pub struct VelloOpset<F: Float + Trig> {
     pub ops: BezPath,
     pub size: Option<Point2D<F>>,
     pub path: Option<String>,
+    pub style: Option<OpSetStyle>,
 }
 
 pub trait ToVelloOpset<F: Float + Trig> {
@@ -37,13 +39,14 @@ impl<F: Float + Trig + FromPrimitive> ToVelloOpset<F> for OpSet<F> {
             op_set_type: self.op_set_type.clone(),
             size: self.size,
             path: self.path.clone(),
+            style: self.style.clone(),
             ops: opset_to_shape(&self),
         }
     }
 }
 
 pub struct VelloDrawable<F: Float + Trig> {
-    pub shape: String,
+    pub shape: ShapeKind<F>,
     pub options: Options,
     pub sets: Vec<VelloOpset<F>>,
 }
@@ -54,11 +57,24 @@ pub trait ToVelloDrawable<F: Float + Trig> {
 
 impl<F: Float + Trig + FromPrimitive> ToVelloDrawable<F> for Drawable<F> {
     fn to_vello_drawable(self) -> VelloDrawable<F> {
-        VelloDrawable {
-            shape: self.shape,
-            options: self.options,
-            sets: self.sets.into_iter().map(|s| s.to_vello_opset()).collect(),
-        }
+        vello_drawable(|sink| {
+            self.stream_into(sink);
+            self
+        })
+    }
+}
+
+/// Vello drawable of the drawable `draw` returns, built from the sets it streams.
+fn vello_drawable<F: Float + Trig>(
+    draw: impl FnOnce(&mut VelloOpsetSink<F>) -> Drawable<F>,
+) -> VelloDrawable<F> {
+    let mut sink = VelloOpsetSink { sets: vec![], set: None };
+    let drawable = draw(&mut sink);
+    sink.end_set();
+    VelloDrawable {
+        shape: drawable.shape,
+        options: drawable.options,
+        sets: sink.sets,
     }
 }
 
@@ -77,7 +93,7 @@ impl<F: Float + Trig> VelloDrawable<F> {
                     let mut stroke = Stroke::new(self.options.stroke_width.unwrap_or(1.0) as f64);
 
                     // Set dash pattern if available
-                    if let Some(ref dash_pattern) = self.options.stroke_line_dash {
+                    if let Some(dash_pattern) = self.options.native_stroke_line_dash() {
                         let dash_pattern_f64: Vec<f64> =
                             dash_pattern.iter().map(|&x| x as f64).collect();
                         stroke = stroke.with_dashes(
@@ -103,12 +119,17 @@ impl<F: Float + Trig> VelloDrawable<F> {
                     scene.stroke(&stroke, Affine::IDENTITY, &stroke_brush, None, &set.ops);
                 }
                 OpSetType::FillPath => {
-                    let fill_rule = match self.shape.as_str() {
-                        "curve" | "polygon" | "path" => Fill::EvenOdd,
-                        _ => Fill::NonZero,
+                    let fill_rule = match self.shape.fill_rule(&self.options) {
+                        roughr::core::FillRule::EvenOdd => Fill::EvenOdd,
+                        roughr::core::FillRule::NonZero => Fill::NonZero,
                     };
 
-                    let fill_color = self.options.fill.unwrap_or(Rgba::new(0.0, 0.0, 0.0, 1.0));
+                    let fill_color = set
+                        .style
+                        .as_ref()
+                        .and_then(|style| style.color)
+                        .or(self.options.fill)
+                        .unwrap_or(Rgba::new(0.0, 0.0, 0.0, 1.0));
                     let fill_brush = convert_rgba_to_vello_brush(fill_color);
 
                     scene.fill(fill_rule, Affine::IDENTITY, &fill_brush, None, &set.ops);
@@ -118,15 +139,25 @@ impl<F: Float + Trig> VelloDrawable<F> {
                     if fweight < 0.0 {
                         fweight = self.options.stroke_width.unwrap_or(1.0) / 2.0;
                     }
+                    let style = set.style.clone().unwrap_or_default();
+                    if let Some(weight) = style.weight {
+                        fweight = weight;
+                    }
 
                     let mut stroke = Stroke::new(fweight as f64);
 
                     // Set dash pattern if available
-                    if let Some(ref dash_pattern) = self.options.fill_line_dash {
+                    if let Some(ref dash_pattern) = style
+                        .line_dash
+                        .or_else(|| self.options.fill_line_dash.clone())
+                    {
                         let dash_pattern_f64: Vec<f64> =
                             dash_pattern.iter().map(|&x| x as f64).collect();
                         stroke = stroke.with_dashes(
-                            self.options.fill_line_dash_offset.unwrap_or(0.0) as f64,
+                            style
+                                .line_dash_offset
+                                .or(self.options.fill_line_dash_offset)
+                                .unwrap_or(0.0) as f64,
                             dash_pattern_f64,
                         );
                     }
@@ -137,9 +168,9 @@ impl<F: Float + Trig> VelloDrawable<F> {
                         self.options.line_join,
                     ));
 
-                    let fill_color = self
-                        .options
-                        .fill
+                    let fill_color = style
+                        .color
+                        .or(self.options.fill)
                         .unwrap_or_else(|| Rgba::new(0.0, 0.0, 0.0, 1.0));
                     let fill_brush = convert_rgba_to_vello_brush(fill_color);
 
@@ -150,37 +181,108 @@ impl<F: Float + Trig> VelloDrawable<F> {
     }
 }
 
-fn opset_to_shape<F: Trig + Float + FromPrimitive>(op_set: &OpSet<F>) -> BezPath {
-    let mut path: BezPath = BezPath::new();
-    for item in op_set.ops.iter() {
-        match item.op {
-            OpType::Move => path.extend([PathEl::MoveTo(Point::new(
-                item.data[0].to_f64().unwrap(),
-                item.data[1].to_f64().unwrap(),
-            ))]),
-            OpType::BCurveTo => path.extend([PathEl::CurveTo(
-                Point::new(
-                    item.data[0].to_f64().unwrap(),
-                    item.data[1].to_f64().unwrap(),
-                ),
-                Point::new(
-                    item.data[2].to_f64().unwrap(),
-                    item.data[3].to_f64().unwrap(),
-                ),
-                Point::new(
-                    item.data[4].to_f64().unwrap(),
-                    item.data[5].to_f64().unwrap(),
-                ),
-            )]),
-            OpType::LineTo => {
-                path.extend([PathEl::LineTo(Point::new(
-                    item.data[0].to_f64().unwrap(),
-                    item.data[1].to_f64().unwrap(),
-                ))]);
-            }
+/// [`PathSink`] building a kurbo [`BezPath`].
+#[derive(Default)]
+pub struct BezPathSink {
+    pub path: BezPath,
+}
+
+impl<F: Float + Trig> PathSink<F> for BezPathSink {
+    fn move_to(&mut self, to: Point2D<F>) {
+        self.path.move_to(kurbo_point(to));
+    }
+
+    fn line_to(&mut self, to: Point2D<F>) {
+        self.path.line_to(kurbo_point(to));
+    }
+
+    fn curve_to(&mut self, cp1: Point2D<F>, cp2: Point2D<F>, to: Point2D<F>) {
+        self.path
+            .curve_to(kurbo_point(cp1), kurbo_point(cp2), kurbo_point(to));
+    }
+
+    fn quad_to(&mut self, _from: Point2D<F>, cp: Point2D<F>, to: Point2D<F>) {
+        self.path.quad_to(kurbo_point(cp), kurbo_point(to));
+    }
+
+    fn close(&mut self) {
+        self.path.close_path();
+    }
+
+    fn ellipse(&mut self, center: Point2D<F>, radii: Vector2D<F>) {
+        let radii = kurbo_point(radii.to_point()).to_vec2();
+        self.path
+            .extend(Ellipse::new(kurbo_point(center), radii, 0.0).path_elements(0.1));
+    }
+}
+
+fn kurbo_point<F: Float>(p: Point2D<F>) -> Point {
+    Point::new(p.x.to_f64().unwrap(), p.y.to_f64().unwrap())
+}
+
+/// [`PathSink`] building a [`VelloOpset`] for every set streamed into it. Ops streamed
+/// outside of a set go into an [`OpSetType::Path`] set of their own.
+struct VelloOpsetSink<F: Float + Trig> {
+    sets: Vec<VelloOpset<F>>,
+    set: Option<(OpSetType, Option<OpSetStyle>, BezPathSink)>,
+}
+
+impl<F: Float + Trig> VelloOpsetSink<F> {
+    fn path(&mut self) -> &mut BezPathSink {
+        &mut self
+            .set
+            .get_or_insert_with(|| (OpSetType::Path, None, BezPathSink::default()))
+            .2
+    }
+}
+
+impl<F: Float + Trig> PathSink<F> for VelloOpsetSink<F> {
+    fn begin_set(&mut self, op_set_type: &OpSetType, style: Option<&OpSetStyle>) {
+        self.end_set();
+        self.set = Some((op_set_type.clone(), style.cloned(), BezPathSink::default()));
+    }
+
+    fn move_to(&mut self, to: Point2D<F>) {
+        self.path().move_to(to);
+    }
+
+    fn line_to(&mut self, to: Point2D<F>) {
+        self.path().line_to(to);
+    }
+
+    fn curve_to(&mut self, cp1: Point2D<F>, cp2: Point2D<F>, to: Point2D<F>) {
+        self.path().curve_to(cp1, cp2, to);
+    }
+
+    fn quad_to(&mut self, from: Point2D<F>, cp: Point2D<F>, to: Point2D<F>) {
+        self.path().quad_to(from, cp, to);
+    }
+
+    fn close(&mut self) {
+        PathSink::<F>::close(self.path());
+    }
+
+    fn ellipse(&mut self, center: Point2D<F>, radii: Vector2D<F>) {
+        self.path().ellipse(center, radii);
+    }
+
+    fn end_set(&mut self) {
+        if let Some((op_set_type, style, sink)) = self.set.take() {
+            self.sets.push(VelloOpset {
+                op_set_type,
+                ops: sink.path,
+                size: None,
+                path: None,
+                style,
+            });
         }
     }
-    path
+}
+
+fn opset_to_shape<F: Trig + Float + FromPrimitive>(op_set: &OpSet<F>) -> BezPath {
+    let mut sink = BezPathSink::default();
+    stream_ops(&op_set.ops, &mut sink);
+    sink.path
 }
 
 impl VelloGenerator {
@@ -191,8 +293,7 @@ impl VelloGenerator {
         x2: F,
         y2: F,
     ) -> VelloDrawable<F> {
-        let drawable = self.gen.line(x1, y1, x2, y2, &self.options);
-        drawable.to_vello_drawable()
+        vello_drawable(|sink| self.gen.line_into(x1, y1, x2, y2, &self.options, sink))
     }
 
     pub fn rectangle<F: Trig + Float + FromPrimitive>(
@@ -202,8 +303,10 @@ impl VelloGenerator {
         width: F,
         height: F,
     ) -> VelloDrawable<F> {
-        let drawable = self.gen.rectangle(x, y, width, height, &self.options);
-        drawable.to_vello_drawable()
+        vello_drawable(|sink| {
+            self.gen
+                .rectangle_into(x, y, width, height, &self.options, sink)
+        })
     }
 
     pub fn ellipse<F: Trig + Float + FromPrimitive>(
@@ -213,8 +316,10 @@ impl VelloGenerator {
         width: F,
         height: F,
     ) -> VelloDrawable<F> {
-        let drawable = self.gen.ellipse(x, y, width, height, &self.options);
-        drawable.to_vello_drawable()
+        vello_drawable(|sink| {
+            self.gen
+                .ellipse_into(x, y, width, height, &self.options, sink)
+        })
     }
 
     pub fn circle<F: Trig + Float + FromPrimitive>(
@@ -223,8 +328,7 @@ impl VelloGenerator {
         y: F,
         diameter: F,
     ) -> VelloDrawable<F> {
-        let drawable = self.gen.circle(x, y, diameter, &self.options);
-        drawable.to_vello_drawable()
+        vello_drawable(|sink| self.gen.circle_into(x, y, diameter, &self.options, sink))
     }
 
     pub fn linear_path<F: Trig + Float + FromPrimitive>(
@@ -232,16 +336,35 @@ impl VelloGenerator {
         points: &[Point2D<F>],
         close: bool,
     ) -> VelloDrawable<F> {
-        let drawable = self.gen.linear_path(points, close, &self.options);
-        drawable.to_vello_drawable()
+        vello_drawable(|sink| {
+            self.gen
+                .linear_path_into(points, close, &self.options, sink)
+        })
     }
 
     pub fn polygon<F: Trig + Float + FromPrimitive + MulAssign + Display>(
         &self,
         points: &[Point2D<F>],
     ) -> VelloDrawable<F> {
-        let drawable = self.gen.polygon(points, &self.options);
-        drawable.to_vello_drawable()
+        vello_drawable(|sink| self.gen.polygon_into(points, &self.options, sink))
+    }
+
+    pub fn polygon_with_holes<F: Trig + Float + FromPrimitive + MulAssign + Display>(
+        &self,
+        outer: &[Point2D<F>],
+        holes: &[Vec<Point2D<F>>],
+    ) -> VelloDrawable<F> {
+        vello_drawable(|sink| {
+            self.gen
+                .polygon_with_holes_into(outer, holes, &self.options, sink)
+        })
+    }
+
+    pub fn multi_polygon<F: Trig + Float + FromPrimitive + MulAssign + Display>(
+        &self,
+        polygons: &[Vec<Vec<Point2D<F>>>],
+    ) -> VelloDrawable<F> {
+        vello_drawable(|sink| self.gen.multi_polygon_into(polygons, &self.options, sink))
     }
 
     pub fn arc<F: Trig + Float + FromPrimitive>(
@@ -252,12 +375,12 @@ impl VelloGenerator {
         height: F,
         start: F,
         stop: F,
-        closed: bool,
+        mode: ArcMode,
     ) -> VelloDrawable<F> {
-        let drawable = self
-            .gen
-            .arc(x, y, width, height, start, stop, closed, &self.options);
-        drawable.to_vello_drawable()
+        vello_drawable(|sink| {
+            self.gen
+                .arc_into(x, y, width, height, start, stop, mode, &self.options, sink)
+        })
     }
 
     pub fn bezier_quadratic<F: Trig + Float + FromPrimitive + MulAssign + Display>(
@@ -266,8 +389,10 @@ impl VelloGenerator {
         cp: Point2D<F>,
         end: Point2D<F>,
     ) -> VelloDrawable<F> {
-        let drawable = self.gen.bezier_quadratic(start, cp, end, &self.options);
-        drawable.to_vello_drawable()
+        vello_drawable(|sink| {
+            self.gen
+                .bezier_quadratic_into(start, cp, end, &self.options, sink)
+        })
     }
 
     pub fn bezier_cubic<F: Trig + Float + FromPrimitive + MulAssign + Display>(
@@ -277,24 +402,24 @@ impl VelloGenerator {
         cp2: Point2D<F>,
         end: Point2D<F>,
     ) -> VelloDrawable<F> {
-        let drawable = self.gen.bezier_cubic(start, cp1, cp2, end, &self.options);
-        drawable.to_vello_drawable()
+        vello_drawable(|sink| {
+            self.gen
+                .bezier_cubic_into(start, cp1, cp2, end, &self.options, sink)
+        })
     }
 
     pub fn curve<F: Trig + Float + FromPrimitive + MulAssign + Display>(
         &self,
         points: &[Point2D<F>],
     ) -> VelloDrawable<F> {
-        let drawable = self.gen.curve(points, &self.options);
-        drawable.to_vello_drawable()
+        vello_drawable(|sink| self.gen.curve_into(points, &self.options, sink))
     }
 
     pub fn path<F: Trig + Float + FromPrimitive + MulAssign + Display>(
         &self,
         svg_path: String,
     ) -> VelloDrawable<F> {
-        let drawable = self.gen.path(svg_path, &self.options);
-        drawable.to_vello_drawable()
+        vello_drawable(|sink| self.gen.path_into(svg_path, &self.options, sink))
     }
 
     pub fn bez_path<F: Trig + Float + FromPrimitive + MulAssign + Display>(
@@ -302,9 +427,10 @@ impl VelloGenerator {
         bezier_path: BezPath,
     ) -> VelloDrawable<F> {
         let segments = bezpath_to_svg_segments(&bezier_path);
-        self.gen
-            .path_from_segments(segments, &self.options)
-            .to_vello_drawable()
+        vello_drawable(|sink| {
+            self.gen
+                .path_from_segments_into(segments, &self.options, sink)
+        })
     }
 }
 
@@ -0,0 +1,94 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use euclid::default::Point2D;
+use palette::Srgba;
+use roughr::core::{FillStyle, Options, OptionsBuilder, ShapeKind};
+use roughr::generator::Generator;
+
+fn options(fill_style: FillStyle) -> Option<Options> {
+    Some(
+        OptionsBuilder::default()
+            .seed(345_u64)
+            .stroke(Srgba::new(0.0, 0.0, 0.0, 1.0))
+            .fill(Srgba::new(1.0, 0.0, 0.0, 1.0))
+            .fill_style(fill_style)
+            .hachure_gap(2.0)
+            .build()
+            .expect("failed to build options"),
+    )
+}
+
+fn dense_fills(c: &mut Criterion) {
+    let generator = Generator::default();
+    let mut group = c.benchmark_group("dense_fills");
+
+    let cross_hatch = options(FillStyle::CrossHatch);
+    group.bench_function("cross_hatched_ellipse", |b| {
+        b.iter(|| generator.ellipse(black_box(250.0f64), 250.0, 400.0, 300.0, &cross_hatch))
+    });
+
+    let hachure = options(FillStyle::Hachure);
+    let star: Vec<Point2D<f64>> = (0..10)
+        .map(|i| {
+            let angle = i as f64 * std::f64::consts::PI / 5.0;
+            let radius = if i % 2 == 0 { 200.0 } else { 80.0 };
+            Point2D::new(250.0 + radius * angle.cos(), 250.0 + radius * angle.sin())
+        })
+        .collect();
+    group.bench_function("hachured_polygon", |b| {
+        b.iter(|| generator.polygon(black_box(&star), &hachure))
+    });
+
+    let dots = options(FillStyle::Dots);
+    group.bench_function("dotted_rectangle", |b| {
+        b.iter(|| generator.rectangle(black_box(0.0f64), 0.0, 300.0, 200.0, &dots))
+    });
+
+    let zig_zag = options(FillStyle::ZigZag);
+    group.bench_function("zig_zag_path", |b| {
+        b.iter(|| {
+            generator.path::<f64>(
+                black_box("M10 80 C 40 10, 65 10, 95 80 S 150 150, 180 80 L 180 300 Z".into()),
+                &zig_zag,
+            )
+        })
+    });
+    group.finish();
+}
+
+fn svg_output(c: &mut Criterion) {
+    let generator = Generator::default();
+    let cross_hatch = options(FillStyle::CrossHatch);
+    c.bench_function("cross_hatched_ellipse_to_paths", |b| {
+        b.iter(|| {
+            let drawable =
+                generator.ellipse(black_box(250.0f64), 250.0, 400.0, 300.0, &cross_hatch);
+            Generator::to_paths(drawable)
+        })
+    });
+}
+
+fn batch(c: &mut Criterion) {
+    let generator = Generator::default();
+    let mut group = c.benchmark_group("batch");
+    let cross_hatch = options(FillStyle::CrossHatch);
+    let scene: Vec<(ShapeKind<f64>, Option<Options>)> = (0..200)
+        .map(|i| {
+            let center = Point2D::new((i % 20) as f64 * 50.0, (i / 20) as f64 * 50.0);
+            (
+                ShapeKind::Circle { center, diameter: 45.0 },
+                cross_hatch.clone(),
+            )
+        })
+        .collect();
+    group.bench_function("sequential", |b| {
+        b.iter(|| generator.generate_batch(black_box(&scene)))
+    });
+    #[cfg(feature = "rayon")]
+    group.bench_function("parallel", |b| {
+        b.iter(|| generator.par_generate_batch(black_box(&scene)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, dense_fills, svg_output, batch);
+criterion_main!(benches);
@@ -0,0 +1,602 @@
+//! Caching of generated drawables.
+//!
+//! Interactive applications redraw the same shapes over and over. A [`DrawableCache`] keeps
+//! the drawables it generated, keyed by the shape parameters and options they were generated
+//! with, and hands them out again instead of roughening the shape anew. When full, the least
+//! recently used drawable makes room for the new one.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Display;
+use std::ops::MulAssign;
+use std::sync::Arc;
+
+use euclid::{Point2D, Trig, Vector2D};
+use num_traits::{Float, FromPrimitive};
+use palette::Srgba;
+use svgtypes::PathSegment;
+
+use crate::core::{Drawable, HatchLayer, LineJoin, Motif, Options, ShapeKind, ToneMap};
+use crate::generator::Generator;
+
+/// Exact key of a drawable: the parameters of its shape and all of its options. Floats are
+/// compared by their bits, so any change to a value, however small, gives a different key.
+/// Tone functions are compared by identity.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CacheKey(Vec<u64>);
+
+impl CacheKey {
+    pub fn new<F: Float + Trig>(shape: &ShapeKind<F>, options: &Options) -> Self {
+        let mut words = vec![];
+        shape.write(&mut words);
+        options.write(&mut words);
+        CacheKey(words)
+    }
+}
+
+/// Hit and miss counts of a [`DrawableCache`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+}
+
+impl CacheStats {
+    /// Share of lookups that were served from the cache, or `0.0` before the first one.
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / lookups as f64
+        }
+    }
+}
+
+/// Least recently used cache of the drawables generated by a [`Generator`].
+///
+/// Shapes drawn without a seed are cached as they first came out, so they keep their look
+/// across redraws instead of jittering.
+pub struct DrawableCache<F: Float + Trig> {
+    generator: Generator,
+    capacity: usize,
+    entries: HashMap<CacheKey, (Arc<Drawable<F>>, u64)>,
+    recency: BTreeMap<u64, CacheKey>,
+    tick: u64,
+    stats: CacheStats,
+}
+
+impl<F> DrawableCache<F>
+where
+    F: Float + Trig + FromPrimitive + MulAssign + Display,
+{
+    /// Cache holding up to `capacity` drawables generated by `generator`.
+    pub fn new(generator: Generator, capacity: usize) -> Self {
+        DrawableCache {
+            generator,
+            capacity,
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+            tick: 0,
+            stats: CacheStats::default(),
+        }
+    }
+
+    /// The drawable of `shape` with its options, or the default options of the generator,
+    /// generated only if it is not in the cache yet.
+    pub fn draw(&mut self, shape: &ShapeKind<F>, options: &Option<Options>) -> Arc<Drawable<F>> {
+        let resolved = options.as_ref().unwrap_or(&self.generator.default_options);
+        let key = CacheKey::new(shape, resolved);
+        self.tick += 1;
+        if let Some((drawable, used)) = self.entries.get_mut(&key) {
+            self.recency.remove(used);
+            *used = self.tick;
+            self.recency.insert(self.tick, key);
+            self.stats.hits += 1;
+            return drawable.clone();
+        }
+
+        self.stats.misses += 1;
+        let drawable = Arc::new(self.generator.draw(shape, options));
+        if self.capacity == 0 {
+            return drawable;
+        }
+        if self.entries.len() >= self.capacity {
+            if let Some((_, oldest)) = self.recency.pop_first() {
+                self.entries.remove(&oldest);
+                self.stats.evictions += 1;
+            }
+        }
+        self.recency.insert(self.tick, key.clone());
+        self.entries.insert(key, (drawable.clone(), self.tick));
+        drawable
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Drops all cached drawables, keeping the statistics.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.recency.clear();
+    }
+}
+
+/// Values that can be written into a [`CacheKey`].
+trait KeyPart {
+    fn write(&self, words: &mut Vec<u64>);
+}
+
+fn float<F: Float>(value: F, words: &mut Vec<u64>) {
+    words.push(value.to_f64().unwrap_or(f64::NAN).to_bits());
+}
+
+impl KeyPart for f32 {
+    fn write(&self, words: &mut Vec<u64>) {
+        words.push(self.to_bits() as u64);
+    }
+}
+
+impl KeyPart for f64 {
+    fn write(&self, words: &mut Vec<u64>) {
+        words.push(self.to_bits());
+    }
+}
+
+impl KeyPart for u32 {
+    fn write(&self, words: &mut Vec<u64>) {
+        words.push(*self as u64);
+    }
+}
+
+impl KeyPart for u64 {
+    fn write(&self, words: &mut Vec<u64>) {
+        words.push(*self);
+    }
+}
+
+impl KeyPart for bool {
+    fn write(&self, words: &mut Vec<u64>) {
+        words.push(*self as u64);
+    }
+}
+
+impl KeyPart for str {
+    fn write(&self, words: &mut Vec<u64>) {
+        words.push(self.len() as u64);
+        words.extend(self.bytes().map(u64::from));
+    }
+}
+
+impl KeyPart for String {
+    fn write(&self, words: &mut Vec<u64>) {
+        self.as_str().write(words);
+    }
+}
+
+impl<T: KeyPart> KeyPart for Option<T> {
+    fn write(&self, words: &mut Vec<u64>) {
+        match self {
+            Some(value) => {
+                words.push(1);
+                value.write(words);
+            }
+            None => words.push(0),
+        }
+    }
+}
+
+impl<T: KeyPart> KeyPart for [T] {
+    fn write(&self, words: &mut Vec<u64>) {
+        words.push(self.len() as u64);
+        self.iter().for_each(|value| value.write(words));
+    }
+}
+
+impl<T: KeyPart> KeyPart for Vec<T> {
+    fn write(&self, words: &mut Vec<u64>) {
+        self.as_slice().write(words);
+    }
+}
+
+impl<F: Float, U> KeyPart for Point2D<F, U> {
+    fn write(&self, words: &mut Vec<u64>) {
+        float(self.x, words);
+        float(self.y, words);
+    }
+}
+
+impl<F: Float, U> KeyPart for Vector2D<F, U> {
+    fn write(&self, words: &mut Vec<u64>) {
+        float(self.x, words);
+        float(self.y, words);
+    }
+}
+
+impl KeyPart for Srgba {
+    fn write(&self, words: &mut Vec<u64>) {
+        [self.red, self.green, self.blue, self.alpha]
+            .iter()
+            .for_each(|c| c.write(words));
+    }
+}
+
+impl KeyPart for HatchLayer {
+    fn write(&self, words: &mut Vec<u64>) {
+        self.angle.write(words);
+        self.gap.write(words);
+        self.weight.write(words);
+        self.color.write(words);
+        self.line_dash.write(words);
+        self.line_dash_offset.write(words);
+    }
+}
+
+impl KeyPart for ToneMap {
+    fn write(&self, words: &mut Vec<u64>) {
+        match self {
+            ToneMap::Linear { start, end, start_tone, end_tone } => {
+                words.push(0);
+                start.write(words);
+                end.write(words);
+                start_tone.write(words);
+                end_tone.write(words);
+            }
+            ToneMap::Radial { center, radius, center_tone, edge_tone } => {
+                words.push(1);
+                center.write(words);
+                radius.write(words);
+                center_tone.write(words);
+                edge_tone.write(words);
+            }
+            ToneMap::Function(f) => {
+                words.push(2);
+                words.push(Arc::as_ptr(f) as *const () as usize as u64);
+            }
+        }
+    }
+}
+
+impl KeyPart for Motif {
+    fn write(&self, words: &mut Vec<u64>) {
+        self.path.write(words);
+        self.width.write(words);
+        self.height.write(words);
+        words.push(self.layout as u64);
+    }
+}
+
+impl KeyPart for LineJoin {
+    fn write(&self, words: &mut Vec<u64>) {
+        match self {
+            LineJoin::Miter { limit } => {
+                words.push(0);
+                limit.write(words);
+            }
+            LineJoin::Round => words.push(1),
+            LineJoin::Bevel => words.push(2),
+        }
+    }
+}
+
+impl KeyPart for PathSegment {
+    fn write(&self, words: &mut Vec<u64>) {
+        let (tag, abs, values): (u64, bool, Vec<f64>) = match *self {
+            PathSegment::MoveTo { abs, x, y } => (0, abs, vec![x, y]),
+            PathSegment::LineTo { abs, x, y } => (1, abs, vec![x, y]),
+            PathSegment::HorizontalLineTo { abs, x } => (2, abs, vec![x]),
+            PathSegment::VerticalLineTo { abs, y } => (3, abs, vec![y]),
+            PathSegment::CurveTo { abs, x1, y1, x2, y2, x, y } => {
+                (4, abs, vec![x1, y1, x2, y2, x, y])
+            }
+            PathSegment::SmoothCurveTo { abs, x2, y2, x, y } => (5, abs, vec![x2, y2, x, y]),
+            PathSegment::Quadratic { abs, x1, y1, x, y } => (6, abs, vec![x1, y1, x, y]),
+            PathSegment::SmoothQuadratic { abs, x, y } => (7, abs, vec![x, y]),
+            PathSegment::EllipticalArc {
+                abs,
+                rx,
+                ry,
+                x_axis_rotation,
+                large_arc,
+                sweep,
+                x,
+                y,
+            } => {
+                let flags = large_arc as u8 as f64 * 2.0 + sweep as u8 as f64;
+                (8, abs, vec![rx, ry, x_axis_rotation, flags, x, y])
+            }
+            PathSegment::ClosePath { abs } => (9, abs, vec![]),
+        };
+        words.push(tag);
+        abs.write(words);
+        values.write(words);
+    }
+}
+
+impl<F: Float + Trig> KeyPart for ShapeKind<F> {
+    fn write(&self, words: &mut Vec<u64>) {
+        match self {
+            ShapeKind::Line { start, end } => {
+                words.push(0);
+                start.write(words);
+                end.write(words);
+            }
+            ShapeKind::Rectangle { x, y, width, height } => {
+                words.push(1);
+                [*x, *y, *width, *height]
+                    .into_iter()
+                    .for_each(|v| float(v, words));
+            }
+            ShapeKind::Ellipse { center, width, height } => {
+                words.push(2);
+                center.write(words);
+                float(*width, words);
+                float(*height, words);
+            }
+            ShapeKind::Circle { center, diameter } => {
+                words.push(3);
+                center.write(words);
+                float(*diameter, words);
+            }
+            ShapeKind::LinearPath { points, close } => {
+                words.push(4);
+                points.write(words);
+                close.write(words);
+            }
+            ShapeKind::Arc { center, width, height, start, stop, mode } => {
+                words.push(5);
+                center.write(words);
+                [*width, *height, *start, *stop]
+                    .into_iter()
+                    .for_each(|v| float(v, words));
+                words.push(*mode as u64);
+            }
+            ShapeKind::BezierQuadratic { start, cp, end } => {
+                words.push(6);
+                [start, cp, end].into_iter().for_each(|p| p.write(words));
+            }
+            ShapeKind::BezierCubic { start, cp1, cp2, end } => {
+                words.push(7);
+                [start, cp1, cp2, end]
+                    .into_iter()
+                    .for_each(|p| p.write(words));
+            }
+            ShapeKind::Curve { points } => {
+                words.push(8);
+                points.write(words);
+            }
+            ShapeKind::Polygon { points } => {
+                words.push(9);
+                points.write(words);
+            }
+            ShapeKind::PolygonWithHoles { outer, holes } => {
+                words.push(10);
+                outer.write(words);
+                holes.write(words);
+            }
+            ShapeKind::MultiPolygon { polygons } => {
+                words.push(11);
+                polygons.write(words);
+            }
+            ShapeKind::Path { d } => {
+                words.push(12);
+                d.write(words);
+            }
+            ShapeKind::PathSegments { segments } => {
+                words.push(13);
+                segments.write(words);
+            }
+        }
+    }
+}
+
+impl KeyPart for Options {
+    fn write(&self, words: &mut Vec<u64>) {
+        // destructured so that new options can not be left out of the key
+        let Options {
+            max_randomness_offset,
+            roughness,
+            bowing,
+            stroke,
+            stroke_width,
+            curve_fitting,
+            curve_tightness,
+            curve_step_count,
+            curve_closed,
+            curve_parameterization,
+            fill,
+            fill_style,
+            fill_weight,
+            hachure_angle,
+            hachure_gap,
+            hachure_angle_jitter,
+            hachure_gap_jitter,
+            hachure_line_wobble,
+            hachure_line_trim,
+            simplification,
+            dash_offset,
+            dash_gap,
+            zigzag_offset,
+            stipple_density,
+            stipple_min_size,
+            stipple_max_size,
+            wave_amplitude,
+            wave_length,
+            fill_rule,
+            hatch_layers,
+            tone,
+            tone_cross_hatch,
+            motif,
+            watercolor_layers,
+            watercolor_color_jitter,
+            fill_offset,
+            fill_inset,
+            fill_misregistration,
+            seed,
+            stroke_line_dash,
+            stroke_line_dash_offset,
+            stroke_style,
+            line_cap,
+            line_join,
+            fill_line_dash,
+            fill_line_dash_offset,
+            disable_multi_stroke,
+            disable_multi_stroke_fill,
+            stroke_passes,
+            fill_passes,
+            pass_roughness_scale,
+            preserve_vertices,
+            overshoot,
+            fixed_decimal_place_digits,
+        } = self;
+        [
+            max_randomness_offset,
+            roughness,
+            bowing,
+            stroke_width,
+            curve_fitting,
+            curve_tightness,
+            curve_step_count,
+            fill_weight,
+            hachure_angle,
+            hachure_gap,
+            hachure_angle_jitter,
+            hachure_gap_jitter,
+            hachure_line_wobble,
+            hachure_line_trim,
+            simplification,
+            dash_offset,
+            dash_gap,
+            zigzag_offset,
+            stipple_density,
+            stipple_min_size,
+            stipple_max_size,
+            wave_amplitude,
+            wave_length,
+            watercolor_color_jitter,
+            fill_inset,
+            fill_misregistration,
+            pass_roughness_scale,
+            overshoot,
+            fixed_decimal_place_digits,
+        ]
+        .into_iter()
+        .for_each(|value| value.write(words));
+        [
+            curve_closed,
+            tone_cross_hatch,
+            disable_multi_stroke,
+            disable_multi_stroke_fill,
+            preserve_vertices,
+        ]
+        .into_iter()
+        .for_each(|value| value.write(words));
+        [watercolor_layers, stroke_passes, fill_passes]
+            .into_iter()
+            .for_each(|value| value.write(words));
+        [stroke, fill]
+            .into_iter()
+            .for_each(|value| value.write(words));
+        [stroke_line_dash, fill_line_dash]
+            .into_iter()
+            .for_each(|value| value.write(words));
+        [stroke_line_dash_offset, fill_line_dash_offset]
+            .into_iter()
+            .for_each(|value| value.write(words));
+        curve_parameterization.map(|v| v as u64).write(words);
+        fill_style.map(|v| v as u64).write(words);
+        fill_rule.map(|v| v as u64).write(words);
+        stroke_style.map(|v| v as u64).write(words);
+        line_cap.map(|v| v as u64).write(words);
+        hatch_layers.write(words);
+        tone.write(words);
+        motif.write(words);
+        fill_offset.write(words);
+        seed.write(words);
+        line_join.write(words);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use euclid::point2;
+
+    use super::{CacheKey, CacheStats, DrawableCache};
+    use crate::core::{OptionsBuilder, ShapeKind, ToneMap};
+    use crate::generator::Generator;
+
+    fn square(x: f64) -> ShapeKind<f64> {
+        ShapeKind::Rectangle { x, y: 0.0, width: 10.0, height: 10.0 }
+    }
+
+    #[test]
+    fn keys() {
+        let options = OptionsBuilder::default().build().unwrap();
+        let key = CacheKey::new(&square(0.0), &options);
+        assert_eq!(key, CacheKey::new(&square(0.0), &options.clone()));
+        assert_ne!(key, CacheKey::new(&square(1e-9), &options));
+        assert_ne!(key, CacheKey::new(&square(-0.0), &options));
+
+        let rougher = OptionsBuilder::default().roughness(1.5).build().unwrap();
+        assert_ne!(key, CacheKey::new(&square(0.0), &rougher));
+        let line = ShapeKind::Line { start: point2(0.0, 0.0), end: point2(10.0, 0.0) };
+        assert_ne!(key, CacheKey::new(&line, &options));
+
+        let tone = ToneMap::Function(Arc::new(|x, _| x));
+        let toned = OptionsBuilder::default()
+            .tone(tone.clone())
+            .build()
+            .unwrap();
+        let same_tone = OptionsBuilder::default().tone(tone).build().unwrap();
+        let other_tone = OptionsBuilder::default()
+            .tone(ToneMap::Function(Arc::new(|x, _| x)))
+            .build()
+            .unwrap();
+        let toned_key = CacheKey::new(&square(0.0), &toned);
+        assert_eq!(toned_key, CacheKey::new(&square(0.0), &same_tone));
+        assert_ne!(toned_key, CacheKey::new(&square(0.0), &other_tone));
+    }
+
+    #[test]
+    fn least_recently_used_is_evicted() {
+        let mut cache = DrawableCache::new(Generator::default(), 2);
+        let first = cache.draw(&square(0.0), &None);
+        let again = cache.draw(&square(0.0), &None);
+        assert!(Arc::ptr_eq(&first, &again));
+        assert_eq!(
+            first.sets,
+            Generator::default().draw(&square(0.0), &None).sets
+        );
+
+        cache.draw(&square(20.0), &None);
+        // the first square was used more recently than the second one
+        cache.draw(&square(0.0), &None);
+        cache.draw(&square(40.0), &None);
+        assert_eq!(cache.len(), 2);
+        assert!(Arc::ptr_eq(&first, &cache.draw(&square(0.0), &None)));
+        cache.draw(&square(20.0), &None);
+
+        let stats = cache.stats();
+        assert_eq!(stats, CacheStats { hits: 3, misses: 4, evictions: 2 });
+        assert!((stats.hit_rate() - 3.0 / 7.0).abs() < 1e-12);
+
+        cache.clear();
+        assert!(cache.is_empty());
+        assert_eq!(cache.stats().evictions, 2);
+    }
+}
@@ -1,9 +1,15 @@
-use euclid::default::Point2D;
+use std::fmt::Display;
+use std::ops::{Add, Sub};
+use std::sync::Arc;
+
+use euclid::default::{Point2D, Vector2D};
 use euclid::Trig;
 use num_traits::{Float, FromPrimitive};
 use palette::Srgba;
+pub use points_on_curve::CurveParameterization;
 use rand::rngs::StdRng;
-use rand::{random, Rng, SeedableRng};
+use rand::{random, SeedableRng};
+use svgtypes::PathSegment;
 
 pub struct Space;
 
@@ -25,6 +31,16 @@ pub enum FillStyle {
     Dots,
     Dashed,
     ZigZagLine,
+    Stipple,
+    Wavy,
+    Scribble,
+    HatchLayers,
+    ToneHatch,
+    Motif,
+    Flow,
+    Hilbert,
+    Serpentine,
+    Watercolor,
 }
 
 impl ToString for FillStyle {
@@ -37,6 +53,16 @@ impl ToString for FillStyle {
             FillStyle::Dots => "Dots".into(),
             FillStyle::Dashed => "Dashed".into(),
             FillStyle::ZigZagLine => "ZigZagLine".into(),
+            FillStyle::Stipple => "Stipple".into(),
+            FillStyle::Wavy => "Wavy".into(),
+            FillStyle::Scribble => "Scribble".into(),
+            FillStyle::HatchLayers => "HatchLayers".into(),
+            FillStyle::ToneHatch => "ToneHatch".into(),
+            FillStyle::Motif => "Motif".into(),
+            FillStyle::Flow => "Flow".into(),
+            FillStyle::Hilbert => "Hilbert".into(),
+            FillStyle::Serpentine => "Serpentine".into(),
+            FillStyle::Watercolor => "Watercolor".into(),
         }
     }
 }
@@ -54,6 +80,163 @@ impl Default for LineCap {
     }
 }
 
+/// Rule deciding which regions of a set of polygons count as inside.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum FillRule {
+    #[default]
+    EvenOdd,
+    NonZero,
+}
+
+/// A single pass of straight hachure lines in a [`FillStyle::HatchLayers`] fill.
+/// Values left to `None` fall back to the matching fill options of the drawable.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct HatchLayer {
+    /// Angle of the lines in degrees, like [`Options::hachure_angle`].
+    pub angle: f32,
+    pub gap: Option<f32>,
+    pub weight: Option<f32>,
+    pub color: Option<Srgba>,
+    pub line_dash: Option<Vec<f64>>,
+    pub line_dash_offset: Option<f64>,
+}
+
+/// Tone over the drawing plane that drives [`FillStyle::ToneHatch`] fills and thins out the
+/// dots of [`FillStyle::Stipple`] fills. Tones range from `0.0`, left blank, to `1.0`, filled
+/// at full density. Positions are in the same coordinates as the shape being filled.
+#[derive(Clone)]
+pub enum ToneMap {
+    /// Tone interpolated along the line from `start` to `end`, held constant beyond them.
+    Linear {
+        start: Point2D<f32>,
+        end: Point2D<f32>,
+        start_tone: f32,
+        end_tone: f32,
+    },
+    /// Tone interpolated from `center` outwards, held constant beyond `radius`.
+    Radial {
+        center: Point2D<f32>,
+        radius: f32,
+        center_tone: f32,
+        edge_tone: f32,
+    },
+    /// Arbitrary tone function of the `x` and `y` coordinates.
+    Function(Arc<dyn Fn(f32, f32) -> f32 + Send + Sync>),
+}
+
+impl ToneMap {
+    pub fn tone_at(&self, x: f32, y: f32) -> f32 {
+        let tone = match self {
+            ToneMap::Linear { start, end, start_tone, end_tone } => {
+                let axis = *end - *start;
+                let length_sq = axis.square_length();
+                let t = if length_sq > 0.0 {
+                    ((Point2D::new(x, y) - *start).dot(axis) / length_sq).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                };
+                start_tone + (end_tone - start_tone) * t
+            }
+            ToneMap::Radial { center, radius, center_tone, edge_tone } => {
+                let t = if *radius > 0.0 {
+                    ((Point2D::new(x, y) - *center).length() / radius).min(1.0)
+                } else {
+                    1.0
+                };
+                center_tone + (edge_tone - center_tone) * t
+            }
+            ToneMap::Function(f) => f(x, y),
+        };
+        tone.clamp(0.0, 1.0)
+    }
+}
+
+/// Arrangement of the cells a [`Motif`] is stamped into.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum MotifLayout {
+    #[default]
+    Grid,
+    /// Every other row is shifted by half a cell, like the courses of a brick wall.
+    Staggered,
+}
+
+/// Shape stamped repeatedly by [`FillStyle::Motif`] fills.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Motif {
+    /// SVG path of the motif, relative to the top left corner of its cell.
+    pub path: String,
+    pub width: f32,
+    pub height: f32,
+    pub layout: MotifLayout,
+}
+
+impl Motif {
+    pub fn new<T: Into<String>>(path: T, width: f32, height: f32) -> Self {
+        Motif {
+            path: path.into(),
+            width,
+            height,
+            layout: MotifLayout::Grid,
+        }
+    }
+
+    pub fn with_layout(mut self, layout: MotifLayout) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    /// Uses the outlines of a drawable as motif.
+    pub fn from_drawable<F: Float + Trig>(drawable: &Drawable<F>, width: f32, height: f32) -> Self {
+        let mut path = String::new();
+        for set in drawable.sets.iter() {
+            for op in set.ops.iter() {
+                let command = match op {
+                    Op::Move(_) => "M",
+                    Op::LineTo(_) => "L",
+                    Op::BCurveTo { .. } => "C",
+                    Op::QuadTo { .. } => "Q",
+                    Op::Close => "Z",
+                    Op::Ellipse { center, radii } => {
+                        let [cx, cy, rx, ry] = [center.x, center.y, radii.x, radii.y]
+                            .map(|v| v.to_f32().unwrap_or(0.0));
+                        path.push_str(&ellipse_path(cx, cy, rx, ry));
+                        continue;
+                    }
+                };
+                let data: Vec<String> = op
+                    .points()
+                    .flat_map(|p| [p.x, p.y])
+                    .map(|v| v.to_f32().unwrap_or(0.0).to_string())
+                    .collect();
+                path.push_str(&format!("{}{} ", command, data.join(" ")));
+            }
+        }
+        Motif::new(path.trim_end(), width, height)
+    }
+}
+
+/// Svg path of an [`OpType::Ellipse`] op, as two half arcs.
+pub(crate) fn ellipse_path<T: Display + Copy + Add<Output = T> + Sub<Output = T>>(
+    cx: T,
+    cy: T,
+    rx: T,
+    ry: T,
+) -> String {
+    format!(
+        "M{} {} A{} {} 0 1 0 {} {} A{} {} 0 1 0 {} {} Z ",
+        cx + rx,
+        cy,
+        rx,
+        ry,
+        cx - rx,
+        cy,
+        rx,
+        ry,
+        cx + rx,
+        cy
+    )
+}
+
 /// Options for angled joins in strokes.
 #[derive(Clone, Copy, PartialEq, Debug)]
 pub enum LineJoin {
@@ -70,6 +253,32 @@ impl Default for LineJoin {
     }
 }
 
+/// How the ends of an arc are connected, both for its outline and for its fill.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum ArcMode {
+    /// Only the curve itself is drawn, and the arc is not filled.
+    #[default]
+    Open,
+    /// The ends are joined by a straight line, filling the segment between curve and chord.
+    Chord,
+    /// Both ends are joined to the center, filling a slice of the ellipse.
+    Pie,
+}
+
+/// How the outline of a shape is stroked. Dashed and dotted outlines are drawn as rough
+/// geometry instead of passing the dash pattern on to the backend.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum StrokeStyle {
+    #[default]
+    Solid,
+    /// Rough dashes cut from the outline, following [`Options::stroke_line_dash`] or about
+    /// eight stroke widths long if it is not set.
+    Dashed,
+    /// Rough dots along the outline, one for every repetition of
+    /// [`Options::stroke_line_dash`] or every four stroke widths if it is not set.
+    Dotted,
+}
+
 #[derive(Clone, Builder)]
 #[builder(setter(strip_option))]
 pub struct Options {
@@ -90,6 +299,10 @@ pub struct Options {
     #[builder(default = "Some(9.0)")]
     pub curve_step_count: Option<f32>,
     #[builder(default = "None")]
+    pub curve_closed: Option<bool>,
+    #[builder(default = "None")]
+    pub curve_parameterization: Option<CurveParameterization>,
+    #[builder(default = "None")]
     pub fill: Option<Srgba>,
     #[builder(default = "None")]
     pub fill_style: Option<FillStyle>,
@@ -99,6 +312,14 @@ pub struct Options {
     pub hachure_angle: Option<f32>,
     #[builder(default = "Some(-1.0)")]
     pub hachure_gap: Option<f32>,
+    #[builder(default = "None")]
+    pub hachure_angle_jitter: Option<f32>,
+    #[builder(default = "None")]
+    pub hachure_gap_jitter: Option<f32>,
+    #[builder(default = "None")]
+    pub hachure_line_wobble: Option<f32>,
+    #[builder(default = "None")]
+    pub hachure_line_trim: Option<f32>,
     #[builder(default = "Some(1.0)")]
     pub simplification: Option<f32>,
     #[builder(default = "Some(-1.0)")]
@@ -107,6 +328,36 @@ pub struct Options {
     pub dash_gap: Option<f32>,
     #[builder(default = "Some(-1.0)")]
     pub zigzag_offset: Option<f32>,
+    #[builder(default = "Some(-1.0)")]
+    pub stipple_density: Option<f32>,
+    #[builder(default = "Some(-1.0)")]
+    pub stipple_min_size: Option<f32>,
+    #[builder(default = "Some(-1.0)")]
+    pub stipple_max_size: Option<f32>,
+    #[builder(default = "Some(-1.0)")]
+    pub wave_amplitude: Option<f32>,
+    #[builder(default = "Some(-1.0)")]
+    pub wave_length: Option<f32>,
+    #[builder(default = "None")]
+    pub fill_rule: Option<FillRule>,
+    #[builder(default = "None")]
+    pub hatch_layers: Option<Vec<HatchLayer>>,
+    #[builder(default = "None")]
+    pub tone: Option<ToneMap>,
+    #[builder(default = "Some(false)")]
+    pub tone_cross_hatch: Option<bool>,
+    #[builder(default = "None")]
+    pub motif: Option<Motif>,
+    #[builder(default = "Some(5)")]
+    pub watercolor_layers: Option<u32>,
+    #[builder(default = "Some(0.05)")]
+    pub watercolor_color_jitter: Option<f32>,
+    #[builder(default = "None")]
+    pub fill_offset: Option<Vector2D<f32>>,
+    #[builder(default = "None")]
+    pub fill_inset: Option<f32>,
+    #[builder(default = "None")]
+    pub fill_misregistration: Option<f32>,
     #[builder(default = "Some(345_u64)")]
     pub seed: Option<u64>,
     #[builder(default = "None")]
@@ -114,6 +365,8 @@ pub struct Options {
     #[builder(default = "None")]
     pub stroke_line_dash_offset: Option<f64>,
     #[builder(default = "None")]
+    pub stroke_style: Option<StrokeStyle>,
+    #[builder(default = "None")]
     pub line_cap: Option<LineCap>,
     #[builder(default = "None")]
     pub line_join: Option<LineJoin>,
@@ -125,12 +378,18 @@ pub struct Options {
     pub disable_multi_stroke: Option<bool>,
     #[builder(default = "Some(false)")]
     pub disable_multi_stroke_fill: Option<bool>,
+    #[builder(default = "None")]
+    pub stroke_passes: Option<u32>,
+    #[builder(default = "None")]
+    pub fill_passes: Option<u32>,
+    #[builder(default = "Some(1.0)")]
+    pub pass_roughness_scale: Option<f32>,
     #[builder(default = "Some(false)")]
     pub preserve_vertices: Option<bool>,
     #[builder(default = "None")]
-    pub fixed_decimal_place_digits: Option<f32>,
+    pub overshoot: Option<f32>,
     #[builder(default = "None")]
-    pub randomizer: Option<StdRng>,
+    pub fixed_decimal_place_digits: Option<f32>,
 }
 
 impl Default for Options {
@@ -144,45 +403,70 @@ impl Default for Options {
             curve_tightness: Some(0.0),
             curve_fitting: Some(0.95),
             curve_step_count: Some(9.0),
+            curve_closed: None,
+            curve_parameterization: None,
             fill: None,
             fill_style: None,
             fill_weight: Some(-1.0),
             hachure_angle: Some(-41.0),
             hachure_gap: Some(-1.0),
+            hachure_angle_jitter: None,
+            hachure_gap_jitter: None,
+            hachure_line_wobble: None,
+            hachure_line_trim: None,
             dash_offset: Some(-1.0),
             dash_gap: Some(-1.0),
             zigzag_offset: Some(-1.0),
+            stipple_density: Some(-1.0),
+            stipple_min_size: Some(-1.0),
+            stipple_max_size: Some(-1.0),
+            wave_amplitude: Some(-1.0),
+            wave_length: Some(-1.0),
+            fill_rule: None,
+            hatch_layers: None,
+            tone: None,
+            tone_cross_hatch: Some(false),
+            motif: None,
+            watercolor_layers: Some(5),
+            watercolor_color_jitter: Some(0.05),
+            fill_offset: None,
+            fill_inset: None,
+            fill_misregistration: None,
             seed: Some(345_u64),
             disable_multi_stroke: Some(false),
             disable_multi_stroke_fill: Some(false),
+            stroke_passes: None,
+            fill_passes: None,
+            pass_roughness_scale: Some(1.0),
             preserve_vertices: Some(false),
+            overshoot: None,
             simplification: Some(1.0),
             stroke_line_dash: None,
             stroke_line_dash_offset: None,
+            stroke_style: None,
             line_cap: None,
             line_join: None,
             fill_line_dash: None,
             fill_line_dash_offset: None,
             fixed_decimal_place_digits: None,
-            randomizer: None,
         }
     }
 }
 
 impl Options {
-    pub fn random(&mut self) -> f64 {
-        match &mut self.randomizer {
-            Some(r) => r.gen(),
-            None => match self.seed {
-                Some(s) => {
-                    let rnd = self.randomizer.insert(StdRng::seed_from_u64(s));
-                    rnd.gen()
-                }
-                None => {
-                    let rnd = self.randomizer.insert(StdRng::seed_from_u64(random()));
-                    rnd.gen()
-                }
-            },
+    /// Random number generator for drawing a shape, seeded with `seed`, or from entropy if
+    /// there is none.
+    pub fn rng(&self) -> StdRng {
+        StdRng::seed_from_u64(self.seed.unwrap_or_else(random))
+    }
+
+    /// Dash pattern for backends to stroke outlines with. Dashed and dotted
+    /// [`Options::stroke_style`]s are drawn as rough dashes already, so their outlines are
+    /// stroked solid.
+    pub fn native_stroke_line_dash(&self) -> Option<&[f64]> {
+        match self.stroke_style.unwrap_or_default() {
+            StrokeStyle::Solid => self.stroke_line_dash.as_deref(),
+            StrokeStyle::Dashed | StrokeStyle::Dotted => None,
         }
     }
 
@@ -197,11 +481,15 @@ impl Options {
     }
 }
 
-#[derive(Clone, PartialEq, Debug, Eq)]
+/// Kind of an [`Op`], without its coordinates.
+#[derive(Clone, Copy, PartialEq, Debug, Eq)]
 pub enum OpType {
     Move,
     BCurveTo,
     LineTo,
+    QuadTo,
+    Close,
+    Ellipse,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -211,22 +499,174 @@ pub enum OpSetType {
     FillSketch,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
-pub struct Op<F: Float + Trig> {
-    pub op: OpType,
-    pub data: Vec<F>,
+/// A single path command. Coordinates are kept inline, so the ops of an [`OpSet`] sit in
+/// one contiguous buffer without any allocation of their own.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Op<F: Float + Trig> {
+    Move(Point2D<F>),
+    LineTo(Point2D<F>),
+    BCurveTo {
+        cp1: Point2D<F>,
+        cp2: Point2D<F>,
+        to: Point2D<F>,
+    },
+    QuadTo {
+        cp: Point2D<F>,
+        to: Point2D<F>,
+    },
+    /// Closes the current subpath with a straight line to its start.
+    Close,
+    /// A whole ellipse as its own closed subpath. Used for dots that are too small to be
+    /// drawn rough.
+    Ellipse {
+        center: Point2D<F>,
+        radii: Vector2D<F>,
+    },
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+impl<F: Float + Trig> Op<F> {
+    pub fn op_type(&self) -> OpType {
+        match self {
+            Op::Move(_) => OpType::Move,
+            Op::LineTo(_) => OpType::LineTo,
+            Op::BCurveTo { .. } => OpType::BCurveTo,
+            Op::QuadTo { .. } => OpType::QuadTo,
+            Op::Close => OpType::Close,
+            Op::Ellipse { .. } => OpType::Ellipse,
+        }
+    }
+
+    /// Points defining the op, in path order. An ellipse gives the corners of its bounding
+    /// box, so that the points always span the area the op draws over.
+    pub fn points(&self) -> impl Iterator<Item = Point2D<F>> {
+        let origin = Point2D::origin();
+        let (points, len) = match *self {
+            Op::Move(p) | Op::LineTo(p) => ([p, origin, origin], 1),
+            Op::BCurveTo { cp1, cp2, to } => ([cp1, cp2, to], 3),
+            Op::QuadTo { cp, to } => ([cp, to, origin], 2),
+            Op::Close => ([origin; 3], 0),
+            Op::Ellipse { center, radii } => ([center - radii, center + radii, origin], 2),
+        };
+        points.into_iter().take(len)
+    }
+
+    /// Point the op leaves the pen at, if it is known without the rest of the path.
+    pub fn end_point(&self) -> Option<Point2D<F>> {
+        match *self {
+            Op::Move(to) | Op::LineTo(to) | Op::BCurveTo { to, .. } | Op::QuadTo { to, .. } => {
+                Some(to)
+            }
+            Op::Close | Op::Ellipse { .. } => None,
+        }
+    }
+}
+
+/// Overrides of the drawable's options for a single op set. Fills made of several
+/// differently styled layers emit one op set per layer, each carrying its own style.
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct OpSetStyle {
+    pub color: Option<Srgba>,
+    pub weight: Option<f32>,
+    pub line_dash: Option<Vec<f64>>,
+    pub line_dash_offset: Option<f64>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
 pub struct OpSet<F: Float + Trig> {
     pub op_set_type: OpSetType,
     pub ops: Vec<Op<F>>,
     pub size: Option<Point2D<F>>,
     pub path: Option<String>,
+    pub style: Option<OpSetStyle>,
+}
+
+/// The primitive a [`Drawable`] was generated from, with the parameters it was given.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ShapeKind<F: Float + Trig> {
+    Line {
+        start: Point2D<F>,
+        end: Point2D<F>,
+    },
+    Rectangle {
+        x: F,
+        y: F,
+        width: F,
+        height: F,
+    },
+    Ellipse {
+        center: Point2D<F>,
+        width: F,
+        height: F,
+    },
+    Circle {
+        center: Point2D<F>,
+        diameter: F,
+    },
+    LinearPath {
+        points: Vec<Point2D<F>>,
+        close: bool,
+    },
+    Arc {
+        center: Point2D<F>,
+        width: F,
+        height: F,
+        start: F,
+        stop: F,
+        mode: ArcMode,
+    },
+    BezierQuadratic {
+        start: Point2D<F>,
+        cp: Point2D<F>,
+        end: Point2D<F>,
+    },
+    BezierCubic {
+        start: Point2D<F>,
+        cp1: Point2D<F>,
+        cp2: Point2D<F>,
+        end: Point2D<F>,
+    },
+    Curve {
+        points: Vec<Point2D<F>>,
+    },
+    Polygon {
+        points: Vec<Point2D<F>>,
+    },
+    PolygonWithHoles {
+        outer: Vec<Point2D<F>>,
+        holes: Vec<Vec<Point2D<F>>>,
+    },
+    MultiPolygon {
+        polygons: Vec<Vec<Vec<Point2D<F>>>>,
+    },
+    Path {
+        d: String,
+    },
+    PathSegments {
+        segments: Vec<PathSegment>,
+    },
+}
+
+impl<F: Float + Trig> ShapeKind<F> {
+    /// Rule to fill the [`OpSetType::FillPath`] sets of the shape with. An explicit
+    /// [`Options::fill_rule`] always wins. Otherwise outlines that may cross themselves
+    /// and polygons with holes are filled even-odd and all other shapes non-zero.
+    pub fn fill_rule(&self, options: &Options) -> FillRule {
+        options.fill_rule.unwrap_or(match self {
+            ShapeKind::BezierQuadratic { .. }
+            | ShapeKind::BezierCubic { .. }
+            | ShapeKind::Curve { .. }
+            | ShapeKind::Polygon { .. }
+            | ShapeKind::Path { .. }
+            | ShapeKind::PathSegments { .. }
+            | ShapeKind::PolygonWithHoles { .. }
+            | ShapeKind::MultiPolygon { .. } => FillRule::EvenOdd,
+            _ => FillRule::NonZero,
+        })
+    }
 }
 
 pub struct Drawable<F: Float + Trig> {
-    pub shape: String,
+    pub shape: ShapeKind<F>,
     pub options: Options,
     pub sets: Vec<OpSet<F>>,
 }
@@ -245,3 +685,56 @@ pub fn _c<U: Float + FromPrimitive>(inp: f32) -> U {
 pub fn _cc<U: Float + FromPrimitive>(inp: f64) -> U {
     U::from(inp).expect("can not parse from f64")
 }
+
+#[cfg(test)]
+mod test {
+    use std::mem::size_of;
+    use std::sync::Arc;
+
+    use euclid::point2;
+
+    use super::{Op, ToneMap};
+
+    #[test]
+    fn ops_are_compact() {
+        // six coordinates of a cubic bezier plus a discriminant no wider than one of them
+        assert!(size_of::<Op<f32>>() <= 7 * size_of::<f32>());
+        assert!(size_of::<Op<f64>>() <= 7 * size_of::<f64>());
+    }
+
+    #[test]
+    fn tone_at() {
+        let linear = ToneMap::Linear {
+            start: point2(0.0, 0.0),
+            end: point2(64.0, 0.0),
+            start_tone: 0.0,
+            end_tone: 1.0,
+        };
+        assert_eq!(linear.tone_at(-10.0, 5.0), 0.0);
+        assert_eq!(linear.tone_at(32.0, 5.0), 0.5);
+        assert_eq!(linear.tone_at(100.0, 5.0), 1.0);
+
+        let radial = ToneMap::Radial {
+            center: point2(10.0, 10.0),
+            radius: 20.0,
+            center_tone: 1.0,
+            edge_tone: 0.0,
+        };
+        assert_eq!(radial.tone_at(10.0, 10.0), 1.0);
+        assert_eq!(radial.tone_at(10.0, 20.0), 0.5);
+        assert_eq!(radial.tone_at(22.0, 26.0), 0.0);
+        assert_eq!(radial.tone_at(-50.0, 10.0), 0.0);
+        let point = ToneMap::Radial {
+            center: point2(10.0, 10.0),
+            radius: 0.0,
+            center_tone: 1.0,
+            edge_tone: 0.0,
+        };
+        assert_eq!(point.tone_at(10.0, 10.0), 0.0);
+
+        let function = ToneMap::Function(Arc::new(|x, _| x * 2.0));
+        assert_eq!(function.tone_at(0.25, 0.0), 0.5);
+        assert_eq!(function.tone_at(3.0, 0.0), 1.0);
+        assert_eq!(function.tone_at(-1.0, 0.0), 0.0);
+    }
+}
@@ -4,31 +4,34 @@ use std::marker::PhantomData;
 use euclid::default::Point2D;
 use euclid::{point2, Trig};
 use num_traits::{Float, FromPrimitive};
+use rand::Rng;
 
 use super::scan_line_hachure::polygon_hachure_lines;
 use super::traits::PatternFiller;
-use crate::core::{OpSet, Options, _c};
+use crate::core::{_c, Options};
 use crate::geometry::Line;
 use crate::renderer::_double_line;
+use crate::sink::PathSink;
 
 pub struct DashedFiller<F> {
     _phantom: PhantomData<F>,
 }
 
-impl<F, P> PatternFiller<F, P> for DashedFiller<F>
+impl<F, P, R> PatternFiller<F, P, R> for DashedFiller<F>
 where
     F: Float + Trig + FromPrimitive,
     P: BorrowMut<Vec<Vec<Point2D<F>>>>,
+    R: Rng + Clone,
 {
-    fn fill_polygons(&self, mut polygon_list: P, o: &mut Options) -> crate::core::OpSet<F> {
-        let lines = polygon_hachure_lines(polygon_list.borrow_mut(), o);
-        let ops = DashedFiller::dashed_line(lines, o);
-        OpSet {
-            op_set_type: crate::core::OpSetType::FillSketch,
-            ops,
-            size: None,
-            path: None,
-        }
+    fn fill_polygons_into(
+        &self,
+        mut polygon_list: P,
+        o: &Options,
+        rng: &mut R,
+        sink: &mut dyn PathSink<F>,
+    ) {
+        let lines = polygon_hachure_lines(polygon_list.borrow_mut(), o, rng);
+        DashedFiller::dashed_line(lines, o, rng, sink);
     }
 }
 impl<'a, F: Float + Trig + FromPrimitive> DashedFiller<F> {
@@ -36,7 +39,12 @@ impl<'a, F: Float + Trig + FromPrimitive> DashedFiller<F> {
         DashedFiller { _phantom: PhantomData }
     }
 
-    fn dashed_line(lines: Vec<Line<F>>, o: &mut Options) -> Vec<crate::core::Op<F>> {
+    fn dashed_line<R: Rng + ?Sized>(
+        lines: Vec<Line<F>>,
+        o: &Options,
+        rng: &mut R,
+        sink: &mut dyn PathSink<F>,
+    ) {
         let dash_offset: F = o.dash_offset.map(_c).unwrap_or_else(|| _c(-1.0));
         let offset = if dash_offset < _c(0.0) {
             let hachure_gap: F = o.hachure_gap.map(_c).unwrap_or_else(|| _c(-1.0));
@@ -60,8 +68,6 @@ impl<'a, F: Float + Trig + FromPrimitive> DashedFiller<F> {
             dash_gap
         };
 
-        let mut ops = vec![];
-
         for line in lines.iter() {
             let length = line.length();
             let count = (length / (offset + gap)).floor();
@@ -88,11 +94,8 @@ impl<'a, F: Float + Trig + FromPrimitive> DashedFiller<F> {
                     p1.y + (lend * num_traits::Float::sin(alpha))
                         + (start_offset * num_traits::Float::sin(alpha)),
                 );
-                let line_ops = _double_line(start.x, start.y, end.x, end.y, o, false);
-                ops.extend(line_ops);
+                _double_line(start, end, o, rng, false, sink);
             }
         }
-
-        ops
     }
 }
@@ -2,34 +2,41 @@ use std::borrow::BorrowMut;
 use std::marker::PhantomData;
 
 use euclid::default::Point2D;
-use euclid::Trig;
+use euclid::{point2, vec2, Trig};
 use num_traits::{Float, FromPrimitive};
+use rand::Rng;
 
 use super::scan_line_hachure::polygon_hachure_lines;
 use super::traits::PatternFiller;
-use crate::core::{OpSet, Options, _c, _cc};
+use crate::core::{_c, _cc, Options};
 use crate::geometry::Line;
-use crate::renderer::ellipse;
+use crate::renderer::_ellipse;
+use crate::sink::PathSink;
+
+/// Largest radius of dots drawn as exact ellipses. Any roughness would be lost on dots
+/// that small, larger ones are drawn rough.
+const MAX_EXACT_DOT_RADIUS: f32 = 2.0;
 
 pub struct DotFiller<F> {
     _phantom: PhantomData<F>,
 }
 
-impl<F, P> PatternFiller<F, P> for DotFiller<F>
+impl<F, P, R> PatternFiller<F, P, R> for DotFiller<F>
 where
     F: Float + Trig + FromPrimitive,
     P: BorrowMut<Vec<Vec<Point2D<F>>>>,
+    R: Rng + Clone,
 {
-    fn fill_polygons(&self, mut polygon_list: P, o: &mut Options) -> crate::core::OpSet<F> {
-        o.set_hachure_angle(Some(0.0));
-        let lines = polygon_hachure_lines(polygon_list.borrow_mut(), o);
-        let ops = DotFiller::dots_on_line(lines, o);
-        OpSet {
-            op_set_type: crate::core::OpSetType::FillSketch,
-            ops,
-            size: None,
-            path: None,
-        }
+    fn fill_polygons_into(
+        &self,
+        mut polygon_list: P,
+        o: &Options,
+        rng: &mut R,
+        sink: &mut dyn PathSink<F>,
+    ) {
+        let o = &Options { hachure_angle: Some(0.0), ..o.clone() };
+        let lines = polygon_hachure_lines(polygon_list.borrow_mut(), o, rng);
+        DotFiller::dots_on_line(lines, o, rng, sink);
     }
 }
 impl<F: Float + Trig + FromPrimitive> DotFiller<F> {
@@ -37,8 +44,12 @@ impl<F: Float + Trig + FromPrimitive> DotFiller<F> {
         DotFiller { _phantom: PhantomData }
     }
 
-    fn dots_on_line(lines: Vec<Line<F>>, o: &mut Options) -> Vec<crate::core::Op<F>> {
-        let mut ops = vec![];
+    fn dots_on_line<R: Rng + ?Sized>(
+        lines: Vec<Line<F>>,
+        o: &Options,
+        rng: &mut R,
+        sink: &mut dyn PathSink<F>,
+    ) {
         let mut gap = o.hachure_gap.map(_c::<F>).unwrap_or_else(|| _c::<F>(-1.0));
         if gap < F::zero() {
             gap = o.stroke_width.map(_c::<F>).unwrap_or_else(|| _c::<F>(1.0)) * _c::<F>(4.0);
@@ -62,14 +73,16 @@ impl<F: Float + Trig + FromPrimitive> DotFiller<F> {
             let min_y = F::min(line.start_point.y, line.end_point.y);
             for i in 0..count.to_u64().unwrap() {
                 let y = min_y + offset + (F::from(i).unwrap() * gap);
-                let cx = (x - ro) + _cc::<F>(o.random()) * _c::<F>(2.0) * ro;
-                let cy = (y - ro) + _cc::<F>(o.random()) * _c::<F>(2.0) * ro;
-                let ellipse_ops = ellipse(cx, cy, fweight, fweight, o);
-                ops.extend(ellipse_ops.ops);
+                let cx = (x - ro) + _cc::<F>(rng.gen()) * _c::<F>(2.0) * ro;
+                let cy = (y - ro) + _cc::<F>(rng.gen()) * _c::<F>(2.0) * ro;
+                let radius = fweight / _c::<F>(2.0);
+                if radius <= _c::<F>(MAX_EXACT_DOT_RADIUS) {
+                    sink.ellipse(point2(cx, cy), vec2(radius, radius));
+                } else {
+                    _ellipse(point2(cx, cy), fweight, fweight, o, rng, sink);
+                }
             }
         }
-
-        ops
     }
 }
 
@@ -78,3 +91,38 @@ impl<F: Float + Trig + FromPrimitive> Default for DotFiller<F> {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use euclid::point2;
+
+    use super::DotFiller;
+    use crate::core::{Op, OptionsBuilder};
+    use crate::filler::traits::PatternFiller;
+
+    #[test]
+    fn fill_polygons() {
+        let square = vec![vec![
+            point2(0.0, 0.0),
+            point2(40.0, 0.0),
+            point2(40.0, 40.0),
+            point2(0.0, 40.0),
+        ]];
+        let filler = DotFiller::<f64>::new();
+        let exact = |op: &Op<f64>| matches!(op, Op::Ellipse { .. });
+
+        let small = OptionsBuilder::default().fill_weight(2.0).build().unwrap();
+        let set = filler.fill_polygons(square.clone(), &small, &mut small.rng());
+        assert!(!set.ops.is_empty());
+        assert!(set.ops.iter().all(exact));
+
+        let large = OptionsBuilder::default()
+            .fill_weight(8.0)
+            .hachure_gap(10.0)
+            .build()
+            .unwrap();
+        let set = filler.fill_polygons(square, &large, &mut large.rng());
+        assert!(!set.ops.is_empty());
+        assert!(!set.ops.iter().any(exact));
+    }
+}
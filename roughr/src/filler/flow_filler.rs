@@ -0,0 +1,379 @@
+use std::borrow::BorrowMut;
+use std::collections::VecDeque;
+use std::marker::PhantomData;
+
+use euclid::default::{Point2D, Vector2D};
+use euclid::{point2, vec2, Trig};
+use num_traits::{Float, FromPrimitive};
+use rand::Rng;
+
+use super::grid_scale;
+use super::traits::PatternFiller;
+use crate::core::{_c, FillRule, Options};
+use crate::geometry::point_in_polygons;
+use crate::renderer::_double_curve;
+use crate::sink::PathSink;
+
+/// Upper bound on the number of steps of a single streamline.
+const MAX_STREAMLINE_STEPS: usize = 10_000;
+
+/// Number of integration steps taken per hachure gap.
+const STREAMLINE_STEPS_PER_GAP: usize = 3;
+
+/// Most cells of the grid the streamlines are bucketed and seeded in. Shapes too large to
+/// fill at the set gap with so many cells get streamlines further apart.
+const MAX_GRID_CELLS: usize = 1 << 16;
+
+pub struct FlowFiller<F> {
+    _phantom: PhantomData<F>,
+}
+
+impl<F, P, R> PatternFiller<F, P, R> for FlowFiller<F>
+where
+    F: Float + Trig + FromPrimitive,
+    P: BorrowMut<Vec<Vec<Point2D<F>>>>,
+    R: Rng + Clone,
+{
+    fn fill_polygons_into(
+        &self,
+        mut polygon_list: P,
+        o: &Options,
+        rng: &mut R,
+        sink: &mut dyn PathSink<F>,
+    ) {
+        let mut gap = o.hachure_gap.map(_c::<F>).unwrap_or_else(|| _c::<F>(-1.0));
+        if gap < F::zero() {
+            gap = o.stroke_width.map(_c::<F>).unwrap_or_else(|| _c::<F>(1.0)) * _c::<F>(4.0);
+        }
+        gap = gap.max(_c::<F>(0.1));
+
+        let fill_rule = o.fill_rule.unwrap_or_default();
+        let streamlines = StreamlinePlacer::new(polygon_list.borrow_mut(), gap, fill_rule).place();
+        // The streamlines are traced in small steps, the curves only need a point every
+        // couple of gaps, which also keeps the roughness offsets from piling up.
+        let stride = STREAMLINE_STEPS_PER_GAP * 2;
+        for streamline in streamlines.iter() {
+            let mut points: Vec<Point2D<F>> = streamline.iter().step_by(stride).copied().collect();
+            if (streamline.len() - 1) % stride != 0 {
+                points.push(streamline[streamline.len() - 1]);
+            }
+            _double_curve(&points, o, rng, true, sink);
+        }
+    }
+}
+
+impl<F: Float + Trig + FromPrimitive> FlowFiller<F> {
+    pub fn new() -> Self {
+        FlowFiller { _phantom: PhantomData }
+    }
+}
+
+impl<F: Float + Trig + FromPrimitive> Default for FlowFiller<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Places evenly spaced streamlines of a direction field that follows the outline, after
+/// Jobard and Lefer. New streamlines are seeded one gap away from the accepted ones and
+/// stop when they leave the shape or come closer than half a gap to another streamline.
+struct StreamlinePlacer<'a, F: Float + Trig> {
+    polygon_list: &'a [Vec<Point2D<F>>],
+    fill_rule: FillRule,
+    gap: F,
+    step: F,
+    origin: Point2D<F>,
+    columns: usize,
+    rows: usize,
+    /// Points of the accepted streamlines and of the one being traced, bucketed into cells
+    /// of one gap.
+    grid: Vec<Vec<Point2D<F>>>,
+}
+
+impl<'a, F: Float + Trig + FromPrimitive> StreamlinePlacer<'a, F> {
+    fn new(polygon_list: &'a [Vec<Point2D<F>>], gap: F, fill_rule: FillRule) -> Self {
+        let (mut min_x, mut min_y) = (F::infinity(), F::infinity());
+        let (mut max_x, mut max_y) = (F::neg_infinity(), F::neg_infinity());
+        for p in polygon_list.iter().flatten() {
+            min_x = min_x.min(p.x);
+            min_y = min_y.min(p.y);
+            max_x = max_x.max(p.x);
+            max_y = max_y.max(p.y);
+        }
+        let (gap, columns, rows) =
+            match grid_scale(max_x - min_x, max_y - min_y, gap, gap, MAX_GRID_CELLS) {
+                Some(scale) if max_x > min_x && max_y > min_y => {
+                    let gap = gap * scale;
+                    (
+                        gap,
+                        ((max_x - min_x) / gap).ceil().to_usize().unwrap_or(0) + 1,
+                        ((max_y - min_y) / gap).ceil().to_usize().unwrap_or(0) + 1,
+                    )
+                }
+                _ => (gap, 0, 0),
+            };
+        StreamlinePlacer {
+            polygon_list,
+            fill_rule,
+            gap,
+            step: gap / F::from(STREAMLINE_STEPS_PER_GAP).unwrap(),
+            origin: point2(min_x, min_y),
+            columns,
+            rows,
+            grid: vec![vec![]; columns * rows],
+        }
+    }
+
+    fn place(&mut self) -> Vec<Vec<Point2D<F>>> {
+        let mut streamlines: Vec<Vec<Point2D<F>>> = vec![];
+        // Seeds next to accepted streamlines come first, the lattice of the bounding box
+        // only picks up the parts of the shape no streamline has reached.
+        let mut seeds: VecDeque<Point2D<F>> = VecDeque::new();
+        let columns = self.columns;
+        let mut lattice =
+            (0..self.rows).flat_map(|row| (0..columns).map(move |column| (column, row)));
+        loop {
+            let seed = match seeds.pop_front() {
+                Some(seed) => seed,
+                None => match lattice.next() {
+                    Some((column, row)) => {
+                        self.origin
+                            + vec2(
+                                (F::from(column).unwrap() + _c(0.5)) * self.gap,
+                                (F::from(row).unwrap() + _c(0.5)) * self.gap,
+                            )
+                    }
+                    None => break,
+                },
+            };
+            if !self.inside(seed) || self.is_near(seed, self.gap * _c(0.9)) {
+                continue;
+            }
+
+            let direction = self.direction(seed);
+            let mut inserted = vec![];
+            let forward = self.trace(seed, direction, &mut inserted);
+            let mut backward = self.trace(seed, -direction, &mut inserted);
+            backward.reverse();
+            backward.push(seed);
+            backward.extend(forward);
+            if backward.len() < 3 {
+                // the points of a rejected streamline must not keep others away
+                for cell in inserted.into_iter().rev() {
+                    self.grid[cell].pop();
+                }
+                continue;
+            }
+            self.insert(seed);
+
+            for (i, p) in backward.iter().enumerate() {
+                let next = backward[(i + 1).min(backward.len() - 1)];
+                let previous = backward[i.saturating_sub(1)];
+                let tangent = next - previous;
+                if tangent.length() > F::zero() {
+                    let normal = vec2(-tangent.y, tangent.x) / tangent.length() * self.gap;
+                    seeds.push_back(*p + normal);
+                    seeds.push_back(*p - normal);
+                }
+            }
+            streamlines.push(backward);
+        }
+        streamlines
+    }
+
+    /// Follows the field from `start` with midpoint steps, keeping the heading consistent.
+    /// The traced points go into the grid as well, and the cells they went into are added to
+    /// `inserted`.
+    fn trace(
+        &mut self,
+        start: Point2D<F>,
+        direction: Vector2D<F>,
+        inserted: &mut Vec<usize>,
+    ) -> Vec<Point2D<F>> {
+        let mut points = vec![];
+        let mut current = start;
+        let mut heading = direction;
+        // Points of the streamline itself only go into the grid once they are a couple of
+        // gaps behind, so that closed streamlines end where they started.
+        let skip = (self.gap * _c(2.0) / self.step)
+            .ceil()
+            .to_usize()
+            .unwrap_or(0);
+        for _ in 0..MAX_STREAMLINE_STEPS {
+            let half = self.aligned(self.direction(current), heading);
+            let middle = current + half * (self.step / _c(2.0));
+            let full = self.aligned(self.direction(middle), heading);
+            let next = current + full * self.step;
+            if !self.inside(next) || self.is_near(next, self.gap * _c(0.5)) {
+                break;
+            }
+            points.push(next);
+            if points.len() > skip {
+                inserted.extend(self.insert(points[points.len() - 1 - skip]));
+            }
+            heading = full;
+            current = next;
+        }
+        for p in points[points.len().saturating_sub(skip)..].iter() {
+            inserted.extend(self.insert(*p));
+        }
+        points
+    }
+
+    /// Unit direction of the outline around `p`. Edge directions are blended by inverse
+    /// squared distance as doubled angles, so that opposite edges reinforce each other.
+    fn direction(&self, p: Point2D<F>) -> Vector2D<F> {
+        let mut sum: Vector2D<F> = vec2(F::zero(), F::zero());
+        for polygon in self.polygon_list.iter() {
+            for i in 0..polygon.len() {
+                let a = polygon[i];
+                let b = polygon[(i + 1) % polygon.len()];
+                let edge = b - a;
+                let length = edge.length();
+                if length <= F::zero() {
+                    continue;
+                }
+                let t = ((p - a).dot(edge) / (length * length))
+                    .max(F::zero())
+                    .min(F::one());
+                let distance = (a + edge * t - p).length();
+                let weight = length / (distance * distance + self.step * self.step);
+                let (x, y) = (edge.x / length, edge.y / length);
+                sum += vec2(x * x - y * y, _c::<F>(2.0) * x * y) * weight;
+            }
+        }
+        let angle = sum.y.atan2(sum.x) / _c(2.0);
+        vec2(Float::cos(angle), Float::sin(angle))
+    }
+
+    fn aligned(&self, direction: Vector2D<F>, heading: Vector2D<F>) -> Vector2D<F> {
+        if direction.dot(heading) < F::zero() {
+            -direction
+        } else {
+            direction
+        }
+    }
+
+    fn inside(&self, p: Point2D<F>) -> bool {
+        point_in_polygons(p, self.polygon_list, self.fill_rule)
+    }
+
+    fn cell(&self, p: Point2D<F>) -> Option<(usize, usize)> {
+        let x = ((p.x - self.origin.x) / self.gap).floor().to_i64()?;
+        let y = ((p.y - self.origin.y) / self.gap).floor().to_i64()?;
+        if x < 0 || y < 0 || x as usize >= self.columns || y as usize >= self.rows {
+            return None;
+        }
+        Some((x as usize, y as usize))
+    }
+
+    /// Adds `p` to the grid, returning the index of the cell it went into.
+    fn insert(&mut self, p: Point2D<F>) -> Option<usize> {
+        let (x, y) = self.cell(p)?;
+        let cell = y * self.columns + x;
+        self.grid[cell].push(p);
+        Some(cell)
+    }
+
+    /// Whether a point of a streamline lies within `distance` of `p`.
+    fn is_near(&self, p: Point2D<F>, distance: F) -> bool {
+        let (x, y) = match self.cell(p) {
+            Some(cell) => cell,
+            None => return false,
+        };
+        for cy in y.saturating_sub(1)..(y + 2).min(self.rows) {
+            for cx in x.saturating_sub(1)..(x + 2).min(self.columns) {
+                for q in self.grid[cy * self.columns + cx].iter() {
+                    if (*q - p).length() < distance {
+                        return true;
+                    }
+                }
+            }
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use euclid::default::Point2D;
+    use euclid::point2;
+
+    use super::{FlowFiller, StreamlinePlacer, MAX_GRID_CELLS, MAX_STREAMLINE_STEPS};
+    use crate::core::{FillRule, OptionsBuilder};
+    use crate::filler::traits::PatternFiller;
+
+    #[test]
+    fn streamlines_follow_outline() {
+        let square = vec![vec![
+            point2(0.0, 0.0),
+            point2(40.0, 0.0),
+            point2(40.0, 40.0),
+            point2(0.0, 40.0),
+        ]];
+        let streamlines = StreamlinePlacer::new(&square, 4.0, FillRule::EvenOdd).place();
+        assert!(!streamlines.is_empty());
+
+        // Close to the bottom edge the strokes run along it.
+        let near_bottom = streamlines
+            .iter()
+            .flat_map(|s| s.windows(2))
+            .filter(|w| w[0].y < 3.0 && w[0].x > 10.0 && w[0].x < 30.0);
+        for w in near_bottom {
+            let d: euclid::default::Vector2D<f64> = w[1] - w[0];
+            assert!(d.y.abs() < d.x.abs());
+        }
+    }
+
+    #[test]
+    fn streamlines_close_on_themselves() {
+        // a ring, around which the streamlines run in loops
+        let circle = |radius: f64| -> Vec<Point2D<f64>> {
+            (0..64)
+                .map(|i| {
+                    let angle = i as f64 * std::f64::consts::PI / 32.0;
+                    point2(20.0 + radius * angle.cos(), 20.0 + radius * angle.sin())
+                })
+                .collect()
+        };
+        let ring = vec![circle(20.0), circle(10.0)];
+        let gap = 4.0;
+        let mut placer = StreamlinePlacer::new(&ring, gap, FillRule::EvenOdd);
+        let seed = point2(20.0, 5.0);
+        let direction = placer.direction(seed);
+        let mut inserted = vec![];
+        let forward = placer.trace(seed, direction, &mut inserted);
+
+        assert!(forward.len() > 10 && forward.len() < MAX_STREAMLINE_STEPS);
+        assert_eq!(inserted.len(), forward.len());
+        for p in forward.iter() {
+            assert!(placer.is_near(*p, 1e-9));
+        }
+        // the loop ends before running into its own start
+        let end = forward[forward.len() - 1];
+        assert!((end - forward[0]).length() >= gap * 0.5);
+        assert!((end - seed).length() < gap * 3.0);
+        // and the way back is blocked right away
+        assert!(placer.trace(seed, -direction, &mut inserted).len() < 3);
+    }
+
+    #[test]
+    fn huge_shapes() {
+        let square = vec![vec![
+            point2(0.0, 0.0),
+            point2(1e7, 0.0),
+            point2(1e7, 1e7),
+            point2(0.0, 1e7),
+        ]];
+        let placer = StreamlinePlacer::new(&square, 4.0, FillRule::EvenOdd);
+        assert!(placer.columns * placer.rows <= MAX_GRID_CELLS);
+
+        let o = OptionsBuilder::default()
+            .hachure_gap(4.0)
+            .disable_multi_stroke_fill(true)
+            .build()
+            .unwrap();
+        let set = FlowFiller::<f64>::new().fill_polygons(square, &o, &mut o.rng());
+        assert!(!set.ops.is_empty());
+    }
+}
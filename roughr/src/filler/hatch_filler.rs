@@ -4,29 +4,39 @@ use std::marker::PhantomData;
 use euclid::default::Point2D;
 use euclid::Trig;
 use num_traits::{Float, FromPrimitive};
+use rand::Rng;
 
 use super::scan_line_hachure::ScanlineHachureFiller;
 use super::traits::PatternFiller;
 use crate::core::Options;
+use crate::sink::PathSink;
 
 pub struct HatchFiller<F> {
     _phantom: PhantomData<F>,
     hachure_filler: ScanlineHachureFiller<F>,
 }
 
-impl<F, P> PatternFiller<F, P> for HatchFiller<F>
+impl<F, P, R> PatternFiller<F, P, R> for HatchFiller<F>
 where
     F: Float + Trig + FromPrimitive,
     P: BorrowMut<Vec<Vec<Point2D<F>>>>,
+    R: Rng + Clone,
 {
-    fn fill_polygons(&self, mut polygon_list: P, o: &mut Options) -> crate::core::OpSet<F> {
-        let mut set1 = self
-            .hachure_filler
-            .fill_polygons(polygon_list.borrow_mut(), o);
-        o.set_hachure_angle(o.hachure_angle.map(|a| a + 90.0));
-        let set2 = self.hachure_filler.fill_polygons(polygon_list, o);
-        set1.ops.extend(set2.ops);
-        set1
+    fn fill_polygons_into(
+        &self,
+        mut polygon_list: P,
+        o: &Options,
+        rng: &mut R,
+        sink: &mut dyn PathSink<F>,
+    ) {
+        self.hachure_filler
+            .fill_polygons_into(polygon_list.borrow_mut(), o, rng, sink);
+        let crossed = Options {
+            hachure_angle: o.hachure_angle.map(|a| a + 90.0),
+            ..o.clone()
+        };
+        self.hachure_filler
+            .fill_polygons_into(polygon_list, &crossed, rng, sink);
     }
 }
 
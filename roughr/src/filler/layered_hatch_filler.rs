@@ -0,0 +1,162 @@
+use std::borrow::BorrowMut;
+use std::marker::PhantomData;
+
+use euclid::default::Point2D;
+use euclid::Trig;
+use num_traits::{Float, FromPrimitive};
+use rand::Rng;
+
+use super::scan_line_hachure::ScanlineHachureFiller;
+use super::traits::PatternFiller;
+use crate::core::{HatchLayer, OpSetStyle, OpSetType, Options};
+use crate::sink::PathSink;
+
+pub struct LayeredHatchFiller<F> {
+    _phantom: PhantomData<F>,
+    hachure_filler: ScanlineHachureFiller<F>,
+}
+
+impl<F, P, R> PatternFiller<F, P, R> for LayeredHatchFiller<F>
+where
+    F: Float + Trig + FromPrimitive,
+    P: BorrowMut<Vec<Vec<Point2D<F>>>>,
+    R: Rng + Clone,
+{
+    /// Draws all layers one after the other, dropping their individual styles.
+    fn fill_polygons_into(
+        &self,
+        mut polygon_list: P,
+        o: &Options,
+        rng: &mut R,
+        sink: &mut dyn PathSink<F>,
+    ) {
+        for layer in LayeredHatchFiller::<F>::layers(o).iter() {
+            self.hachure_filler.fill_polygons_into(
+                polygon_list.borrow_mut(),
+                &LayeredHatchFiller::<F>::layer_options(layer, o),
+                rng,
+                sink,
+            );
+        }
+    }
+
+    fn fill_polygon_sets_into(
+        &self,
+        mut polygon_list: P,
+        o: &Options,
+        rng: &mut R,
+        sink: &mut dyn PathSink<F>,
+    ) {
+        for layer in LayeredHatchFiller::<F>::layers(o).iter() {
+            let style = OpSetStyle {
+                color: layer.color,
+                weight: layer.weight,
+                line_dash: layer.line_dash.clone(),
+                line_dash_offset: layer.line_dash_offset,
+            };
+            sink.begin_set(&OpSetType::FillSketch, Some(&style));
+            self.hachure_filler.fill_polygons_into(
+                polygon_list.borrow_mut(),
+                &LayeredHatchFiller::<F>::layer_options(layer, o),
+                rng,
+                sink,
+            );
+            sink.end_set();
+        }
+    }
+}
+
+impl<F: Float + Trig + FromPrimitive> LayeredHatchFiller<F> {
+    pub fn new() -> Self {
+        LayeredHatchFiller {
+            _phantom: PhantomData,
+            hachure_filler: ScanlineHachureFiller::new(),
+        }
+    }
+
+    /// The configured layers, or a plain cross-hatch when none are set.
+    fn layers(o: &Options) -> Vec<HatchLayer> {
+        match &o.hatch_layers {
+            Some(layers) if !layers.is_empty() => layers.clone(),
+            _ => {
+                let angle = o.hachure_angle.unwrap_or(0.0);
+                vec![
+                    HatchLayer { angle, ..HatchLayer::default() },
+                    HatchLayer { angle: angle + 90.0, ..HatchLayer::default() },
+                ]
+            }
+        }
+    }
+
+    /// Hachure options of a single layer.
+    fn layer_options(layer: &HatchLayer, o: &Options) -> Options {
+        Options {
+            hachure_angle: Some(layer.angle),
+            hachure_gap: layer.gap.or(o.hachure_gap),
+            ..o.clone()
+        }
+    }
+}
+
+impl<F: Float + Trig + FromPrimitive> Default for LayeredHatchFiller<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use euclid::point2;
+    use palette::Srgba;
+
+    use super::LayeredHatchFiller;
+    use crate::core::{HatchLayer, OptionsBuilder};
+    use crate::filler::traits::PatternFiller;
+
+    #[test]
+    fn fill_polygon_sets() {
+        let square = vec![vec![
+            point2(0.0, 0.0),
+            point2(40.0, 0.0),
+            point2(40.0, 40.0),
+            point2(0.0, 40.0),
+        ]];
+        let red = Srgba::new(1.0, 0.0, 0.0, 1.0);
+        let o = OptionsBuilder::default()
+            .hachure_angle(-41.0)
+            .hatch_layers(vec![
+                HatchLayer {
+                    angle: 0.0,
+                    gap: Some(4.0),
+                    ..HatchLayer::default()
+                },
+                HatchLayer {
+                    angle: 60.0,
+                    color: Some(red),
+                    weight: Some(2.0),
+                    ..HatchLayer::default()
+                },
+                HatchLayer {
+                    angle: 120.0,
+                    line_dash: Some(vec![2.0, 1.0]),
+                    ..HatchLayer::default()
+                },
+            ])
+            .build()
+            .unwrap();
+        let filler = LayeredHatchFiller::<f64>::new();
+        let sets = filler.fill_polygon_sets(square.clone(), &o, &mut o.rng());
+
+        assert_eq!(sets.len(), 3);
+        assert!(sets.iter().all(|s| !s.ops.is_empty()));
+        let styles: Vec<_> = sets.iter().map(|s| s.style.clone().unwrap()).collect();
+        assert_eq!(styles[0].color, None);
+        assert_eq!(styles[1].color, Some(red));
+        assert_eq!(styles[1].weight, Some(2.0));
+        assert_eq!(styles[2].line_dash, Some(vec![2.0, 1.0]));
+        assert_eq!(o.hachure_angle, Some(-41.0));
+
+        let merged = filler.fill_polygons(square, &o, &mut o.rng());
+        assert_eq!(merged.style, None);
+    }
+}
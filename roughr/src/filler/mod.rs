@@ -3,20 +3,39 @@ use std::borrow::BorrowMut;
 use euclid::default::Point2D;
 use euclid::Trig;
 use num_traits::{Float, FromPrimitive};
+use rand::Rng;
 
 use self::dashed_filler::DashedFiller;
 use self::dot_filler::DotFiller;
+use self::flow_filler::FlowFiller;
 use self::hatch_filler::HatchFiller;
+use self::layered_hatch_filler::LayeredHatchFiller;
+use self::motif_filler::MotifFiller;
 use self::scan_line_hachure::ScanlineHachureFiller;
+use self::scribble_filler::ScribbleFiller;
+use self::space_filling_filler::{SpaceFillingCurve, SpaceFillingFiller};
+use self::stipple_filler::StippleFiller;
+use self::tone_hatch_filler::ToneHatchFiller;
 use self::traits::PatternFiller;
+use self::watercolor_filler::WatercolorFiller;
+use self::wavy_filler::WavyFiller;
 use self::zig_zag_filler::ZigZagFiller;
 use self::zig_zag_line_filler::ZigZagLineFiller;
 
 pub mod dashed_filler;
 pub mod dot_filler;
+pub mod flow_filler;
 pub mod hatch_filler;
+pub mod layered_hatch_filler;
+pub mod motif_filler;
 pub mod scan_line_hachure;
+pub mod scribble_filler;
+pub mod space_filling_filler;
+pub mod stipple_filler;
+pub mod tone_hatch_filler;
 pub mod traits;
+pub mod watercolor_filler;
+pub mod wavy_filler;
 pub mod zig_zag_filler;
 pub mod zig_zag_line_filler;
 
@@ -27,12 +46,23 @@ pub enum FillerType {
     HatchFiller,
     ZigZagFiller,
     ZigZagLineFiller,
+    StippleFiller,
+    WavyFiller,
+    ScribbleFiller,
+    LayeredHatchFiller,
+    ToneHatchFiller,
+    MotifFiller,
+    FlowFiller,
+    HilbertFiller,
+    SerpentineFiller,
+    WatercolorFiller,
 }
 
-pub fn get_filler<'a, F, P>(f: FillerType) -> Box<dyn PatternFiller<F, P> + 'a>
+pub fn get_filler<'a, F, P, R>(f: FillerType) -> Box<dyn PatternFiller<F, P, R> + 'a>
 where
     F: Float + Trig + FromPrimitive + 'a,
     P: BorrowMut<Vec<Vec<Point2D<F>>>>,
+    R: Rng + Clone,
 {
     match f {
         FillerType::ScanLineHachure => Box::new(ScanlineHachureFiller::new()),
@@ -41,5 +71,62 @@ where
         FillerType::HatchFiller => Box::new(HatchFiller::new()),
         FillerType::ZigZagFiller => Box::new(ZigZagFiller::new()),
         FillerType::ZigZagLineFiller => Box::new(ZigZagLineFiller::new()),
+        FillerType::StippleFiller => Box::new(StippleFiller::new()),
+        FillerType::WavyFiller => Box::new(WavyFiller::new()),
+        FillerType::ScribbleFiller => Box::new(ScribbleFiller::new()),
+        FillerType::LayeredHatchFiller => Box::new(LayeredHatchFiller::new()),
+        FillerType::ToneHatchFiller => Box::new(ToneHatchFiller::new()),
+        FillerType::MotifFiller => Box::new(MotifFiller::new()),
+        FillerType::FlowFiller => Box::new(FlowFiller::new()),
+        FillerType::HilbertFiller => Box::new(SpaceFillingFiller::new(SpaceFillingCurve::Hilbert)),
+        FillerType::SerpentineFiller => {
+            Box::new(SpaceFillingFiller::new(SpaceFillingCurve::Serpentine))
+        }
+        FillerType::WatercolorFiller => Box::new(WatercolorFiller::new()),
+    }
+}
+
+/// Factor of at least one to grow cells of `cell_width` by `cell_height` by, so that no more
+/// than `max_cells` of them, with a few to spare on every side, cover an area of `width` by
+/// `height`. Fillers laying a grid over a shape coarsen it like this for huge shapes, instead
+/// of running out of memory. `None` if the area is not finite.
+pub(crate) fn grid_scale<F: Float>(
+    width: F,
+    height: F,
+    cell_width: F,
+    cell_height: F,
+    max_cells: usize,
+) -> Option<F> {
+    if !(width.is_finite() && height.is_finite()) {
+        return None;
+    }
+    let spare = F::from(3.0).unwrap();
+    let cells = |scale: F| {
+        (width / (cell_width * scale) + spare) * (height / (cell_height * scale) + spare)
+    };
+    let max_cells = F::from(max_cells).unwrap();
+    let mut scale = F::one();
+    while cells(scale) > max_cells {
+        // long, thin areas shrink slower than the square root suggests
+        scale = scale * (cells(scale) / max_cells).sqrt().max(F::from(1.1).unwrap());
+    }
+    Some(scale)
+}
+
+#[cfg(test)]
+mod test {
+    use super::grid_scale;
+
+    #[test]
+    fn grid_scales() {
+        assert_eq!(grid_scale(100.0, 100.0, 2.0, 2.0, 1 << 18), Some(1.0));
+        for (width, height) in [(1e9, 1e9), (1e9, 1.0), (1.0, 1e12), (1e30, 1e30)] {
+            let scale = grid_scale(width, height, 2.0, 3.0, 1 << 16).unwrap();
+            let cells = (width / (2.0 * scale) + 3.0) * (height / (3.0 * scale) + 3.0);
+            assert!(cells <= (1 << 16) as f64);
+            // the cells grow no more than they need to
+            assert!(cells > (1 << 14) as f64);
+        }
+        assert_eq!(grid_scale(f64::INFINITY, 1.0, 2.0, 2.0, 1 << 16), None);
     }
 }
@@ -0,0 +1,240 @@
+use std::borrow::BorrowMut;
+use std::marker::PhantomData;
+
+use euclid::default::Point2D;
+use euclid::{point2, vec2, Trig};
+use num_traits::{Float, FromPrimitive};
+use rand::Rng;
+use svgtypes::{PathParser, PathSegment};
+
+use super::grid_scale;
+use super::traits::PatternFiller;
+use crate::core::{_c, _cc, Motif, MotifLayout, Options};
+use crate::geometry::clip_polyline;
+use crate::points_on_path::points_on_path;
+use crate::renderer::{_double_curve, _double_line};
+use crate::sink::PathSink;
+
+/// About the most motifs stamped over a shape. Larger shapes get larger motifs.
+const MAX_STAMPS: usize = 1 << 16;
+
+pub struct MotifFiller<F> {
+    _phantom: PhantomData<F>,
+}
+
+impl<F, P, R> PatternFiller<F, P, R> for MotifFiller<F>
+where
+    F: Float + Trig + FromPrimitive,
+    P: BorrowMut<Vec<Vec<Point2D<F>>>>,
+    R: Rng + Clone,
+{
+    fn fill_polygons_into(
+        &self,
+        mut polygon_list: P,
+        o: &Options,
+        rng: &mut R,
+        sink: &mut dyn PathSink<F>,
+    ) {
+        let polygon_list = polygon_list.borrow_mut();
+        let motif = o
+            .motif
+            .clone()
+            .unwrap_or_else(|| MotifFiller::<F>::default_motif(o));
+        let width = _c::<F>(motif.width.max(0.1));
+        let height = _c::<F>(motif.height.max(0.1));
+        let shape = MotifFiller::motif_polylines(&motif);
+        let straight = MotifFiller::<F>::is_straight(&motif);
+
+        let (mut min_x, mut min_y) = (F::infinity(), F::infinity());
+        let (mut max_x, mut max_y) = (F::neg_infinity(), F::neg_infinity());
+        for p in polygon_list.iter().flatten() {
+            min_x = min_x.min(p.x);
+            min_y = min_y.min(p.y);
+            max_x = max_x.max(p.x);
+            max_y = max_y.max(p.y);
+        }
+        let scale = match grid_scale(max_x - min_x, max_y - min_y, width, height, MAX_STAMPS) {
+            Some(scale) if !shape.is_empty() && max_x > min_x && max_y > min_y => scale,
+            _ => return,
+        };
+        let (width, height) = (width * scale, height * scale);
+        let shape: Vec<Vec<Point2D<F>>> = shape
+            .into_iter()
+            .map(|polyline| polyline.into_iter().map(|p| p * scale).collect())
+            .collect();
+
+        // Cells are anchored to the origin, so that neighbouring shapes share the lattice.
+        let jitter = _c::<F>(0.1 * o.roughness.unwrap_or(1.0));
+        let fill_rule = o.fill_rule.unwrap_or_default();
+        let first_row = (min_y / height).floor().to_i64().unwrap_or(0) - 1;
+        let last_row = (max_y / height).ceil().to_i64().unwrap_or(0);
+        for row in first_row..=last_row {
+            let shift = if motif.layout == MotifLayout::Staggered && row.rem_euclid(2) == 1 {
+                width / _c(2.0)
+            } else {
+                F::zero()
+            };
+            let first_column = ((min_x - shift) / width).floor().to_i64().unwrap_or(0) - 1;
+            let last_column = ((max_x - shift) / width).ceil().to_i64().unwrap_or(0);
+            for column in first_column..=last_column {
+                let offset = vec2(
+                    F::from(column).unwrap() * width
+                        + shift
+                        + (_cc::<F>(rng.gen()) - _c(0.5)) * width * jitter,
+                    F::from(row).unwrap() * height
+                        + (_cc::<F>(rng.gen()) - _c(0.5)) * height * jitter,
+                );
+                for polyline in shape.iter() {
+                    let stamp: Vec<Point2D<F>> = polyline.iter().map(|p| *p + offset).collect();
+                    for run in clip_polyline(&stamp, polygon_list, fill_rule) {
+                        if straight {
+                            for segment in run.windows(2) {
+                                _double_line(segment[0], segment[1], o, rng, true, sink);
+                            }
+                        } else {
+                            _double_curve(&run, o, rng, true, sink);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<F: Float + Trig + FromPrimitive> MotifFiller<F> {
+    pub fn new() -> Self {
+        MotifFiller { _phantom: PhantomData }
+    }
+
+    /// A small cross in cells of two hachure gaps, used when no motif is set.
+    fn default_motif(o: &Options) -> Motif {
+        let mut gap = o.hachure_gap.unwrap_or(-1.0);
+        if gap < 0.0 {
+            gap = o.stroke_width.unwrap_or(1.0) * 4.0;
+        }
+        let (a, b) = (gap * 0.6, gap * 1.4);
+        Motif::new(
+            format!("M{a} {a} L{b} {b} M{b} {a} L{a} {b}"),
+            gap * 2.0,
+            gap * 2.0,
+        )
+    }
+
+    /// Whether the motif is made of straight lines only. Their corners are kept, while
+    /// curved motifs are drawn as one rough curve for each of their runs.
+    fn is_straight(motif: &Motif) -> bool {
+        PathParser::from(motif.path.as_str())
+            .flatten()
+            .all(|segment| {
+                matches!(
+                    segment,
+                    PathSegment::MoveTo { .. }
+                        | PathSegment::LineTo { .. }
+                        | PathSegment::HorizontalLineTo { .. }
+                        | PathSegment::VerticalLineTo { .. }
+                        | PathSegment::ClosePath { .. }
+                )
+            })
+    }
+
+    /// Flattens the motif path into polylines, precise enough for the size of its cell.
+    fn motif_polylines(motif: &Motif) -> Vec<Vec<Point2D<F>>> {
+        let size = motif.width.min(motif.height).max(0.1);
+        points_on_path::<f32>(motif.path.clone(), Some(size * 0.02), Some(size * 0.02))
+            .into_iter()
+            .filter(|polyline| polyline.len() > 1)
+            .map(|polyline| {
+                polyline
+                    .iter()
+                    .map(|p| point2(_c::<F>(p.x), _c::<F>(p.y)))
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+impl<F: Float + Trig + FromPrimitive> Default for MotifFiller<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use euclid::point2;
+
+    use super::{MotifFiller, MAX_STAMPS};
+    use crate::core::{Motif, MotifLayout, Op, OptionsBuilder};
+    use crate::filler::traits::PatternFiller;
+
+    #[test]
+    fn fill_polygons() {
+        let square = vec![vec![
+            point2(0.0, 0.0),
+            point2(40.0, 0.0),
+            point2(40.0, 40.0),
+            point2(0.0, 40.0),
+        ]];
+        let motif = Motif::new("M2 5 L8 5", 10.0, 10.0).with_layout(MotifLayout::Staggered);
+        let o = OptionsBuilder::default()
+            .motif(motif)
+            .roughness(0.0)
+            .disable_multi_stroke_fill(true)
+            .build()
+            .unwrap();
+        let set = MotifFiller::<f64>::new().fill_polygons(square, &o, &mut o.rng());
+
+        // Every stamp stays inside the square, including the ones cut by its sides.
+        assert!(!set.ops.is_empty());
+        for op in set.ops.iter() {
+            for p in op.points() {
+                assert!(p.x >= -0.5 && p.x <= 40.5 && p.y >= -0.5 && p.y <= 40.5);
+            }
+        }
+    }
+
+    #[test]
+    fn curved_motifs() {
+        let square = vec![vec![
+            point2(0.0, 0.0),
+            point2(40.0, 0.0),
+            point2(40.0, 40.0),
+            point2(0.0, 40.0),
+        ]];
+        let o = OptionsBuilder::default()
+            .motif(Motif::new("M2 5 Q5 0 8 5", 10.0, 10.0))
+            .roughness(0.0)
+            .disable_multi_stroke_fill(true)
+            .build()
+            .unwrap();
+        let set = MotifFiller::<f64>::new().fill_polygons(square, &o, &mut o.rng());
+
+        // one stroke for each of the 16 stamps inside the square
+        let moves = set
+            .ops
+            .iter()
+            .filter(|op| matches!(op, Op::Move(_)))
+            .count();
+        assert_eq!(moves, 16);
+        assert!(set.ops.len() > 3 * moves);
+    }
+
+    #[test]
+    fn huge_shapes() {
+        let square = vec![vec![
+            point2(0.0, 0.0),
+            point2(1e9, 0.0),
+            point2(1e9, 1e9),
+            point2(0.0, 1e9),
+        ]];
+        let o = OptionsBuilder::default()
+            .motif(Motif::new("M2 5 L8 5", 10.0, 10.0))
+            .disable_multi_stroke_fill(true)
+            .build()
+            .unwrap();
+        let set = MotifFiller::<f64>::new().fill_polygons(square, &o, &mut o.rng());
+        // a move and a curve for each stamp
+        assert!(!set.ops.is_empty());
+        assert!(set.ops.len() <= 2 * MAX_STAMPS);
+    }
+}
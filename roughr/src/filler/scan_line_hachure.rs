@@ -5,10 +5,12 @@ use std::marker::PhantomData;
 use euclid::default::Point2D;
 use euclid::Trig;
 use num_traits::{Float, FromPrimitive};
+use rand::Rng;
 
 use super::traits::PatternFiller;
-use crate::core::{OpSet, Options, _c};
-use crate::geometry::{rotate_lines, rotate_points, Line};
+use crate::core::{_c, Options};
+use crate::geometry::{clip_polyline, rotate_points, Line};
+use crate::sink::PathSink;
 
 #[derive(Clone)]
 struct EdgeEntry<F: Float + FromPrimitive + Trig> {
@@ -35,11 +37,25 @@ struct ActiveEdgeEntry<F: Float + FromPrimitive + Trig> {
     pub(crate) edge: EdgeEntry<F>,
 }
 
-pub fn polygon_hachure_lines<F: Float + FromPrimitive + Trig>(
+pub fn polygon_hachure_lines<F: Float + FromPrimitive + Trig, R: Rng + ?Sized>(
     polygon_list: &mut Vec<Vec<Point2D<F>>>,
     options: &Options,
+    rng: &mut R,
 ) -> Vec<Line<F>> {
-    let angle = options.hachure_angle.unwrap_or(0.0) + 90.0;
+    indexed_hachure_lines(polygon_list, options, rng)
+        .into_iter()
+        .map(|(_, line)| line)
+        .collect()
+}
+
+/// Hachure lines of the polygons, each with the index of the scan line it lies on, counted
+/// from the first one.
+pub fn indexed_hachure_lines<F: Float + FromPrimitive + Trig, R: Rng + ?Sized>(
+    polygon_list: &mut Vec<Vec<Point2D<F>>>,
+    options: &Options,
+    rng: &mut R,
+) -> Vec<(usize, Line<F>)> {
+    let mut angle = options.hachure_angle.unwrap_or(0.0) + 90.0;
     let mut gap = options.hachure_gap.unwrap_or(0.0);
     if gap < 0.0 {
         gap = options.stroke_width.unwrap_or(0.0) * 4.0;
@@ -47,6 +63,11 @@ pub fn polygon_hachure_lines<F: Float + FromPrimitive + Trig>(
 
     gap = f32::max(gap, 0.1);
 
+    let angle_jitter = options.hachure_angle_jitter.unwrap_or(0.0);
+    if angle_jitter > 0.0 {
+        angle += (rng.gen::<f64>() as f32 * 2.0 - 1.0) * angle_jitter;
+    }
+
     let center = Point2D::new(_c(0.0), _c(0.0));
     if angle != 0.0 {
         polygon_list
@@ -54,19 +75,70 @@ pub fn polygon_hachure_lines<F: Float + FromPrimitive + Trig>(
             .for_each(|polygon| *polygon = rotate_points(polygon, &center, _c(angle)))
     }
 
-    let mut lines = straight_hachure_lines(polygon_list, _c(gap));
+    let gap_jitter = options.hachure_gap_jitter.unwrap_or(0.0);
+    let mut lines = if gap_jitter > 0.0 {
+        scan_hachure_lines(polygon_list, || {
+            let jitter = (rng.gen::<f64>() as f32 * 2.0 - 1.0) * gap_jitter.min(1.0);
+            _c(f32::max(gap * (1.0 + jitter), 0.1))
+        })
+    } else {
+        straight_hachure_lines(polygon_list, _c(gap))
+    };
 
     if angle != 0.0 {
         polygon_list
             .iter_mut()
             .for_each(|polygon| *polygon = rotate_points(polygon, &center, _c(-angle)));
-        lines = rotate_lines(&lines, &center, _c(-angle));
+        lines
+            .iter_mut()
+            .for_each(|(_, line)| line.rotate(&center, _c(-angle)));
+    }
+
+    let wobble = options.hachure_line_wobble.unwrap_or(0.0);
+    if wobble > 0.0 {
+        let fill_rule = options.fill_rule.unwrap_or_default();
+        lines = lines
+            .into_iter()
+            .flat_map(|(index, mut line)| {
+                let middle = line.start_point.lerp(line.end_point, _c(0.5));
+                line.rotate(&middle, _c((rng.gen::<f64>() as f32 * 2.0 - 1.0) * wobble));
+                // tilted lines are cut back to the shape they poke out of
+                clip_polyline(&line.as_points(), polygon_list, fill_rule)
+                    .into_iter()
+                    .map(move |run| (index, Line::from(&[run[0], run[run.len() - 1]])))
+            })
+            .collect();
+    }
+
+    let trim = options.hachure_line_trim.unwrap_or(0.0);
+    if trim > 0.0 {
+        let trim = trim.min(0.5);
+        lines.iter_mut().for_each(|(_, line)| {
+            let (start, end) = (line.start_point, line.end_point);
+            let start_trim = _c::<F>(rng.gen::<f64>() as f32 * trim);
+            let end_trim = _c::<F>(rng.gen::<f64>() as f32 * trim);
+            line.start_point = start.lerp(end, start_trim);
+            line.end_point = end.lerp(start, end_trim);
+        });
     }
 
     return lines;
 }
 
-fn straight_hachure_lines<F>(polygon_list: &mut [Vec<Point2D<F>>], gap: F) -> Vec<Line<F>>
+fn straight_hachure_lines<F>(polygon_list: &mut [Vec<Point2D<F>>], gap: F) -> Vec<(usize, Line<F>)>
+where
+    F: Float + FromPrimitive + Trig,
+{
+    let gap = F::max(gap, _c(0.1));
+    scan_hachure_lines(polygon_list, || gap)
+}
+
+/// Scans the polygons with horizontal lines, taking the distance to each next line from
+/// `next_gap`. Lines are returned with the index of the scan they come from.
+fn scan_hachure_lines<F>(
+    polygon_list: &mut [Vec<Point2D<F>>],
+    mut next_gap: impl FnMut() -> F,
+) -> Vec<(usize, Line<F>)>
 where
     F: Float + FromPrimitive + Trig,
 {
@@ -84,8 +156,7 @@ where
         }
     }
 
-    let mut lines: Vec<Line<F>> = vec![];
-    let gap = F::max(gap, _c(0.1));
+    let mut lines: Vec<(usize, Line<F>)> = vec![];
 
     // create sorted edges table
     let mut edges: Vec<EdgeEntry<F>> = vec![];
@@ -142,6 +213,7 @@ where
 
     let mut active_edges: Vec<ActiveEdgeEntry<F>> = Vec::new();
     let mut y = edges.first().unwrap().ymin;
+    let mut scan = 0;
 
     loop {
         if !edges.is_empty() {
@@ -184,14 +256,19 @@ where
             active_edges[..].chunks(2).for_each(|ae| {
                 let ce = &ae[0];
                 let ne = &ae[1];
-                lines.push(Line::from(&[
-                    euclid::Point2D::new(ce.edge.x, y),
-                    euclid::Point2D::new(ne.edge.x, y),
-                ]));
+                lines.push((
+                    scan,
+                    Line::from(&[
+                        euclid::Point2D::new(ce.edge.x, y),
+                        euclid::Point2D::new(ne.edge.x, y),
+                    ]),
+                ));
             });
         }
 
+        let gap = next_gap();
         y = y + gap;
+        scan += 1;
         active_edges.iter_mut().for_each(|ae| {
             ae.edge.x = ae.edge.x + (gap * ae.edge.islope);
         });
@@ -207,20 +284,21 @@ pub struct ScanlineHachureFiller<F> {
     _phantom: PhantomData<F>,
 }
 
-impl<F, P> PatternFiller<F, P> for ScanlineHachureFiller<F>
+impl<F, P, R> PatternFiller<F, P, R> for ScanlineHachureFiller<F>
 where
     F: Float + Trig + FromPrimitive,
     P: BorrowMut<Vec<Vec<Point2D<F>>>>,
+    R: Rng + Clone,
 {
-    fn fill_polygons(&self, mut polygon_list: P, o: &mut Options) -> crate::core::OpSet<F> {
-        let lines = polygon_hachure_lines(polygon_list.borrow_mut(), o);
-        let ops = ScanlineHachureFiller::render_lines(lines, o);
-        OpSet {
-            op_set_type: crate::core::OpSetType::FillSketch,
-            ops: ops,
-            size: None,
-            path: None,
-        }
+    fn fill_polygons_into(
+        &self,
+        mut polygon_list: P,
+        o: &Options,
+        rng: &mut R,
+        sink: &mut dyn PathSink<F>,
+    ) {
+        let lines = polygon_hachure_lines(polygon_list.borrow_mut(), o, rng);
+        ScanlineHachureFiller::render_lines(lines, o, rng, sink);
     }
 }
 
@@ -229,20 +307,15 @@ impl<F: Float + Trig + FromPrimitive> ScanlineHachureFiller<F> {
         ScanlineHachureFiller { _phantom: PhantomData }
     }
 
-    fn render_lines(lines: Vec<Line<F>>, o: &mut Options) -> Vec<crate::core::Op<F>> {
-        let mut ops: Vec<crate::core::Op<F>> = vec![];
+    fn render_lines<R: Rng + ?Sized>(
+        lines: Vec<Line<F>>,
+        o: &Options,
+        rng: &mut R,
+        sink: &mut dyn PathSink<F>,
+    ) {
         lines.iter().for_each(|l| {
-            ops.extend(crate::renderer::_double_line(
-                l.start_point.x,
-                l.start_point.y,
-                l.end_point.x,
-                l.end_point.y,
-                o,
-                true,
-            ))
+            crate::renderer::_double_line(l.start_point, l.end_point, o, rng, true, sink)
         });
-
-        ops
     }
 }
 
@@ -250,8 +323,41 @@ impl<F: Float + Trig + FromPrimitive> ScanlineHachureFiller<F> {
 mod test {
     use euclid::point2;
 
+    use crate::core::OptionsBuilder;
     use crate::geometry::Line;
 
+    #[test]
+    fn jittered_hachure_lines() {
+        let square = vec![vec![
+            point2(0.0, 0.0),
+            point2(40.0, 0.0),
+            point2(40.0, 40.0),
+            point2(0.0, 40.0),
+        ]];
+        let o = OptionsBuilder::default()
+            .seed(7_u64)
+            .hachure_angle(-90.0)
+            .hachure_gap(4.0)
+            .hachure_gap_jitter(0.5)
+            .hachure_line_trim(0.2)
+            .build()
+            .unwrap();
+        let lines = super::polygon_hachure_lines(&mut square.clone(), &o, &mut o.rng());
+        let again = super::polygon_hachure_lines(&mut square.clone(), &o, &mut o.rng());
+        assert_eq!(lines, again);
+
+        let gaps: Vec<f64> = lines
+            .windows(2)
+            .map(|w| w[1].start_point.y - w[0].start_point.y)
+            .collect();
+        assert!(gaps
+            .iter()
+            .all(|gap| *gap >= 2.0 - 1e-6 && *gap <= 6.0 + 1e-6));
+        assert!(gaps.iter().any(|gap| (gap - gaps[0]).abs() > 1e-3));
+        assert!(lines.iter().all(|line| line.length() <= 40.0 + 1e-6));
+        assert!(lines.iter().any(|line| line.length() < 38.0));
+    }
+
     #[test]
     fn straight_hachure_lines() {
         let mut input = vec![vec![
@@ -300,6 +406,11 @@ mod test {
             ]),
         ];
         let result = super::straight_hachure_lines(&mut input, 0.1);
+        assert_eq!(
+            result.iter().map(|(scan, _)| *scan).collect::<Vec<_>>(),
+            (0..10).collect::<Vec<_>>()
+        );
+        let result: Vec<Line<f64>> = result.into_iter().map(|(_, line)| line).collect();
         assert_eq!(expected, result);
     }
 }
@@ -0,0 +1,198 @@
+use std::borrow::BorrowMut;
+use std::marker::PhantomData;
+
+use euclid::default::Point2D;
+use euclid::{vec2, Trig};
+use num_traits::{Float, FromPrimitive};
+use rand::Rng;
+
+use super::scan_line_hachure::indexed_hachure_lines;
+use super::traits::PatternFiller;
+use crate::core::{_c, _cc, Options};
+use crate::geometry::Line;
+use crate::renderer::_double_curve;
+use crate::sink::PathSink;
+
+pub struct ScribbleFiller<F> {
+    _phantom: PhantomData<F>,
+}
+
+impl<F, P, R> PatternFiller<F, P, R> for ScribbleFiller<F>
+where
+    F: Float + Trig + FromPrimitive,
+    P: BorrowMut<Vec<Vec<Point2D<F>>>>,
+    R: Rng + Clone,
+{
+    fn fill_polygons_into(
+        &self,
+        mut polygon_list: P,
+        o: &Options,
+        rng: &mut R,
+        sink: &mut dyn PathSink<F>,
+    ) {
+        let mut gap = o.hachure_gap.map(_c::<F>).unwrap_or_else(|| _c::<F>(-1.0));
+        if gap < F::zero() {
+            gap = o.stroke_width.map(_c::<F>).unwrap_or_else(|| _c::<F>(1.0)) * _c::<F>(4.0);
+        }
+        gap = gap.max(_c::<F>(0.1));
+
+        let polygon_list = polygon_list.borrow_mut();
+        let lines = indexed_hachure_lines(polygon_list, o, rng);
+        for stroke in ScribbleFiller::strokes(&lines) {
+            let pen_path = ScribbleFiller::pen_path(&stroke, gap, rng);
+            _double_curve(&pen_path, o, rng, true, sink);
+        }
+    }
+}
+
+impl<F: Float + Trig + FromPrimitive> ScribbleFiller<F> {
+    pub fn new() -> Self {
+        ScribbleFiller { _phantom: PhantomData }
+    }
+
+    /// Links the hachure lines into the strokes of the pen. A line continues the stroke whose
+    /// last line lies on the previous scan line and overlaps it along the hachure direction,
+    /// so a convex shape is covered by a single stroke and every arm of a concave one gets a
+    /// stroke of its own that never leaves it.
+    fn strokes(lines: &[(usize, Line<F>)]) -> Vec<Vec<Line<F>>> {
+        let lines = lines
+            .iter()
+            .filter(|(_, line)| line.length() > F::zero())
+            .collect::<Vec<_>>();
+        let direction = match lines.first() {
+            Some((_, first)) => (first.end_point - first.start_point) / first.length(),
+            None => return vec![],
+        };
+        let span = |line: &Line<F>| {
+            let start = line.start_point.to_vector().dot(direction);
+            let end = line.end_point.to_vector().dot(direction);
+            (start.min(end), start.max(end))
+        };
+
+        let mut strokes: Vec<(usize, Vec<Line<F>>)> = vec![];
+        for (scan, line) in lines.into_iter() {
+            let (start, end) = span(line);
+            let previous = strokes.iter_mut().find(|(last_scan, stroke)| {
+                let (last_start, last_end) = span(stroke.last().unwrap());
+                *last_scan + 1 == *scan && start <= last_end && last_start <= end
+            });
+            match previous {
+                Some((last_scan, stroke)) => {
+                    *last_scan = *scan;
+                    stroke.push(line.clone());
+                }
+                None => strokes.push((*scan, vec![line.clone()])),
+            }
+        }
+        strokes.into_iter().map(|(_, stroke)| stroke).collect()
+    }
+
+    /// Walks the lines of a stroke back and forth as a single path. Every turning point is
+    /// pulled inside by a random amount and pushed sideways, so that the strokes do not
+    /// line up the way straight hachure does. Passes are sampled every couple of gaps to
+    /// keep the rounded turns of the curve close to the turning points.
+    fn pen_path<R: Rng + ?Sized>(lines: &[Line<F>], gap: F, rng: &mut R) -> Vec<Point2D<F>> {
+        let mut points = vec![];
+        let half = _c::<F>(0.5);
+        for (i, line) in lines.iter().enumerate() {
+            let length = line.length();
+            let (start, end) = if i % 2 == 0 {
+                (line.start_point, line.end_point)
+            } else {
+                (line.end_point, line.start_point)
+            };
+            let direction = (end - start) / length;
+            let normal = vec2(-direction.y, direction.x);
+            let max_inset = gap.min(length / _c(4.0));
+
+            let start_inset = (_c::<F>(0.25) + _c::<F>(0.75) * _cc::<F>(rng.gen())) * max_inset;
+            let start_shift = (_cc::<F>(rng.gen()) - half) * gap * half;
+            let end_inset = (_c::<F>(0.25) + _c::<F>(0.75) * _cc::<F>(rng.gen())) * max_inset;
+            let end_shift = (_cc::<F>(rng.gen()) - half) * gap * half;
+            let pass_start = start + direction * start_inset + normal * start_shift;
+            let pass_end = end - direction * end_inset + normal * end_shift;
+
+            let steps = ((pass_end - pass_start).length() / (gap * _c(2.0)))
+                .ceil()
+                .to_usize()
+                .unwrap_or(1)
+                .max(1);
+            for step in 0..=steps {
+                points.push(
+                    pass_start.lerp(pass_end, F::from(step).unwrap() / F::from(steps).unwrap()),
+                );
+            }
+        }
+        points
+    }
+}
+
+impl<F: Float + Trig + FromPrimitive> Default for ScribbleFiller<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use euclid::point2;
+
+    use super::ScribbleFiller;
+    use crate::core::{OpType, OptionsBuilder};
+    use crate::filler::scan_line_hachure::indexed_hachure_lines;
+    use crate::filler::traits::PatternFiller;
+
+    #[test]
+    fn one_pen_path_per_pass() {
+        let square = vec![vec![
+            point2(0.0, 0.0),
+            point2(60.0, 0.0),
+            point2(60.0, 60.0),
+            point2(0.0, 60.0),
+        ]];
+        let o = OptionsBuilder::default()
+            .hachure_gap(4.0)
+            .fill_passes(3)
+            .build()
+            .unwrap();
+        let set = ScribbleFiller::<f64>::new().fill_polygons(square, &o, &mut o.rng());
+        let moves = set
+            .ops
+            .iter()
+            .filter(|op| op.op_type() == OpType::Move)
+            .count();
+        assert_eq!(moves, 3);
+    }
+
+    #[test]
+    fn one_stroke_per_arm() {
+        // A U standing upside down: two arms hanging from a bar, scanned from the bar down.
+        let mut u = vec![vec![
+            point2(0.0, 0.0),
+            point2(50.0, 0.0),
+            point2(50.0, 40.0),
+            point2(35.0, 40.0),
+            point2(35.0, 10.0),
+            point2(15.0, 10.0),
+            point2(15.0, 40.0),
+            point2(0.0, 40.0),
+        ]];
+        let o = OptionsBuilder::default()
+            .hachure_angle(-90.0)
+            .hachure_gap(2.0)
+            .build()
+            .unwrap();
+        let lines = indexed_hachure_lines(&mut u, &o, &mut o.rng());
+        let strokes = ScribbleFiller::<f64>::strokes(&lines);
+        assert_eq!(strokes.len(), 2);
+        assert_eq!(strokes.iter().map(Vec::len).sum::<usize>(), lines.len());
+        // The right arm has a stroke of its own, which stays clear of the left one.
+        let arm = strokes
+            .iter()
+            .find(|stroke| stroke[0].start_point.x > 25.0)
+            .unwrap();
+        assert!(arm
+            .iter()
+            .all(|line| line.start_point.x.min(line.end_point.x) > 34.0));
+    }
+}
@@ -0,0 +1,291 @@
+use std::borrow::BorrowMut;
+use std::marker::PhantomData;
+
+use euclid::default::Point2D;
+use euclid::{point2, Trig};
+use num_traits::{Float, FromPrimitive};
+use rand::Rng;
+
+use super::grid_scale;
+use super::traits::PatternFiller;
+use crate::core::{_c, FillRule, Options};
+use crate::geometry::{clip_polyline, rotate_points};
+use crate::renderer::_double_curve;
+use crate::sink::PathSink;
+
+/// Highest order of the Hilbert curve, which bounds the number of points to `4^order`.
+const MAX_HILBERT_ORDER: u32 = 8;
+
+/// Most points of a serpentine, as many as the finest Hilbert curve has. Rows of larger
+/// shapes are drawn further apart than the gap.
+const MAX_SERPENTINE_POINTS: usize = 1 << (2 * MAX_HILBERT_ORDER);
+
+/// Shape of the single line a [`SpaceFillingFiller`] covers the polygons with.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SpaceFillingCurve {
+    Hilbert,
+    /// Back and forth rows at the hachure angle, joined at their ends.
+    Serpentine,
+}
+
+pub struct SpaceFillingFiller<F> {
+    _phantom: PhantomData<F>,
+    curve: SpaceFillingCurve,
+}
+
+impl<F, P, R> PatternFiller<F, P, R> for SpaceFillingFiller<F>
+where
+    F: Float + Trig + FromPrimitive,
+    P: BorrowMut<Vec<Vec<Point2D<F>>>>,
+    R: Rng + Clone,
+{
+    fn fill_polygons_into(
+        &self,
+        mut polygon_list: P,
+        o: &Options,
+        rng: &mut R,
+        sink: &mut dyn PathSink<F>,
+    ) {
+        let mut gap = o.hachure_gap.map(_c::<F>).unwrap_or_else(|| _c::<F>(-1.0));
+        if gap < F::zero() {
+            gap = o.stroke_width.map(_c::<F>).unwrap_or_else(|| _c::<F>(1.0)) * _c::<F>(4.0);
+        }
+        gap = gap.max(_c::<F>(0.1));
+
+        let polygon_list = polygon_list.borrow_mut();
+        let path = match self.curve {
+            SpaceFillingCurve::Hilbert => SpaceFillingFiller::hilbert_path(polygon_list, gap),
+            SpaceFillingCurve::Serpentine => {
+                let angle = _c::<F>(o.hachure_angle.unwrap_or(0.0) + 90.0);
+                let center = point2(F::zero(), F::zero());
+                let rotated: Vec<Vec<Point2D<F>>> = polygon_list
+                    .iter()
+                    .map(|polygon| rotate_points(polygon, &center, angle))
+                    .collect();
+                let path = SpaceFillingFiller::serpentine_path(&rotated, gap);
+                rotate_points(&path, &center, -angle)
+            }
+        };
+
+        let fill_rule = o.fill_rule.unwrap_or_default();
+        for run in SpaceFillingFiller::joined_runs(&path, polygon_list, fill_rule, gap) {
+            let points = SpaceFillingFiller::curve_points(&run, gap);
+            _double_curve(&points, o, rng, true, sink);
+        }
+    }
+}
+
+impl<F: Float + Trig + FromPrimitive> SpaceFillingFiller<F> {
+    pub fn new(curve: SpaceFillingCurve) -> Self {
+        SpaceFillingFiller { _phantom: PhantomData, curve }
+    }
+
+    fn bounds(polygon_list: &[Vec<Point2D<F>>]) -> Option<(Point2D<F>, Point2D<F>)> {
+        let (mut min_x, mut min_y) = (F::infinity(), F::infinity());
+        let (mut max_x, mut max_y) = (F::neg_infinity(), F::neg_infinity());
+        for p in polygon_list.iter().flatten() {
+            min_x = min_x.min(p.x);
+            min_y = min_y.min(p.y);
+            max_x = max_x.max(p.x);
+            max_y = max_y.max(p.y);
+        }
+        if max_x > min_x && max_y > min_y {
+            Some((point2(min_x, min_y), point2(max_x, max_y)))
+        } else {
+            None
+        }
+    }
+
+    /// Hilbert curve over the square around the bounding box, of the order whose cells come
+    /// closest to the gap.
+    fn hilbert_path(polygon_list: &[Vec<Point2D<F>>], gap: F) -> Vec<Point2D<F>> {
+        let (min, max) = match SpaceFillingFiller::bounds(polygon_list) {
+            Some(bounds) => bounds,
+            None => return vec![],
+        };
+        let size = (max.x - min.x).max(max.y - min.y);
+        let order = (size / gap)
+            .log2()
+            .round()
+            .to_u32()
+            .unwrap_or(0)
+            .min(MAX_HILBERT_ORDER);
+        let side = 1_u32 << order;
+        let cell = size / F::from(side).unwrap();
+
+        (0..side * side)
+            .map(|d| {
+                let (x, y) = hilbert_point(side, d);
+                point2(
+                    min.x + (F::from(x).unwrap() + _c(0.5)) * cell,
+                    min.y + (F::from(y).unwrap() + _c(0.5)) * cell,
+                )
+            })
+            .collect()
+    }
+
+    /// Horizontal rows one gap apart, walked in alternating directions and sampled every
+    /// gap so that clipping follows the outline closely.
+    fn serpentine_path(polygon_list: &[Vec<Point2D<F>>], gap: F) -> Vec<Point2D<F>> {
+        let (min, max) = match SpaceFillingFiller::bounds(polygon_list) {
+            Some(bounds) => bounds,
+            None => return vec![],
+        };
+        let size = max - min;
+        let gap = match grid_scale(size.x, size.y, gap, gap, MAX_SERPENTINE_POINTS) {
+            Some(scale) => gap * scale,
+            None => return vec![],
+        };
+        // Rows and turns keep half a gap from the bounding box, so that the turns of convex
+        // shapes stay inside and need no pen lift.
+        let rows = ((max.y - min.y) / gap).ceil().to_usize().unwrap_or(0);
+        let width = (max.x - min.x - gap).max(F::zero());
+        let columns = (width / gap).ceil().to_usize().unwrap_or(0).max(1);
+        let mut path = vec![];
+        for row in 0..rows {
+            let y = min.y + (F::from(row).unwrap() + _c(0.5)) * gap;
+            for column in 0..=columns {
+                let column = if row % 2 == 0 {
+                    column
+                } else {
+                    columns - column
+                };
+                let x = min.x
+                    + (max.x - min.x - width) / _c(2.0)
+                    + F::from(column).unwrap() * width / F::from(columns).unwrap();
+                path.push(point2(x, y));
+            }
+        }
+        path
+    }
+
+    /// Reduces a run to its corners, since every point on the straight parts adds its own
+    /// wobble. Long parts keep a point one gap away from each end, so that the curve turns
+    /// tightly instead of swinging out at the corners.
+    fn curve_points(run: &[Point2D<F>], gap: F) -> Vec<Point2D<F>> {
+        let mut corners: Vec<Point2D<F>> = vec![run[0]];
+        for w in run.windows(3) {
+            let (a, b) = (w[1] - w[0], w[2] - w[1]);
+            if a.cross(b).abs() > a.length() * b.length() * _c(1e-3) || a.dot(b) < F::zero() {
+                corners.push(w[1]);
+            }
+        }
+        corners.push(run[run.len() - 1]);
+
+        let mut points = vec![corners[0]];
+        for w in corners.windows(2) {
+            let length = (w[1] - w[0]).length();
+            if length > gap * _c(3.0) {
+                points.push(w[0].lerp(w[1], gap / length));
+                points.push(w[1].lerp(w[0], gap / length));
+            }
+            points.push(w[1]);
+        }
+        points
+    }
+
+    /// Clips the path to the polygons and lifts the pen only where it has to: consecutive
+    /// pieces are joined again when the straight connection between them stays inside and
+    /// is no longer than a few gaps.
+    fn joined_runs(
+        path: &[Point2D<F>],
+        polygon_list: &[Vec<Point2D<F>>],
+        fill_rule: FillRule,
+        gap: F,
+    ) -> Vec<Vec<Point2D<F>>> {
+        let mut runs: Vec<Vec<Point2D<F>>> = vec![];
+        for run in clip_polyline(path, polygon_list, fill_rule) {
+            if let Some(last) = runs.last_mut() {
+                let (from, to) = (last[last.len() - 1], run[0]);
+                let connection = clip_polyline(&[from, to], polygon_list, fill_rule);
+                let tolerance = gap * _c(0.01);
+                let inside = connection.len() == 1
+                    && (connection[0][0] - from).length() < tolerance
+                    && (connection[0][connection[0].len() - 1] - to).length() < tolerance;
+                if inside && (to - from).length() <= gap * _c(4.0) {
+                    last.extend(run);
+                    continue;
+                }
+            }
+            runs.push(run);
+        }
+        runs
+    }
+}
+
+/// Position of the `d`-th cell along a Hilbert curve through a square of `side` cells.
+fn hilbert_point(side: u32, d: u32) -> (u32, u32) {
+    let (mut x, mut y) = (0, 0);
+    let mut t = d;
+    let mut s = 1;
+    while s < side {
+        let rx = 1 & (t / 2);
+        let ry = 1 & (t ^ rx);
+        if ry == 0 {
+            if rx == 1 {
+                x = s - 1 - x;
+                y = s - 1 - y;
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+        x += s * rx;
+        y += s * ry;
+        t /= 4;
+        s *= 2;
+    }
+    (x, y)
+}
+
+#[cfg(test)]
+mod test {
+    use euclid::point2;
+
+    use super::{hilbert_point, SpaceFillingFiller, MAX_SERPENTINE_POINTS};
+    use crate::core::FillRule;
+
+    #[test]
+    fn hilbert_points() {
+        let side = 8;
+        let points: Vec<(u32, u32)> = (0..side * side).map(|d| hilbert_point(side, d)).collect();
+        for w in points.windows(2) {
+            let step = w[0].0.abs_diff(w[1].0) + w[0].1.abs_diff(w[1].1);
+            assert_eq!(step, 1);
+        }
+        let mut unique = points.clone();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(unique.len(), points.len());
+    }
+
+    #[test]
+    fn convex_shapes_are_filled_in_one_stroke() {
+        let square = vec![vec![
+            point2(0.0, 0.0),
+            point2(30.0, 0.0),
+            point2(30.0, 30.0),
+            point2(0.0, 30.0),
+        ]];
+        let path = SpaceFillingFiller::<f64>::serpentine_path(&square, 3.0);
+        let runs = SpaceFillingFiller::joined_runs(&path, &square, FillRule::EvenOdd, 3.0);
+        assert_eq!(runs.len(), 1);
+
+        let path = SpaceFillingFiller::<f64>::hilbert_path(&square, 3.0);
+        assert_eq!(path.len(), 64);
+        let runs = SpaceFillingFiller::joined_runs(&path, &square, FillRule::EvenOdd, 3.0);
+        assert_eq!(runs.len(), 1);
+    }
+    #[test]
+    fn huge_shapes() {
+        for (width, height) in [(1e9, 1e9), (1e9, 10.0)] {
+            let rectangle = vec![vec![
+                point2(0.0, 0.0),
+                point2(width, 0.0),
+                point2(width, height),
+                point2(0.0, height),
+            ]];
+            let path = SpaceFillingFiller::<f64>::serpentine_path(&rectangle, 3.0);
+            assert!(!path.is_empty());
+            assert!(path.len() <= MAX_SERPENTINE_POINTS);
+        }
+    }
+}
@@ -0,0 +1,252 @@
+use std::borrow::BorrowMut;
+use std::marker::PhantomData;
+
+use euclid::default::Point2D;
+use euclid::{point2, Trig};
+use num_traits::{Float, FloatConst, FromPrimitive};
+use rand::Rng;
+
+use super::grid_scale;
+use super::traits::PatternFiller;
+use crate::core::{_c, _cc, Options};
+use crate::geometry::point_in_polygons;
+use crate::renderer::_ellipse;
+use crate::sink::PathSink;
+
+/// Number of candidates tried around an active sample before it is retired.
+const CANDIDATE_ATTEMPTS: usize = 30;
+
+/// Most cells of the sampling grid. Shapes too large to stipple at the set spacing with so
+/// many cells are stippled more sparsely.
+const MAX_GRID_CELLS: usize = 1 << 18;
+
+pub struct StippleFiller<F> {
+    _phantom: PhantomData<F>,
+}
+
+impl<F, P, R> PatternFiller<F, P, R> for StippleFiller<F>
+where
+    F: Float + Trig + FromPrimitive,
+    P: BorrowMut<Vec<Vec<Point2D<F>>>>,
+    R: Rng + Clone,
+{
+    fn fill_polygons_into(
+        &self,
+        mut polygon_list: P,
+        o: &Options,
+        rng: &mut R,
+        sink: &mut dyn PathSink<F>,
+    ) {
+        let mut gap = o.hachure_gap.map(_c::<F>).unwrap_or_else(|| _c::<F>(-1.0));
+        if gap < F::zero() {
+            gap = o.stroke_width.map(_c::<F>).unwrap_or_else(|| _c::<F>(1.0)) * _c::<F>(4.0);
+        }
+        let mut density = o.stipple_density.map(_c::<F>).unwrap_or_else(|| F::one());
+        if density <= F::zero() {
+            density = F::one();
+        }
+        let spacing = (gap / density.sqrt()).max(_c::<F>(0.1));
+
+        let points = StippleFiller::poisson_disk_points(polygon_list.borrow_mut(), spacing, o, rng);
+        StippleFiller::dots(&points, o, rng, sink);
+    }
+}
+
+impl<F: Float + Trig + FromPrimitive> StippleFiller<F> {
+    pub fn new() -> Self {
+        StippleFiller { _phantom: PhantomData }
+    }
+
+    /// Samples points with Bridson's algorithm over the bounding box of the polygons, so that
+    /// disjoint parts and holes are covered evenly, and keeps the ones inside the fill area.
+    fn poisson_disk_points<R: Rng + ?Sized>(
+        polygon_list: &[Vec<Point2D<F>>],
+        spacing: F,
+        o: &Options,
+        rng: &mut R,
+    ) -> Vec<Point2D<F>> {
+        let all_points = polygon_list.iter().flatten();
+        let (mut min_x, mut min_y) = (F::infinity(), F::infinity());
+        let (mut max_x, mut max_y) = (F::neg_infinity(), F::neg_infinity());
+        for p in all_points {
+            min_x = min_x.min(p.x);
+            min_y = min_y.min(p.y);
+            max_x = max_x.max(p.x);
+            max_y = max_y.max(p.y);
+        }
+        if !(max_x > min_x && max_y > min_y) {
+            return vec![];
+        }
+
+        let mut cell = spacing / _c::<F>(f32::SQRT_2());
+        let scale = match grid_scale(max_x - min_x, max_y - min_y, cell, cell, MAX_GRID_CELLS) {
+            Some(scale) => scale,
+            None => return vec![],
+        };
+        cell = cell * scale;
+        let spacing = spacing * scale;
+        let columns = ((max_x - min_x) / cell).ceil().to_usize().unwrap_or(0) + 1;
+        let rows = ((max_y - min_y) / cell).ceil().to_usize().unwrap_or(0) + 1;
+        let mut grid: Vec<Option<usize>> = vec![None; columns * rows];
+        let cell_of = |p: &Point2D<F>| {
+            (
+                ((p.x - min_x) / cell)
+                    .to_usize()
+                    .unwrap_or(0)
+                    .min(columns - 1),
+                ((p.y - min_y) / cell).to_usize().unwrap_or(0).min(rows - 1),
+            )
+        };
+
+        let mut samples: Vec<Point2D<F>> = vec![];
+        let mut active: Vec<usize> = vec![];
+
+        let first = point2(
+            min_x + _cc::<F>(rng.gen()) * (max_x - min_x),
+            min_y + _cc::<F>(rng.gen()) * (max_y - min_y),
+        );
+        let (cx, cy) = cell_of(&first);
+        grid[cy * columns + cx] = Some(0);
+        samples.push(first);
+        active.push(0);
+
+        while !active.is_empty() {
+            let active_index = (_cc::<F>(rng.gen()) * F::from(active.len()).unwrap())
+                .to_usize()
+                .unwrap_or(0)
+                .min(active.len() - 1);
+            let center = samples[active[active_index]];
+            let mut found = false;
+            for _ in 0..CANDIDATE_ATTEMPTS {
+                let angle = _cc::<F>(rng.gen()) * _c::<F>(f32::PI() * 2.0);
+                let radius = spacing * (F::one() + _cc::<F>(rng.gen()));
+                let candidate = point2(
+                    center.x + radius * Float::cos(angle),
+                    center.y + radius * Float::sin(angle),
+                );
+                if candidate.x < min_x
+                    || candidate.x > max_x
+                    || candidate.y < min_y
+                    || candidate.y > max_y
+                {
+                    continue;
+                }
+                let (gx, gy) = cell_of(&candidate);
+                let mut too_close = false;
+                'neighbours: for ny in gy.saturating_sub(2)..(gy + 3).min(rows) {
+                    for nx in gx.saturating_sub(2)..(gx + 3).min(columns) {
+                        if let Some(s) = grid[ny * columns + nx] {
+                            if (samples[s] - candidate).length() < spacing {
+                                too_close = true;
+                                break 'neighbours;
+                            }
+                        }
+                    }
+                }
+                if !too_close {
+                    grid[gy * columns + gx] = Some(samples.len());
+                    active.push(samples.len());
+                    samples.push(candidate);
+                    found = true;
+                    break;
+                }
+            }
+            if !found {
+                active.swap_remove(active_index);
+            }
+        }
+
+        let fill_rule = o.fill_rule.unwrap_or_default();
+        let mut points: Vec<Point2D<F>> = samples
+            .into_iter()
+            .filter(|p| point_in_polygons(*p, polygon_list, fill_rule))
+            .collect();
+
+        // With a tone map the samples are thinned out, so that darker areas keep more dots.
+        if let Some(tone) = o.tone.clone() {
+            points.retain(|p| {
+                let t = tone.tone_at(p.x.to_f32().unwrap_or(0.0), p.y.to_f32().unwrap_or(0.0));
+                rng.gen::<f64>() < t as f64
+            });
+        }
+        points
+    }
+
+    fn dots<R: Rng + ?Sized>(
+        points: &[Point2D<F>],
+        o: &Options,
+        rng: &mut R,
+        sink: &mut dyn PathSink<F>,
+    ) {
+        let mut fweight = o.fill_weight.map(_c::<F>).unwrap_or_else(|| _c::<F>(-1.0));
+        if fweight < F::zero() {
+            fweight = o.stroke_width.map(_c::<F>).unwrap_or_else(|| _c::<F>(1.0)) / _c::<F>(2.0);
+        }
+        let mut min_size = o
+            .stipple_min_size
+            .map(_c::<F>)
+            .unwrap_or_else(|| _c::<F>(-1.0));
+        if min_size < F::zero() {
+            min_size = fweight;
+        }
+        let mut max_size = o
+            .stipple_max_size
+            .map(_c::<F>)
+            .unwrap_or_else(|| _c::<F>(-1.0));
+        if max_size < min_size {
+            max_size = min_size;
+        }
+
+        for p in points {
+            let size = min_size + _cc::<F>(rng.gen()) * (max_size - min_size);
+            _ellipse(*p, size, size, o, rng, sink);
+        }
+    }
+}
+
+impl<F: Float + Trig + FromPrimitive> Default for StippleFiller<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use euclid::point2;
+
+    use super::StippleFiller;
+    use crate::core::{FillRule, OptionsBuilder};
+    use crate::geometry::point_in_polygons;
+
+    #[test]
+    fn poisson_disk_points() {
+        let polygons = vec![
+            vec![
+                point2(0.0, 0.0),
+                point2(20.0, 0.0),
+                point2(20.0, 20.0),
+                point2(0.0, 20.0),
+            ],
+            vec![
+                point2(5.0, 5.0),
+                point2(15.0, 5.0),
+                point2(15.0, 15.0),
+                point2(5.0, 15.0),
+            ],
+        ];
+        let o = OptionsBuilder::default().seed(42_u64).build().unwrap();
+        let points = StippleFiller::<f64>::poisson_disk_points(&polygons, 2.0, &o, &mut o.rng());
+
+        assert!(!points.is_empty());
+        for (i, p) in points.iter().enumerate() {
+            assert!(point_in_polygons(*p, &polygons, FillRule::EvenOdd));
+            for q in points.iter().skip(i + 1) {
+                assert!((*p - *q).length() >= 2.0);
+            }
+        }
+
+        let o = OptionsBuilder::default().seed(42_u64).build().unwrap();
+        let again = StippleFiller::<f64>::poisson_disk_points(&polygons, 2.0, &o, &mut o.rng());
+        assert_eq!(points, again);
+    }
+}
@@ -0,0 +1,193 @@
+use std::borrow::BorrowMut;
+use std::marker::PhantomData;
+
+use euclid::default::Point2D;
+use euclid::Trig;
+use num_traits::{Float, FromPrimitive};
+use rand::Rng;
+
+use super::scan_line_hachure::indexed_hachure_lines;
+use super::traits::PatternFiller;
+use crate::core::{_c, Options};
+use crate::geometry::Line;
+use crate::renderer::_double_line;
+use crate::sink::PathSink;
+
+/// Number of times the spacing of the lines doubles between the darkest and the lightest
+/// tone that still gets hatched.
+const TONE_LEVELS: u32 = 3;
+
+pub struct ToneHatchFiller<F> {
+    _phantom: PhantomData<F>,
+}
+
+impl<F, P, R> PatternFiller<F, P, R> for ToneHatchFiller<F>
+where
+    F: Float + Trig + FromPrimitive,
+    P: BorrowMut<Vec<Vec<Point2D<F>>>>,
+    R: Rng + Clone,
+{
+    fn fill_polygons_into(
+        &self,
+        mut polygon_list: P,
+        o: &Options,
+        rng: &mut R,
+        sink: &mut dyn PathSink<F>,
+    ) {
+        let tone = o.tone.clone();
+        let tone_at = |p: Point2D<F>| match &tone {
+            Some(tone) => {
+                _c::<F>(tone.tone_at(p.x.to_f32().unwrap_or(0.0), p.y.to_f32().unwrap_or(0.0)))
+            }
+            None => F::one(),
+        };
+        let polygon_list = polygon_list.borrow_mut();
+
+        let mut lines;
+        if o.tone_cross_hatch.unwrap_or(false) {
+            // The first layer reaches full density at half tone, the darker half adds the
+            // crossing layer on top of it.
+            lines = ToneHatchFiller::tone_lines(polygon_list, o, rng, |p| {
+                (tone_at(p) * _c(2.0)).min(F::one())
+            });
+            let crossed = Options {
+                hachure_angle: o.hachure_angle.map(|a| a + 90.0),
+                ..o.clone()
+            };
+            lines.extend(ToneHatchFiller::tone_lines(
+                polygon_list,
+                &crossed,
+                rng,
+                |p| (tone_at(p) * _c(2.0) - F::one()).max(F::zero()),
+            ));
+        } else {
+            lines = ToneHatchFiller::tone_lines(polygon_list, o, rng, tone_at);
+        }
+
+        for line in lines.iter() {
+            _double_line(line.start_point, line.end_point, o, rng, true, sink);
+        }
+    }
+}
+
+impl<F: Float + Trig + FromPrimitive> ToneHatchFiller<F> {
+    pub fn new() -> Self {
+        ToneHatchFiller { _phantom: PhantomData }
+    }
+
+    /// Hatches the polygons at the full density of the hachure gap and keeps, along each line,
+    /// only the parts where the tone asks for that line. Every level lighter drops every other
+    /// line of the level above, so the lines of a lighter area continue those of a darker one.
+    fn tone_lines<R: Rng + ?Sized>(
+        polygon_list: &mut Vec<Vec<Point2D<F>>>,
+        o: &Options,
+        rng: &mut R,
+        tone_at: impl Fn(Point2D<F>) -> F,
+    ) -> Vec<Line<F>> {
+        let mut gap = o.hachure_gap.map(_c::<F>).unwrap_or_else(|| _c::<F>(-1.0));
+        if gap < F::zero() {
+            gap = o.stroke_width.map(_c::<F>).unwrap_or_else(|| _c::<F>(1.0)) * _c::<F>(4.0);
+        }
+        gap = gap.max(_c::<F>(0.1));
+
+        let lines = indexed_hachure_lines(polygon_list, o, rng);
+        let step = gap / _c(2.0);
+        let levels = _c::<F>(TONE_LEVELS as f32 + 1.0);
+        let mut result = vec![];
+        for (scan, line) in lines.iter() {
+            let keep = |p: Point2D<F>| {
+                let level = (tone_at(p) * levels).ceil().to_u32().unwrap_or(0);
+                if level == 0 {
+                    return false;
+                }
+                let period = 1_usize << (TONE_LEVELS + 1).saturating_sub(level);
+                scan % period == 0
+            };
+
+            let length = line.length();
+            let count = (length / step).ceil().to_usize().unwrap_or(0).max(1);
+            let at = |i: usize| {
+                line.start_point.lerp(
+                    line.end_point,
+                    F::from(i).unwrap() / F::from(count).unwrap(),
+                )
+            };
+            let mut run_start = None;
+            for i in 0..count {
+                let inside = keep(at(i).lerp(at(i + 1), _c(0.5)));
+                match (inside, run_start) {
+                    (true, None) => run_start = Some(at(i)),
+                    (false, Some(start)) => {
+                        result.push(Line { start_point: start, end_point: at(i) });
+                        run_start = None;
+                    }
+                    _ => {}
+                }
+            }
+            if let Some(start) = run_start {
+                result.push(Line { start_point: start, end_point: line.end_point });
+            }
+        }
+        result
+    }
+}
+
+impl<F: Float + Trig + FromPrimitive> Default for ToneHatchFiller<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use euclid::point2;
+
+    use super::ToneHatchFiller;
+    use crate::core::OptionsBuilder;
+
+    #[test]
+    fn tone_lines() {
+        let square = vec![vec![
+            point2(0.0, 0.0),
+            point2(64.0, 0.0),
+            point2(64.0, 64.0),
+            point2(0.0, 64.0),
+        ]];
+        let o = OptionsBuilder::default()
+            .hachure_angle(-90.0)
+            .hachure_gap(2.0)
+            .build()
+            .unwrap();
+        let count = |tone: f32| {
+            ToneHatchFiller::<f64>::tone_lines(&mut square.clone(), &o, &mut o.rng(), |_| {
+                tone.into()
+            })
+            .len()
+        };
+
+        let full = count(1.0);
+        assert!(full > 0);
+        assert_eq!(count(0.0), 0);
+        assert!(count(0.5) < full);
+        assert!(count(0.2) < count(0.5));
+
+        // every level lighter keeps every other line, whichever way the lines run
+        for angle in [-90.0, 0.0, 30.0, -41.0] {
+            let o = OptionsBuilder::default()
+                .hachure_angle(angle)
+                .hachure_gap(2.0)
+                .build()
+                .unwrap();
+            let count = |tone: f32| {
+                ToneHatchFiller::<f64>::tone_lines(&mut square.clone(), &o, &mut o.rng(), |_| {
+                    tone.into()
+                })
+                .len()
+            };
+            let full = count(1.0);
+            assert_eq!(count(0.75), full.div_ceil(2), "{}", angle);
+            assert_eq!(count(0.5), full.div_ceil(4), "{}", angle);
+            assert_eq!(count(0.25), full.div_ceil(8), "{}", angle);
+        }
+    }
+}
@@ -3,9 +3,65 @@ use std::borrow::BorrowMut;
 use euclid::default::Point2D;
 use euclid::Trig;
 use num_traits::{Float, FromPrimitive};
+use rand::Rng;
 
-use crate::core::{OpSet, Options};
+use crate::core::{OpSet, OpSetType, Options};
+use crate::sink::PathSink;
 
-pub trait PatternFiller<F: Float + Trig + FromPrimitive, P: BorrowMut<Vec<Vec<Point2D<F>>>>> {
-    fn fill_polygons(&self, polygon_list: P, o: &mut Options) -> OpSet<F>;
+pub trait PatternFiller<F, P, R>
+where
+    F: Float + Trig + FromPrimitive,
+    P: BorrowMut<Vec<Vec<Point2D<F>>>>,
+    R: Rng + Clone,
+{
+    /// Draws the fill of the polygons into `sink`, without any set boundaries. Fillers
+    /// drawing several layers draw all of them, one after the other.
+    fn fill_polygons_into(
+        &self,
+        polygon_list: P,
+        o: &Options,
+        rng: &mut R,
+        sink: &mut dyn PathSink<F>,
+    );
+
+    /// How the ops of the fill are meant to be drawn.
+    fn op_set_type(&self) -> OpSetType {
+        OpSetType::FillSketch
+    }
+
+    /// Collects the fill of the polygons into a single op set.
+    fn fill_polygons(&self, polygon_list: P, o: &Options, rng: &mut R) -> OpSet<F> {
+        let mut ops = vec![];
+        self.fill_polygons_into(polygon_list, o, rng, &mut ops);
+        OpSet {
+            op_set_type: self.op_set_type(),
+            ops,
+            size: None,
+            path: None,
+            style: None,
+        }
+    }
+
+    /// Fills the polygons with one op set per differently styled layer, streaming each set
+    /// into `sink` between [`PathSink::begin_set`] and [`PathSink::end_set`]. Fillers
+    /// drawing a single layer keep the default, which draws one set of
+    /// [`PatternFiller::op_set_type`].
+    fn fill_polygon_sets_into(
+        &self,
+        polygon_list: P,
+        o: &Options,
+        rng: &mut R,
+        sink: &mut dyn PathSink<F>,
+    ) {
+        sink.begin_set(&self.op_set_type(), None);
+        self.fill_polygons_into(polygon_list, o, rng, sink);
+        sink.end_set();
+    }
+
+    /// Collects the sets of [`PatternFiller::fill_polygon_sets_into`].
+    fn fill_polygon_sets(&self, polygon_list: P, o: &Options, rng: &mut R) -> Vec<OpSet<F>> {
+        let mut sets = vec![];
+        self.fill_polygon_sets_into(polygon_list, o, rng, &mut sets);
+        sets
+    }
 }
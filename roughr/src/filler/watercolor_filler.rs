@@ -0,0 +1,233 @@
+use std::borrow::BorrowMut;
+use std::marker::PhantomData;
+
+use euclid::default::Point2D;
+use euclid::{vec2, Trig};
+use num_traits::{Float, FromPrimitive};
+use palette::{Hsla, IntoColor, Srgba};
+use rand::Rng;
+
+use super::traits::PatternFiller;
+use crate::core::{_c, _cc, Op, OpSetStyle, OpSetType, Options};
+use crate::sink::{stream_ops, PathSink};
+
+/// Frequencies of the waves that deform the outline, in periods along its length.
+const WAVE_FREQUENCIES: [f32; 4] = [2.0, 3.0, 5.0, 8.0];
+
+/// Number of times the edges of the outline are split and displaced before layering.
+const BASE_DEFORMATIONS: u32 = 1;
+
+/// Number of further splits each layer applies on top of the shared base shape.
+const LAYER_DEFORMATIONS: u32 = 2;
+
+/// Fills the shape with several deformed, translucent copies of its outline, each in a
+/// slightly different shade of the fill color, which blend into a watercolor wash.
+pub struct WatercolorFiller<F> {
+    _phantom: PhantomData<F>,
+}
+
+impl<F, P, R> PatternFiller<F, P, R> for WatercolorFiller<F>
+where
+    F: Float + Trig + FromPrimitive,
+    P: BorrowMut<Vec<Vec<Point2D<F>>>>,
+    R: Rng + Clone,
+{
+    /// Draws all layers one after the other, dropping their shades.
+    fn fill_polygons_into(
+        &self,
+        mut polygon_list: P,
+        o: &Options,
+        rng: &mut R,
+        sink: &mut dyn PathSink<F>,
+    ) {
+        WatercolorFiller::layers(polygon_list.borrow_mut(), o, rng, |ops, _| {
+            stream_ops(ops, sink)
+        });
+    }
+
+    fn op_set_type(&self) -> OpSetType {
+        OpSetType::FillPath
+    }
+
+    fn fill_polygon_sets_into(
+        &self,
+        mut polygon_list: P,
+        o: &Options,
+        rng: &mut R,
+        sink: &mut dyn PathSink<F>,
+    ) {
+        WatercolorFiller::layers(polygon_list.borrow_mut(), o, rng, |ops, style| {
+            sink.begin_set(&OpSetType::FillPath, Some(&style));
+            stream_ops(ops, sink);
+            sink.end_set();
+        });
+    }
+}
+
+impl<F: Float + Trig + FromPrimitive> WatercolorFiller<F> {
+    pub fn new() -> Self {
+        WatercolorFiller { _phantom: PhantomData }
+    }
+
+    /// Deforms the polygons into the layers of the wash, handing the ops and the shade of
+    /// each layer to `draw`.
+    fn layers<R: Rng + ?Sized>(
+        polygon_list: &[Vec<Point2D<F>>],
+        o: &Options,
+        rng: &mut R,
+        mut draw: impl FnMut(&[Op<F>], OpSetStyle),
+    ) {
+        let layers = o.watercolor_layers.unwrap_or(5).max(1);
+        let jitter = o.watercolor_color_jitter.unwrap_or(0.05).max(0.0);
+        let roughness = _c::<F>(o.roughness.unwrap_or(1.0));
+        let fill = o.fill.unwrap_or(Srgba::new(0.0, 0.0, 0.0, 1.0));
+        // Overlapping layers add up to about the alpha of the fill in the middle of the shape,
+        // while the edges, where fewer layers overlap, stay lighter.
+        let alpha = (fill.alpha * 2.0 / layers as f32).min(fill.alpha);
+
+        let base: Vec<Vec<Point2D<F>>> = polygon_list
+            .iter()
+            .filter(|polygon| polygon.len() > 2)
+            .map(|polygon| {
+                WatercolorFiller::deform(polygon, roughness * _c(0.02), BASE_DEFORMATIONS, rng)
+            })
+            .collect();
+
+        let mut ops = vec![];
+        for _ in 0..layers {
+            ops.clear();
+            for polygon in base.iter() {
+                let layer =
+                    WatercolorFiller::deform(polygon, roughness * _c(0.015), LAYER_DEFORMATIONS, rng);
+                for (i, p) in layer.iter().enumerate() {
+                    ops.push(if i == 0 { Op::Move(*p) } else { Op::LineTo(*p) });
+                }
+            }
+            let style = OpSetStyle {
+                color: Some(WatercolorFiller::<F>::shade(fill, alpha, jitter, rng)),
+                ..OpSetStyle::default()
+            };
+            draw(&ops, style);
+        }
+    }
+
+    /// Deforms the closed polygon in two scales: the whole outline is pushed in and out
+    /// along its normals by a few random waves of up to `amount` times its size, then every
+    /// edge is split `depth` times with small random bumps for the ragged border.
+    fn deform<R: Rng + ?Sized>(
+        polygon: &[Point2D<F>],
+        amount: F,
+        depth: u32,
+        rng: &mut R,
+    ) -> Vec<Point2D<F>> {
+        let len = polygon.len();
+        let mut perimeter = F::zero();
+        let mut arc = Vec::with_capacity(len);
+        let (mut min, mut max) = (polygon[0], polygon[0]);
+        for i in 0..len {
+            arc.push(perimeter);
+            perimeter = perimeter + (polygon[(i + 1) % len] - polygon[i]).length();
+            min = min.min(polygon[i]);
+            max = max.max(polygon[i]);
+        }
+        let size = (max - min).length();
+        if perimeter <= F::zero() {
+            return polygon.to_vec();
+        }
+
+        let waves: Vec<(F, F, F)> = WAVE_FREQUENCIES
+            .iter()
+            .map(|frequency| {
+                let frequency = _c::<F>(*frequency);
+                let amplitude = _cc::<F>(rng.gen()) / frequency;
+                let phase = _cc::<F>(rng.gen()) * _c(std::f32::consts::TAU);
+                (frequency, amplitude, phase)
+            })
+            .collect();
+        let mut points: Vec<Point2D<F>> = (0..len)
+            .map(|i| {
+                let tangent = polygon[(i + 1) % len] - polygon[(i + len - 1) % len];
+                if tangent.length() <= F::zero() {
+                    return polygon[i];
+                }
+                let normal = vec2(-tangent.y, tangent.x) / tangent.length();
+                let t = arc[i] / perimeter * _c(std::f32::consts::TAU);
+                let offset = waves
+                    .iter()
+                    .fold(F::zero(), |sum, (frequency, amplitude, phase)| {
+                        sum + *amplitude * Float::sin(*frequency * t + *phase)
+                    });
+                polygon[i] + normal * offset * amount * size
+            })
+            .collect();
+
+        for _ in 0..depth {
+            let mut next = Vec::with_capacity(points.len() * 2);
+            for i in 0..points.len() {
+                let (a, b) = (points[i], points[(i + 1) % points.len()]);
+                let edge = b - a;
+                // Summing two samples makes small bumps more likely than large ones.
+                let offset = _cc::<F>(rng.gen::<f64>() + rng.gen::<f64>()) - F::one();
+                next.push(a);
+                next.push(a.lerp(b, _c(0.5)) + vec2(-edge.y, edge.x) * (offset * _c(0.2)));
+            }
+            points = next;
+        }
+        points
+    }
+
+    /// The fill color with its hue and lightness shifted by up to `jitter`.
+    fn shade<R: Rng + ?Sized>(fill: Srgba, alpha: f32, jitter: f32, rng: &mut R) -> Srgba {
+        let mut hsla: Hsla = fill.into_color();
+        hsla.hue += (rng.gen::<f64>() as f32 * 2.0 - 1.0) * jitter * 360.0;
+        hsla.lightness =
+            (hsla.lightness + (rng.gen::<f64>() as f32 * 2.0 - 1.0) * jitter).clamp(0.0, 1.0);
+        hsla.alpha = alpha;
+        hsla.into_color()
+    }
+}
+
+impl<F: Float + Trig + FromPrimitive> Default for WatercolorFiller<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use euclid::point2;
+    use palette::Srgba;
+
+    use super::WatercolorFiller;
+    use crate::core::{OpSetType, OptionsBuilder};
+    use crate::filler::traits::PatternFiller;
+
+    #[test]
+    fn fill_polygon_sets() {
+        let square = vec![vec![
+            point2(0.0, 0.0),
+            point2(40.0, 0.0),
+            point2(40.0, 40.0),
+            point2(0.0, 40.0),
+        ]];
+        let fill = Srgba::new(0.2, 0.4, 0.8, 0.8);
+        let o = OptionsBuilder::default()
+            .fill(fill)
+            .watercolor_layers(4)
+            .build()
+            .unwrap();
+        let sets: Vec<_> =
+            WatercolorFiller::<f64>::new().fill_polygon_sets(square, &o, &mut o.rng());
+
+        assert_eq!(sets.len(), 4);
+        for set in sets.iter() {
+            assert_eq!(set.op_set_type, OpSetType::FillPath);
+            let color = set.style.as_ref().and_then(|style| style.color).unwrap();
+            assert!((color.alpha - 0.4).abs() < 1e-6);
+            assert!((color.blue - fill.blue).abs() < 0.2);
+            // 4 edges, each split once for the base shape and twice for the layer.
+            assert_eq!(set.ops.len(), 4 * 8);
+        }
+        assert_ne!(sets[0].ops, sets[1].ops);
+    }
+}
@@ -0,0 +1,147 @@
+use std::borrow::BorrowMut;
+use std::marker::PhantomData;
+
+use euclid::default::Point2D;
+use euclid::{vec2, Trig};
+use num_traits::{Float, FloatConst, FromPrimitive};
+use rand::Rng;
+
+use super::scan_line_hachure::polygon_hachure_lines;
+use super::traits::PatternFiller;
+use crate::core::{_c, Options};
+use crate::geometry::{clip_polyline, Line};
+use crate::renderer::_double_curve;
+use crate::sink::PathSink;
+
+/// Number of points sampled for every wave length of a line.
+const SAMPLES_PER_WAVE: usize = 8;
+
+pub struct WavyFiller<F> {
+    _phantom: PhantomData<F>,
+}
+
+impl<F, P, R> PatternFiller<F, P, R> for WavyFiller<F>
+where
+    F: Float + Trig + FromPrimitive,
+    P: BorrowMut<Vec<Vec<Point2D<F>>>>,
+    R: Rng + Clone,
+{
+    fn fill_polygons_into(
+        &self,
+        mut polygon_list: P,
+        o: &Options,
+        rng: &mut R,
+        sink: &mut dyn PathSink<F>,
+    ) {
+        let mut gap = o.hachure_gap.map(_c::<F>).unwrap_or_else(|| _c::<F>(-1.0));
+        if gap < F::zero() {
+            gap = o.stroke_width.map(_c::<F>).unwrap_or_else(|| _c::<F>(1.0)) * _c::<F>(4.0);
+        }
+        gap = gap.max(_c::<F>(0.1));
+        let mut amplitude = o
+            .wave_amplitude
+            .map(_c::<F>)
+            .unwrap_or_else(|| _c::<F>(-1.0));
+        if amplitude < F::zero() {
+            amplitude = gap / _c::<F>(4.0);
+        }
+        let mut wave_length = o.wave_length.map(_c::<F>).unwrap_or_else(|| _c::<F>(-1.0));
+        if wave_length <= F::zero() {
+            wave_length = gap * _c::<F>(4.0);
+        }
+        wave_length = wave_length.max(_c::<F>(0.1));
+
+        let polygon_list = polygon_list.borrow_mut();
+        let lines = polygon_hachure_lines(polygon_list, o, rng);
+        let fill_rule = o.fill_rule.unwrap_or_default();
+        for line in lines.iter() {
+            let wave = WavyFiller::wave_points(line, amplitude, wave_length);
+            for run in clip_polyline(&wave, polygon_list, fill_rule) {
+                _double_curve(&run, o, rng, true, sink);
+            }
+        }
+    }
+}
+
+impl<F: Float + Trig + FromPrimitive> WavyFiller<F> {
+    pub fn new() -> Self {
+        WavyFiller { _phantom: PhantomData }
+    }
+
+    /// Samples a sine wave around the line. The phase is taken from the position along the
+    /// hachure direction, so that the waves of neighbouring lines run in parallel.
+    fn wave_points(line: &Line<F>, amplitude: F, wave_length: F) -> Vec<Point2D<F>> {
+        let length = line.length();
+        if length <= F::zero() {
+            return vec![];
+        }
+        let direction = (line.end_point - line.start_point) / length;
+        let normal = vec2(-direction.y, direction.x);
+        let step = wave_length / F::from(SAMPLES_PER_WAVE).unwrap();
+        let count = (length / step).ceil().to_usize().unwrap_or(0).max(1);
+        let phase_origin = line.start_point.to_vector().dot(direction);
+
+        (0..=count)
+            .map(|i| {
+                let distance = (F::from(i).unwrap() * step).min(length);
+                let phase = (phase_origin + distance) / wave_length * _c::<F>(f32::PI() * 2.0);
+                line.start_point + direction * distance + normal * (amplitude * Float::sin(phase))
+            })
+            .collect()
+    }
+}
+
+impl<F: Float + Trig + FromPrimitive> Default for WavyFiller<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use euclid::default::Point2D;
+    use euclid::point2;
+
+    use super::WavyFiller;
+    use crate::core::{FillRule, OptionsBuilder};
+    use crate::filler::traits::PatternFiller;
+    use crate::geometry::point_in_polygons;
+
+    #[test]
+    fn waves_stay_inside() {
+        let u = vec![vec![
+            point2(0.0, 0.0),
+            point2(15.0, 0.0),
+            point2(15.0, 30.0),
+            point2(35.0, 30.0),
+            point2(35.0, 0.0),
+            point2(50.0, 0.0),
+            point2(50.0, 40.0),
+            point2(0.0, 40.0),
+        ]];
+        let o = OptionsBuilder::default()
+            .roughness(0.0)
+            .hachure_gap(4.0)
+            .wave_amplitude(3.0)
+            .build()
+            .unwrap();
+        let set = WavyFiller::<f64>::new().fill_polygons(u.clone(), &o, &mut o.rng());
+
+        // Points cut at the outline may land a rounding error outside of it.
+        let inside = |p: Point2D<f64>| {
+            [
+                (0.0, 0.0),
+                (1e-6, 0.0),
+                (-1e-6, 0.0),
+                (0.0, 1e-6),
+                (0.0, -1e-6),
+            ]
+            .iter()
+            .any(|&(dx, dy)| point_in_polygons(point2(p.x + dx, p.y + dy), &u, FillRule::NonZero))
+        };
+        assert!(!set.ops.is_empty());
+        for op in set.ops.iter() {
+            assert!(inside(op.end_point().unwrap()));
+        }
+    }
+}
@@ -4,23 +4,32 @@ use std::marker::PhantomData;
 use euclid::default::Point2D;
 use euclid::{point2, Trig};
 use num_traits::{Float, FloatConst, FromPrimitive};
+use rand::Rng;
 
 use super::scan_line_hachure::polygon_hachure_lines;
 use super::traits::PatternFiller;
-use crate::core::{Op, OpSet, OpSetType, Options, _c};
+use crate::core::{_c, Options};
 use crate::geometry::Line;
 use crate::renderer::_double_line;
+use crate::sink::PathSink;
 
 pub struct ZigZagLineFiller<F> {
     _phantom: PhantomData<F>,
 }
 
-impl<F, P> PatternFiller<F, P> for ZigZagLineFiller<F>
+impl<F, P, R> PatternFiller<F, P, R> for ZigZagLineFiller<F>
 where
     F: Float + Trig + FromPrimitive,
     P: BorrowMut<Vec<Vec<Point2D<F>>>>,
+    R: Rng + Clone,
 {
-    fn fill_polygons(&self, mut polygon_list: P, o: &mut Options) -> crate::core::OpSet<F> {
+    fn fill_polygons_into(
+        &self,
+        mut polygon_list: P,
+        o: &Options,
+        rng: &mut R,
+        sink: &mut dyn PathSink<F>,
+    ) {
         let mut gap = o.hachure_gap.map(_c::<F>).unwrap_or_else(|| _c::<F>(-1.0));
         if gap < F::zero() {
             gap = o.stroke_width.map(_c::<F>).unwrap_or_else(|| _c::<F>(1.0)) * _c::<F>(4.0);
@@ -34,14 +43,12 @@ where
         if zig_zag_offset < F::zero() {
             zig_zag_offset = gap;
         }
-        o.set_hachure_gap(Some((gap + zig_zag_offset).to_f32().unwrap()));
-        let lines = polygon_hachure_lines(polygon_list.borrow_mut(), o);
-        OpSet {
-            op_set_type: OpSetType::FillSketch,
-            ops: ZigZagLineFiller::zig_zag_lines(&lines, zig_zag_offset, o),
-            size: None,
-            path: None,
-        }
+        let o = &Options {
+            hachure_gap: Some((gap + zig_zag_offset).to_f32().unwrap()),
+            ..o.clone()
+        };
+        let lines = polygon_hachure_lines(polygon_list.borrow_mut(), o, rng);
+        ZigZagLineFiller::zig_zag_lines(&lines, zig_zag_offset, o, rng, sink);
     }
 }
 
@@ -50,8 +57,13 @@ impl<F: Float + Trig + FromPrimitive> ZigZagLineFiller<F> {
         ZigZagLineFiller { _phantom: PhantomData }
     }
 
-    fn zig_zag_lines(lines: &[Line<F>], zig_zag_offset: F, o: &mut Options) -> Vec<Op<F>> {
-        let mut ops = vec![];
+    fn zig_zag_lines<R: Rng + ?Sized>(
+        lines: &[Line<F>],
+        zig_zag_offset: F,
+        o: &Options,
+        rng: &mut R,
+        sink: &mut dyn PathSink<F>,
+    ) {
         for line in lines.iter() {
             let length = line.length();
             let count = length / (_c::<F>(2.0) * zig_zag_offset);
@@ -80,11 +92,9 @@ impl<F: Float + Trig + FromPrimitive> ZigZagLineFiller<F> {
                     start.x + dz * num_traits::Float::cos(alpha + _c::<F>(f32::PI() / 4.0)),
                     start.y + dz * num_traits::Float::sin(alpha + _c::<F>(f32::PI() / 4.0)),
                 );
-                ops.extend(_double_line(start.x, start.y, middle.x, middle.y, o, false));
-
-                ops.extend(_double_line(middle.x, middle.y, end.x, end.y, o, false));
+                _double_line(start, middle, o, rng, false, sink);
+                _double_line(middle, end, o, rng, false, sink);
             }
         }
-        ops
     }
 }
@@ -1,43 +1,55 @@
 use std::fmt::{Display, Write};
 use std::ops::MulAssign;
 
-use euclid::default::Point2D;
+use euclid::default::{Point2D, Vector2D};
 use euclid::Trig;
 use num_traits::{Float, FromPrimitive};
-use points_on_curve::{curve_to_bezier, points_on_bezier_curves};
+use points_on_curve::{catmull_rom_to_bezier, curve_to_bezier, points_on_bezier_curves};
+use rand::Rng;
 use svgtypes::PathSegment;
 
 use crate::core::{
+    _c,
+    ellipse_path,
+    ArcMode,
+    CurveParameterization,
     Drawable,
     FillStyle,
+    Op,
     OpSet,
     OpSetType,
-    OpType,
     Options,
     OptionsBuilder,
     PathInfo,
-    _c,
+    ShapeKind,
+    StrokeStyle,
 };
 use crate::geometry::{convert_bezier_quadratic_to_cubic, BezierQuadratic};
 use crate::points_on_path::{points_on_path, points_on_segments};
 use crate::renderer::{
-    bezier_cubic,
-    bezier_quadratic,
-    curve,
-    ellipse_with_params,
+    arc_into,
+    bezier_cubic_into,
+    bezier_quadratic_into,
+    curve_into,
+    dashed_polylines_into,
+    ellipse_with_params_into,
+    fill_registration,
     generate_ellipse_params,
-    line,
-    linear_path,
-    pattern_fill_arc,
-    pattern_fill_polygons,
-    rectangle,
-    solid_fill_polygon,
-    svg_path,
-    svg_segments,
+    line_into,
+    linear_path_into,
+    normalize_arc_angles,
+    pattern_fill_arc_sets_into,
+    pattern_fill_polygon_sets_into,
+    rectangle_into,
+    solid_fill_polygon_into,
+    svg_path_into,
+    svg_segments_into,
+    ArcParams,
 };
+use crate::sink::{stream_ops, PathSink};
 
 pub struct Generator {
-    default_options: Options,
+    pub(crate) default_options: Options,
 }
 
 impl Default for Generator {
@@ -52,39 +64,60 @@ impl Default for Generator {
 }
 
 impl Generator {
-    fn new(options: Options) -> Self {
+    /// Generator drawing shapes with `options` when they are not given options of their own.
+    pub fn new(options: Options) -> Self {
         Generator { default_options: options }
     }
 
-    fn d<T, F>(&self, name: T, op_sets: &[OpSet<F>], options: &Option<Options>) -> Drawable<F>
+    /// The given options, or the default options of the generator.
+    fn options(&self, options: &Option<Options>) -> Options {
+        options
+            .clone()
+            .unwrap_or_else(|| self.default_options.clone())
+    }
+
+    /// Drawable of `shape`, without any sets.
+    fn d<F>(&self, shape: ShapeKind<F>, options: Options) -> Drawable<F>
     where
-        T: Into<String>,
         F: Float + Trig + FromPrimitive,
     {
-        Drawable {
-            shape: name.into(),
-            options: options
-                .clone()
-                .unwrap_or_else(|| self.default_options.clone()),
-            sets: Vec::from_iter(op_sets.iter().cloned()),
-        }
+        Drawable { shape, options, sets: vec![] }
     }
 
     pub fn line<F>(&self, x1: F, y1: F, x2: F, y2: F, options: &Option<Options>) -> Drawable<F>
     where
         F: Float + Trig + FromPrimitive,
     {
+        collect(|sets| self.line_into(x1, y1, x2, y2, options, sets))
+    }
+
+    /// Like [`Generator::line`], but streams the sets into `sink`, see
+    /// [`Generator::draw_into`].
+    pub fn line_into<F>(
+        &self,
+        x1: F,
+        y1: F,
+        x2: F,
+        y2: F,
+        options: &Option<Options>,
+        sink: &mut dyn PathSink<F>,
+    ) -> Drawable<F>
+    where
+        F: Float + Trig + FromPrimitive,
+    {
+        let options = self.options(options);
+        let mut rng = options.rng();
+        let mut outline = vec![];
+        line_into(x1, y1, x2, y2, &options, &mut rng, &mut outline);
+        let polylines = || vec![vec![Point2D::new(x1, y1), Point2D::new(x2, y2)]];
+        if !dashes_into(polylines, &options, &mut rng, sink) {
+            outline_into(&outline, sink);
+        }
         self.d(
-            "line",
-            &[line(
-                x1,
-                y1,
-                x2,
-                y2,
-                &mut options
-                    .clone()
-                    .unwrap_or_else(|| self.default_options.clone()),
-            )],
+            ShapeKind::Line {
+                start: Point2D::new(x1, y1),
+                end: Point2D::new(x2, y2),
+            },
             options,
         )
     }
@@ -100,11 +133,27 @@ impl Generator {
     where
         F: Float + Trig + FromPrimitive,
     {
-        let mut paths = vec![];
-        let mut options = options
-            .clone()
-            .unwrap_or_else(|| self.default_options.clone());
-        let outline = rectangle(x, y, width, height, &mut options);
+        collect(|sets| self.rectangle_into(x, y, width, height, options, sets))
+    }
+
+    /// Like [`Generator::rectangle`], but streams the sets into `sink`, see
+    /// [`Generator::draw_into`].
+    pub fn rectangle_into<F>(
+        &self,
+        x: F,
+        y: F,
+        width: F,
+        height: F,
+        options: &Option<Options>,
+        sink: &mut dyn PathSink<F>,
+    ) -> Drawable<F>
+    where
+        F: Float + Trig + FromPrimitive,
+    {
+        let options = self.options(options);
+        let mut rng = options.rng();
+        let mut outline = vec![];
+        rectangle_into(x, y, width, height, &options, &mut rng, &mut outline);
         if options.fill.is_some() {
             let points = vec![
                 Point2D::new(x, y),
@@ -112,17 +161,21 @@ impl Generator {
                 Point2D::new(x + width, y + height),
                 Point2D::new(x, y + height),
             ];
-            if options.fill_style == Some(FillStyle::Solid) {
-                paths.push(solid_fill_polygon(&vec![points], &mut options));
-            } else {
-                paths.push(pattern_fill_polygons(vec![points], &mut options));
-            }
+            fill_into(vec![points], &options, &mut rng, sink);
         }
         if options.stroke.is_some() {
-            paths.push(outline);
+            let points = vec![
+                Point2D::new(x, y),
+                Point2D::new(x + width, y),
+                Point2D::new(x + width, y + height),
+                Point2D::new(x, y + height),
+                Point2D::new(x, y),
+            ];
+            if !dashes_into(|| vec![points], &options, &mut rng, sink) {
+                outline_into(&outline, sink);
+            }
         }
-
-        self.d("rectangle", &paths, &Some(options))
+        self.d(ShapeKind::Rectangle { x, y, width, height }, options)
     }
 
     pub fn ellipse<F>(
@@ -136,36 +189,101 @@ impl Generator {
     where
         F: Float + Trig + FromPrimitive,
     {
-        let mut paths = vec![];
-        let mut options = options
-            .clone()
-            .unwrap_or_else(|| self.default_options.clone());
-        let ellipse_params = generate_ellipse_params(width, height, &mut options);
-        let ellipse_response = ellipse_with_params(x, y, &mut options, &ellipse_params);
+        collect(|sets| self.ellipse_into(x, y, width, height, options, sets))
+    }
+
+    /// Like [`Generator::ellipse`], but streams the sets into `sink`, see
+    /// [`Generator::draw_into`].
+    pub fn ellipse_into<F>(
+        &self,
+        x: F,
+        y: F,
+        width: F,
+        height: F,
+        options: &Option<Options>,
+        sink: &mut dyn PathSink<F>,
+    ) -> Drawable<F>
+    where
+        F: Float + Trig + FromPrimitive,
+    {
+        let options = self.options(options);
+        let mut rng = options.rng();
+        let ellipse_params = generate_ellipse_params(width, height, &options, &mut rng);
+        let mut outline = vec![];
+        let estimated_points =
+            ellipse_with_params_into(x, y, &options, &mut rng, &ellipse_params, &mut outline);
         if options.fill.is_some() {
             if options.fill_style == Some(FillStyle::Solid) {
-                let mut shape = ellipse_with_params(x, y, &mut options, &ellipse_params).opset;
-                shape.op_set_type = OpSetType::FillPath;
-                paths.push(shape);
+                set_into(sink, OpSetType::FillPath, |sink| {
+                    match fill_registration::<F, _>(&options, &mut rng) {
+                        Some((offset, inset)) => {
+                            let params = generate_ellipse_params(
+                                (width - inset * _c(2.0)).max(F::zero()),
+                                (height - inset * _c(2.0)).max(F::zero()),
+                                &options,
+                                &mut rng,
+                            );
+                            let (x, y) = (x + offset.x, y + offset.y);
+                            ellipse_with_params_into(x, y, &options, &mut rng, &params, sink);
+                        }
+                        None => {
+                            ellipse_with_params_into(
+                                x,
+                                y,
+                                &options,
+                                &mut rng,
+                                &ellipse_params,
+                                sink,
+                            );
+                        }
+                    }
+                });
             } else {
-                paths.push(pattern_fill_polygons(
-                    vec![ellipse_response.estimated_points],
-                    &mut options,
-                ));
+                pattern_fill_polygon_sets_into(vec![estimated_points], &options, &mut rng, sink);
             }
         }
         if options.stroke.is_some() {
-            paths.push(ellipse_response.opset);
+            let points = || {
+                vec![ellipse_points(
+                    Point2D::new(x, y),
+                    width,
+                    height,
+                    F::zero(),
+                    _c(std::f32::consts::TAU),
+                )]
+            };
+            if !dashes_into(points, &options, &mut rng, sink) {
+                outline_into(&outline, sink);
+            }
         }
-        self.d("ellipse", &paths, &Some(options))
+        self.d(
+            ShapeKind::Ellipse { center: Point2D::new(x, y), width, height },
+            options,
+        )
     }
 
     pub fn circle<F>(&self, x: F, y: F, diameter: F, options: &Option<Options>) -> Drawable<F>
     where
         F: Float + Trig + FromPrimitive,
     {
-        let mut shape = self.ellipse(x, y, diameter, diameter, options);
-        shape.shape = "circle".into();
+        collect(|sets| self.circle_into(x, y, diameter, options, sets))
+    }
+
+    /// Like [`Generator::circle`], but streams the sets into `sink`, see
+    /// [`Generator::draw_into`].
+    pub fn circle_into<F>(
+        &self,
+        x: F,
+        y: F,
+        diameter: F,
+        options: &Option<Options>,
+        sink: &mut dyn PathSink<F>,
+    ) -> Drawable<F>
+    where
+        F: Float + Trig + FromPrimitive,
+    {
+        let mut shape = self.ellipse_into(x, y, diameter, diameter, options, sink);
+        shape.shape = ShapeKind::Circle { center: Point2D::new(x, y), diameter };
         shape
     }
 
@@ -178,16 +296,35 @@ impl Generator {
     where
         F: Float + Trig + FromPrimitive,
     {
-        let mut options = options
-            .clone()
-            .unwrap_or_else(|| self.default_options.clone());
+        collect(|sets| self.linear_path_into(points, close, options, sets))
+    }
+
+    /// Like [`Generator::linear_path`], but streams the sets into `sink`, see
+    /// [`Generator::draw_into`].
+    pub fn linear_path_into<F>(
+        &self,
+        points: &[Point2D<F>],
+        close: bool,
+        options: &Option<Options>,
+        sink: &mut dyn PathSink<F>,
+    ) -> Drawable<F>
+    where
+        F: Float + Trig + FromPrimitive,
+    {
+        let options = self.options(options);
+        let mut rng = options.rng();
+        let mut outline = vec![];
+        linear_path_into(points, close, &options, &mut rng, &mut outline);
+        if !dashes_into(|| vec![polyline(points, close)], &options, &mut rng, sink) {
+            outline_into(&outline, sink);
+        }
         self.d(
-            "linear_path",
-            &[linear_path(points, close, &mut options)],
-            &Some(options),
+            ShapeKind::LinearPath { points: points.to_vec(), close },
+            options,
         )
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn arc<F>(
         &self,
         x: F,
@@ -196,50 +333,92 @@ impl Generator {
         height: F,
         start: F,
         stop: F,
-        closed: bool,
+        mode: ArcMode,
         options: &Option<Options>,
     ) -> Drawable<F>
     where
         F: Float + Trig + FromPrimitive,
     {
-        let mut options = options
-            .clone()
-            .unwrap_or_else(|| self.default_options.clone());
-        let mut paths = vec![];
-        let outline =
-            crate::renderer::arc(x, y, width, height, start, stop, closed, true, &mut options);
-        if closed && options.fill.is_some() {
+        collect(|sets| self.arc_into(x, y, width, height, start, stop, mode, options, sets))
+    }
+
+    /// Like [`Generator::arc`], but streams the sets into `sink`, see
+    /// [`Generator::draw_into`].
+    #[allow(clippy::too_many_arguments)]
+    pub fn arc_into<F>(
+        &self,
+        x: F,
+        y: F,
+        width: F,
+        height: F,
+        start: F,
+        stop: F,
+        mode: ArcMode,
+        options: &Option<Options>,
+        sink: &mut dyn PathSink<F>,
+    ) -> Drawable<F>
+    where
+        F: Float + Trig + FromPrimitive,
+    {
+        let options = self.options(options);
+        let mut rng = options.rng();
+        let params = ArcParams { x, y, width, height, start, stop, mode };
+        let mut outline = vec![];
+        arc_into(&params, true, &options, &mut rng, &mut outline);
+        // an arc that sweeps no angle covers no area to fill
+        let (strt, stp) = normalize_arc_angles(start, stop);
+        if mode != ArcMode::Open && options.fill.is_some() && stp > strt {
             if options.fill_style == Some(FillStyle::Solid) {
-                options.disable_multi_stroke = Some(true);
-                let mut shape = crate::renderer::arc(
-                    x,
-                    y,
-                    width,
-                    height,
-                    start,
-                    stop,
-                    true,
-                    false,
-                    &mut options,
-                );
-                shape.op_set_type = OpSetType::FillPath;
-                paths.push(shape);
+                let fill_options = Options {
+                    disable_multi_stroke: Some(true),
+                    stroke_passes: Some(1),
+                    ..options.clone()
+                };
+                let (offset, inset) = fill_registration::<F, _>(&fill_options, &mut rng)
+                    .unwrap_or((Vector2D::zero(), F::zero()));
+                let registered = ArcParams {
+                    x: x + offset.x,
+                    y: y + offset.y,
+                    width: (width - inset * _c(2.0)).max(F::zero()),
+                    height: (height - inset * _c(2.0)).max(F::zero()),
+                    ..params
+                };
+                set_into(sink, OpSetType::FillPath, |sink| {
+                    arc_into(&registered, false, &fill_options, &mut rng, sink)
+                });
             } else {
-                paths.push(pattern_fill_arc(
-                    x,
-                    y,
-                    width,
-                    height,
-                    start,
-                    stop,
-                    &mut options,
-                ));
+                pattern_fill_arc_sets_into(&params, &options, &mut rng, sink);
             }
         }
         if options.stroke.is_some() {
-            paths.push(outline);
+            let points = || {
+                let center = Point2D::new(x, y);
+                let mut points = ellipse_points(center, width, height, start, stop);
+                match mode {
+                    ArcMode::Open => {}
+                    ArcMode::Chord => points.push(points[0]),
+                    ArcMode::Pie => {
+                        points.insert(0, center);
+                        points.push(center);
+                    }
+                }
+                vec![points]
+            };
+            if !dashes_into(points, &options, &mut rng, sink) {
+                outline_into(&outline, sink);
+            }
         }
-        self.d("arc", &paths, &Some(options))
+        self.d(
+            ShapeKind::Arc {
+                center: Point2D::new(x, y),
+                width,
+                height,
+                start,
+                stop,
+                mode,
+            },
+            options,
+        )
     }
 
     pub fn bezier_quadratic<F>(
@@ -252,12 +431,27 @@ impl Generator {
     where
         F: Float + Trig + FromPrimitive + MulAssign + Display,
     {
-        let mut paths = vec![];
-        let mut options = options
-            .clone()
-            .unwrap_or_else(|| self.default_options.clone());
+        collect(|sets| self.bezier_quadratic_into(start, cp, end, options, sets))
+    }
 
-        let outline = bezier_quadratic(start, cp, end, &mut options);
+    /// Like [`Generator::bezier_quadratic`], but streams the sets into `sink`, see
+    /// [`Generator::draw_into`].
+    pub fn bezier_quadratic_into<F>(
+        &self,
+        start: Point2D<F>,
+        cp: Point2D<F>,
+        end: Point2D<F>,
+        options: &Option<Options>,
+        sink: &mut dyn PathSink<F>,
+    ) -> Drawable<F>
+    where
+        F: Float + Trig + FromPrimitive + MulAssign + Display,
+    {
+        let options = self.options(options);
+        let mut rng = options.rng();
+
+        let mut outline = vec![];
+        bezier_quadratic_into(start, cp, end, &options, &mut rng, &mut outline);
 
         if options.fill.is_some() {
             // The fill algorithms expect at least 4 points of a cubic curve, else they panic
@@ -269,18 +463,24 @@ impl Generator {
                 _c(10.0),
                 Some(_c::<F>(1.0) + _c::<F>(options.roughness.unwrap_or(0.0)) / _c(2.0)),
             );
-            if options.fill_style == Some(FillStyle::Solid) {
-                paths.push(solid_fill_polygon(&vec![poly_points], &mut options));
-            } else {
-                paths.push(pattern_fill_polygons(&mut vec![poly_points], &mut options));
-            }
+            fill_into(vec![poly_points], &options, &mut rng, sink);
         }
 
         if options.stroke.is_some() {
-            paths.push(outline);
+            let points = || {
+                let cubic = convert_bezier_quadratic_to_cubic(BezierQuadratic { start, cp, end });
+                vec![points_on_bezier_curves(
+                    &[cubic.start, cubic.cp1, cubic.cp2, cubic.end],
+                    _c(0.5),
+                    None,
+                )]
+            };
+            if !dashes_into(points, &options, &mut rng, sink) {
+                outline_into(&outline, sink);
+            }
         }
 
-        self.d("curve", &paths, &Some(options))
+        self.d(ShapeKind::BezierQuadratic { start, cp, end }, options)
     }
 
     pub fn bezier_cubic<F>(
@@ -294,12 +494,28 @@ impl Generator {
     where
         F: Float + Trig + FromPrimitive + MulAssign + Display,
     {
-        let mut paths = vec![];
-        let mut options = options
-            .clone()
-            .unwrap_or_else(|| self.default_options.clone());
+        collect(|sets| self.bezier_cubic_into(start, cp1, cp2, end, options, sets))
+    }
 
-        let outline = bezier_cubic(start, cp1, cp2, end, &mut options);
+    /// Like [`Generator::bezier_cubic`], but streams the sets into `sink`, see
+    /// [`Generator::draw_into`].
+    pub fn bezier_cubic_into<F>(
+        &self,
+        start: Point2D<F>,
+        cp1: Point2D<F>,
+        cp2: Point2D<F>,
+        end: Point2D<F>,
+        options: &Option<Options>,
+        sink: &mut dyn PathSink<F>,
+    ) -> Drawable<F>
+    where
+        F: Float + Trig + FromPrimitive + MulAssign + Display,
+    {
+        let options = self.options(options);
+        let mut rng = options.rng();
+
+        let mut outline = vec![];
+        bezier_cubic_into(start, cp1, cp2, end, &options, &mut rng, &mut outline);
 
         if options.fill.is_some() {
             let crv = vec![start, cp1, cp2, end];
@@ -309,86 +525,222 @@ impl Generator {
                 _c(10.0),
                 Some(_c::<F>(1.0) + _c::<F>(options.roughness.unwrap_or(0.0)) / _c(2.0)),
             );
-            if options.fill_style == Some(FillStyle::Solid) {
-                paths.push(solid_fill_polygon(&vec![poly_points], &mut options));
-            } else {
-                paths.push(pattern_fill_polygons(&mut vec![poly_points], &mut options));
-            }
+            fill_into(vec![poly_points], &options, &mut rng, sink);
         }
 
         if options.stroke.is_some() {
-            paths.push(outline);
+            let points = || {
+                vec![points_on_bezier_curves(
+                    &[start, cp1, cp2, end],
+                    _c(0.5),
+                    None,
+                )]
+            };
+            if !dashes_into(points, &options, &mut rng, sink) {
+                outline_into(&outline, sink);
+            }
         }
 
-        self.d("curve", &paths, &Some(options))
+        self.d(ShapeKind::BezierCubic { start, cp1, cp2, end }, options)
     }
 
     pub fn curve<F>(&self, points: &[Point2D<F>], options: &Option<Options>) -> Drawable<F>
     where
         F: Float + Trig + FromPrimitive + MulAssign + Display,
     {
-        let mut paths = vec![];
-        let mut options = options
-            .clone()
-            .unwrap_or_else(|| self.default_options.clone());
-        let outline = curve(points, &mut options);
+        collect(|sets| self.curve_into(points, options, sets))
+    }
+
+    /// Like [`Generator::curve`], but streams the sets into `sink`, see
+    /// [`Generator::draw_into`].
+    pub fn curve_into<F>(
+        &self,
+        points: &[Point2D<F>],
+        options: &Option<Options>,
+        sink: &mut dyn PathSink<F>,
+    ) -> Drawable<F>
+    where
+        F: Float + Trig + FromPrimitive + MulAssign + Display,
+    {
+        let options = self.options(options);
+        let mut rng = options.rng();
+        let mut outline = vec![];
+        curve_into(points, &options, &mut rng, &mut outline);
         if options.fill.is_some() && points.len() >= 3 {
-            let curve = curve_to_bezier(points, _c(0.0));
-            if let Some(crv) = curve {
+            if let Some(crv) = curve_bezier(points, &options) {
                 let poly_points = points_on_bezier_curves(
                     &crv,
                     _c(10.0),
                     Some(_c::<F>(1.0) + _c::<F>(options.roughness.unwrap_or(0.0)) / _c(2.0)),
                 );
-                if options.fill_style == Some(FillStyle::Solid) {
-                    paths.push(solid_fill_polygon(&vec![poly_points], &mut options));
-                } else {
-                    paths.push(pattern_fill_polygons(&mut vec![poly_points], &mut options));
-                }
+                fill_into(vec![poly_points], &options, &mut rng, sink);
             }
         }
 
         if options.stroke.is_some() {
-            paths.push(outline);
+            let bezier = curve_bezier(points, &options);
+            let curve_points = || match bezier {
+                Some(crv) => vec![points_on_bezier_curves(&crv, _c(0.5), None)],
+                None => vec![points.to_vec()],
+            };
+            if !dashes_into(curve_points, &options, &mut rng, sink) {
+                outline_into(&outline, sink);
+            }
         }
 
-        self.d("curve", &paths, &Some(options))
+        self.d(ShapeKind::Curve { points: points.to_vec() }, options)
     }
 
     pub fn polygon<F>(&self, points: &[Point2D<F>], options: &Option<Options>) -> Drawable<F>
     where
         F: Float + Trig + FromPrimitive + MulAssign + Display,
     {
-        let mut options = options
-            .clone()
-            .unwrap_or_else(|| self.default_options.clone());
-        let mut paths = vec![];
-        let outline = linear_path(points, true, &mut options);
+        collect(|sets| self.polygon_into(points, options, sets))
+    }
+
+    /// Like [`Generator::polygon`], but streams the sets into `sink`, see
+    /// [`Generator::draw_into`].
+    pub fn polygon_into<F>(
+        &self,
+        points: &[Point2D<F>],
+        options: &Option<Options>,
+        sink: &mut dyn PathSink<F>,
+    ) -> Drawable<F>
+    where
+        F: Float + Trig + FromPrimitive + MulAssign + Display,
+    {
+        let options = self.options(options);
+        let mut rng = options.rng();
+        let mut outline = vec![];
+        linear_path_into(points, true, &options, &mut rng, &mut outline);
         if options.fill.is_some() {
-            if options.fill_style == Some(FillStyle::Solid) {
-                paths.push(solid_fill_polygon(&vec![points.to_vec()], &mut options));
-            } else {
-                paths.push(pattern_fill_polygons(
-                    &mut vec![points.to_vec()],
-                    &mut options,
-                ));
-            }
+            fill_into(vec![points.to_vec()], &options, &mut rng, sink);
+        }
+        if options.stroke.is_some()
+            && !dashes_into(|| vec![polyline(points, true)], &options, &mut rng, sink)
+        {
+            outline_into(&outline, sink);
+        }
+        self.d(ShapeKind::Polygon { points: points.to_vec() }, options)
+    }
+
+    /// A polygon with cut-out holes. Every ring is stroked, and all of them are filled
+    /// together, leaving the holes empty.
+    pub fn polygon_with_holes<F>(
+        &self,
+        outer: &[Point2D<F>],
+        holes: &[Vec<Point2D<F>>],
+        options: &Option<Options>,
+    ) -> Drawable<F>
+    where
+        F: Float + Trig + FromPrimitive + MulAssign + Display,
+    {
+        collect(|sets| self.polygon_with_holes_into(outer, holes, options, sets))
+    }
+
+    /// Like [`Generator::polygon_with_holes`], but streams the sets into `sink`, see
+    /// [`Generator::draw_into`].
+    pub fn polygon_with_holes_into<F>(
+        &self,
+        outer: &[Point2D<F>],
+        holes: &[Vec<Point2D<F>>],
+        options: &Option<Options>,
+        sink: &mut dyn PathSink<F>,
+    ) -> Drawable<F>
+    where
+        F: Float + Trig + FromPrimitive + MulAssign + Display,
+    {
+        let mut rings = vec![outer.to_vec()];
+        rings.extend(holes.iter().cloned());
+        let mut shape = self.multi_polygon_into(&[rings], options, sink);
+        shape.shape = ShapeKind::PolygonWithHoles { outer: outer.to_vec(), holes: holes.to_vec() };
+        shape
+    }
+
+    /// Several polygons drawn as one shape, each given as its outer ring followed by its
+    /// holes. The rings are oriented so that holes stay empty under either fill rule, while
+    /// the fill rule decides how overlapping polygons combine.
+    pub fn multi_polygon<F>(
+        &self,
+        polygons: &[Vec<Vec<Point2D<F>>>],
+        options: &Option<Options>,
+    ) -> Drawable<F>
+    where
+        F: Float + Trig + FromPrimitive + MulAssign + Display,
+    {
+        collect(|sets| self.multi_polygon_into(polygons, options, sets))
+    }
+
+    /// Like [`Generator::multi_polygon`], but streams the sets into `sink`, see
+    /// [`Generator::draw_into`].
+    pub fn multi_polygon_into<F>(
+        &self,
+        polygons: &[Vec<Vec<Point2D<F>>>],
+        options: &Option<Options>,
+        sink: &mut dyn PathSink<F>,
+    ) -> Drawable<F>
+    where
+        F: Float + Trig + FromPrimitive + MulAssign + Display,
+    {
+        let options = self.options(options);
+        let mut rng = options.rng();
+        let rings: Vec<Vec<Point2D<F>>> = polygons
+            .iter()
+            .flat_map(|polygon| {
+                polygon
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, ring)| ring.len() > 2)
+                    .map(|(i, ring)| oriented(ring, i == 0))
+            })
+            .collect();
+        let outlines: Vec<Vec<Op<F>>> = rings
+            .iter()
+            .map(|ring| {
+                let mut outline = vec![];
+                linear_path_into(ring, true, &options, &mut rng, &mut outline);
+                outline
+            })
+            .collect();
+        if options.fill.is_some() && !rings.is_empty() {
+            fill_into(rings.clone(), &options, &mut rng, sink);
         }
         if options.stroke.is_some() {
-            paths.push(outline);
+            let closed = || rings.iter().map(|ring| polyline(ring, true)).collect();
+            if !dashes_into(closed, &options, &mut rng, sink) {
+                for outline in outlines.iter() {
+                    outline_into(outline, sink);
+                }
+            }
         }
-        self.d("polygon", &paths, &Some(options))
+        self.d(
+            ShapeKind::MultiPolygon { polygons: polygons.to_vec() },
+            options,
+        )
     }
 
     pub fn path<F>(&self, d: String, options: &Option<Options>) -> Drawable<F>
     where
         F: Float + Trig + FromPrimitive + MulAssign + Display,
     {
-        let mut options = options.clone().unwrap_or(self.default_options.clone());
-        let mut paths = vec![];
-        if d.is_empty() {
-            self.d("path", &paths, &Some(options))
-        } else {
+        collect(|sets| self.path_into(d, options, sets))
+    }
+
+    /// Like [`Generator::path`], but streams the sets into `sink`, see
+    /// [`Generator::draw_into`].
+    pub fn path_into<F>(
+        &self,
+        d: String,
+        options: &Option<Options>,
+        sink: &mut dyn PathSink<F>,
+    ) -> Drawable<F>
+    where
+        F: Float + Trig + FromPrimitive + MulAssign + Display,
+    {
+        let options = self.options(options);
+        let mut rng = options.rng();
+        let shape = ShapeKind::Path { d: d.clone() };
+        if !d.is_empty() {
             let simplified = options.simplification.map(|a| a < 1.0).unwrap_or(false);
             let distance = if simplified {
                 _c::<F>(4.0) - _c::<F>(4.0) * _c::<F>(options.simplification.unwrap())
@@ -398,24 +750,24 @@ impl Generator {
 
             let sets = points_on_path(d.clone(), Some(_c(1.0)), Some(distance));
             if options.fill.is_some() {
-                if options.fill_style == Some(FillStyle::Solid) {
-                    paths.push(solid_fill_polygon(&sets, &mut options));
-                } else {
-                    paths.push(pattern_fill_polygons(sets.clone(), &mut options));
-                }
+                fill_into(sets.clone(), &options, &mut rng, sink);
             }
 
-            if options.stroke.is_some() {
+            if options.stroke.is_some() && !dashes_into(|| sets.clone(), &options, &mut rng, sink) {
                 if simplified {
-                    sets.iter()
-                        .for_each(|s| paths.push(linear_path(s, false, &mut options)));
+                    for s in sets.iter() {
+                        set_into(sink, OpSetType::Path, |sink| {
+                            linear_path_into(s, false, &options, &mut rng, sink)
+                        });
+                    }
                 } else {
-                    paths.push(svg_path(d, &mut options));
+                    set_into(sink, OpSetType::Path, |sink| {
+                        svg_path_into(d, &options, &mut rng, sink)
+                    });
                 }
             }
-
-            self.d("path", &paths, &Some(options))
         }
+        self.d(shape, options)
     }
 
     pub fn path_from_segments<F>(
@@ -426,11 +778,24 @@ impl Generator {
     where
         F: Float + Trig + FromPrimitive + MulAssign + Display,
     {
-        let mut options = options.clone().unwrap_or(self.default_options.clone());
-        let mut paths = vec![];
-        if segments.is_empty() {
-            self.d("path", &paths, &Some(options))
-        } else {
+        collect(|sets| self.path_from_segments_into(segments, options, sets))
+    }
+
+    /// Like [`Generator::path_from_segments`], but streams the sets into `sink`, see
+    /// [`Generator::draw_into`].
+    pub fn path_from_segments_into<F>(
+        &self,
+        segments: Vec<PathSegment>,
+        options: &Option<Options>,
+        sink: &mut dyn PathSink<F>,
+    ) -> Drawable<F>
+    where
+        F: Float + Trig + FromPrimitive + MulAssign + Display,
+    {
+        let options = self.options(options);
+        let mut rng = options.rng();
+        let shape = ShapeKind::PathSegments { segments: segments.clone() };
+        if !segments.is_empty() {
             let simplified = options.simplification.map(|a| a < 1.0).unwrap_or(false);
             let distance = if simplified {
                 _c::<F>(4.0) - _c::<F>(4.0) * _c::<F>(options.simplification.unwrap())
@@ -440,62 +805,164 @@ impl Generator {
 
             let sets = points_on_segments(segments.clone(), Some(_c(1.0)), Some(distance));
             if options.fill.is_some() {
-                if options.fill_style == Some(FillStyle::Solid) {
-                    paths.push(solid_fill_polygon(&sets, &mut options));
-                } else {
-                    paths.push(pattern_fill_polygons(sets.clone(), &mut options));
-                }
+                fill_into(sets.clone(), &options, &mut rng, sink);
             }
 
-            if options.stroke.is_some() {
+            if options.stroke.is_some() && !dashes_into(|| sets.clone(), &options, &mut rng, sink) {
                 if simplified {
-                    sets.iter()
-                        .for_each(|s| paths.push(linear_path(s, false, &mut options)));
+                    for s in sets.iter() {
+                        set_into(sink, OpSetType::Path, |sink| {
+                            linear_path_into(s, false, &options, &mut rng, sink)
+                        });
+                    }
                 } else {
-                    paths.push(svg_segments(segments, &mut options));
+                    set_into(sink, OpSetType::Path, |sink| {
+                        svg_segments_into(segments, &options, &mut rng, sink)
+                    });
                 }
             }
+        }
+        self.d(shape, options)
+    }
+
+    /// Generates the primitive described by `shape`, as its own method would.
+    pub fn draw<F>(&self, shape: &ShapeKind<F>, options: &Option<Options>) -> Drawable<F>
+    where
+        F: Float + Trig + FromPrimitive + MulAssign + Display,
+    {
+        match shape {
+            ShapeKind::Line { start, end } => self.line(start.x, start.y, end.x, end.y, options),
+            ShapeKind::Rectangle { x, y, width, height } => {
+                self.rectangle(*x, *y, *width, *height, options)
+            }
+            ShapeKind::Ellipse { center, width, height } => {
+                self.ellipse(center.x, center.y, *width, *height, options)
+            }
+            ShapeKind::Circle { center, diameter } => {
+                self.circle(center.x, center.y, *diameter, options)
+            }
+            ShapeKind::LinearPath { points, close } => self.linear_path(points, *close, options),
+            ShapeKind::Arc { center, width, height, start, stop, mode } => self.arc(
+                center.x, center.y, *width, *height, *start, *stop, *mode, options,
+            ),
+            ShapeKind::BezierQuadratic { start, cp, end } => {
+                self.bezier_quadratic(*start, *cp, *end, options)
+            }
+            ShapeKind::BezierCubic { start, cp1, cp2, end } => {
+                self.bezier_cubic(*start, *cp1, *cp2, *end, options)
+            }
+            ShapeKind::Curve { points } => self.curve(points, options),
+            ShapeKind::Polygon { points } => self.polygon(points, options),
+            ShapeKind::PolygonWithHoles { outer, holes } => {
+                self.polygon_with_holes(outer, holes, options)
+            }
+            ShapeKind::MultiPolygon { polygons } => self.multi_polygon(polygons, options),
+            ShapeKind::Path { d } => self.path(d.clone(), options),
+            ShapeKind::PathSegments { segments } => {
+                self.path_from_segments(segments.clone(), options)
+            }
+        }
+    }
 
-            self.d("path", &paths, &Some(options))
+    /// Like [`Generator::draw`], but streams the sets into `sink` instead of collecting them,
+    /// so that backends can build their native paths without an intermediate op list. Each
+    /// set is drawn between [`PathSink::begin_set`] and [`PathSink::end_set`], in the order
+    /// [`Generator::draw`] would collect it. The returned drawable holds the shape and its
+    /// options, without any sets.
+    pub fn draw_into<F>(
+        &self,
+        shape: &ShapeKind<F>,
+        options: &Option<Options>,
+        sink: &mut dyn PathSink<F>,
+    ) -> Drawable<F>
+    where
+        F: Float + Trig + FromPrimitive + MulAssign + Display,
+    {
+        match shape {
+            ShapeKind::Line { start, end } => {
+                self.line_into(start.x, start.y, end.x, end.y, options, sink)
+            }
+            ShapeKind::Rectangle { x, y, width, height } => {
+                self.rectangle_into(*x, *y, *width, *height, options, sink)
+            }
+            ShapeKind::Ellipse { center, width, height } => {
+                self.ellipse_into(center.x, center.y, *width, *height, options, sink)
+            }
+            ShapeKind::Circle { center, diameter } => {
+                self.circle_into(center.x, center.y, *diameter, options, sink)
+            }
+            ShapeKind::LinearPath { points, close } => {
+                self.linear_path_into(points, *close, options, sink)
+            }
+            ShapeKind::Arc { center, width, height, start, stop, mode } => self.arc_into(
+                center.x, center.y, *width, *height, *start, *stop, *mode, options, sink,
+            ),
+            ShapeKind::BezierQuadratic { start, cp, end } => {
+                self.bezier_quadratic_into(*start, *cp, *end, options, sink)
+            }
+            ShapeKind::BezierCubic { start, cp1, cp2, end } => {
+                self.bezier_cubic_into(*start, *cp1, *cp2, *end, options, sink)
+            }
+            ShapeKind::Curve { points } => self.curve_into(points, options, sink),
+            ShapeKind::Polygon { points } => self.polygon_into(points, options, sink),
+            ShapeKind::PolygonWithHoles { outer, holes } => {
+                self.polygon_with_holes_into(outer, holes, options, sink)
+            }
+            ShapeKind::MultiPolygon { polygons } => {
+                self.multi_polygon_into(polygons, options, sink)
+            }
+            ShapeKind::Path { d } => self.path_into(d.clone(), options, sink),
+            ShapeKind::PathSegments { segments } => {
+                self.path_from_segments_into(segments.clone(), options, sink)
+            }
         }
     }
 
-    pub fn ops_to_path<F>(mut drawing: OpSet<F>, fixed_decimals: Option<u32>) -> String
+    pub fn ops_to_path<F>(drawing: OpSet<F>, fixed_decimals: Option<u32>) -> String
     where
         F: Float + FromPrimitive + Trig + Display,
     {
         let mut path = String::new();
-
-        for item in drawing.ops.iter_mut() {
-            if let Some(fd) = fixed_decimals {
-                let pow: u32 = 10u32.pow(fd);
-                item.data.iter_mut().for_each(|p| {
-                    *p = (*p * F::from(pow).unwrap()).round() / F::from(pow).unwrap();
-                });
+        let round = |v: F| match fixed_decimals {
+            Some(fd) => {
+                let pow = F::from(10u32.pow(fd)).unwrap();
+                (v * pow).round() / pow
             }
+            None => v,
+        };
+        let r = |p: Point2D<F>| Point2D::new(round(p.x), round(p.y));
 
-            match item.op {
-                OpType::Move => {
-                    write!(&mut path, "L{} {} ", item.data[0], item.data[1])
-                        .expect("Failed to write path string");
+        for item in drawing.ops.iter() {
+            match *item {
+                Op::Move(to) => {
+                    let to = r(to);
+                    write!(&mut path, "M{} {} ", to.x, to.y).expect("Failed to write path string");
                 }
-                OpType::BCurveTo => {
+                Op::BCurveTo { cp1, cp2, to } => {
+                    let (cp1, cp2, to) = (r(cp1), r(cp2), r(to));
                     write!(
                         &mut path,
                         "C{} {}, {} {}, {} {} ",
-                        item.data[0],
-                        item.data[1],
-                        item.data[2],
-                        item.data[3],
-                        item.data[4],
-                        item.data[5]
+                        cp1.x, cp1.y, cp2.x, cp2.y, to.x, to.y
                     )
                     .expect("Failed to write path string");
                 }
-                OpType::LineTo => {
-                    write!(&mut path, "L{} {}, ", item.data[0], item.data[1])
+                Op::LineTo(to) => {
+                    let to = r(to);
+                    write!(&mut path, "L{} {}, ", to.x, to.y).expect("Failed to write path string");
+                }
+                Op::QuadTo { cp, to } => {
+                    let (cp, to) = (r(cp), r(to));
+                    write!(&mut path, "Q{} {}, {} {} ", cp.x, cp.y, to.x, to.y)
                         .expect("Failed to write path string");
                 }
+                Op::Close => path.push_str("Z "),
+                Op::Ellipse { center, radii } => path.push_str(&ellipse_path(
+                    round(center.x),
+                    round(center.y),
+                    round(radii.x),
+                    round(radii.y),
+                )),
             }
         }
 
@@ -521,17 +988,24 @@ impl Generator {
                     d: Self::ops_to_path(drawing.clone(), None),
                     stroke: None,
                     stroke_width: Some(0.0f32),
-                    fill: o.fill,
+                    fill: drawing
+                        .style
+                        .as_ref()
+                        .and_then(|style| style.color)
+                        .or(o.fill),
                 },
                 OpSetType::FillSketch => {
-                    let fill_weight = if o.fill_weight.unwrap_or(0.0) < 0.0 {
+                    let style = drawing.style.clone().unwrap_or_default();
+                    let fill_weight = if let Some(weight) = style.weight {
+                        weight
+                    } else if o.fill_weight.unwrap_or(0.0) < 0.0 {
                         o.stroke_width.unwrap_or(0.0) / 2.0
                     } else {
                         o.fill_weight.unwrap_or(0.0)
                     };
                     PathInfo {
                         d: Self::ops_to_path(drawing.clone(), None),
-                        stroke: o.fill,
+                        stroke: style.color.or(o.fill),
                         stroke_width: Some(fill_weight),
                         fill: None,
                     }
@@ -542,3 +1016,438 @@ impl Generator {
         path_infos
     }
 }
+
+/// Drawable drawn by `draw`, with the sets it streams collected.
+fn collect<F>(draw: impl FnOnce(&mut Vec<OpSet<F>>) -> Drawable<F>) -> Drawable<F>
+where
+    F: Float + Trig + FromPrimitive,
+{
+    let mut sets = vec![];
+    let drawable = draw(&mut sets);
+    Drawable { sets, ..drawable }
+}
+
+/// Draws a set of `op_set_type` into `sink`, without a style of its own.
+fn set_into<F>(
+    sink: &mut dyn PathSink<F>,
+    op_set_type: OpSetType,
+    draw: impl FnOnce(&mut dyn PathSink<F>),
+) where
+    F: Float + Trig,
+{
+    sink.begin_set(&op_set_type, None);
+    draw(sink);
+    sink.end_set();
+}
+
+/// Replays the buffered `outline` of a shape into `sink` as its stroke set.
+fn outline_into<F: Float + Trig>(outline: &[Op<F>], sink: &mut dyn PathSink<F>) {
+    set_into(sink, OpSetType::Path, |sink| stream_ops(outline, sink));
+}
+
+/// Fills the `polygon_list` as the fill style of the options asks for.
+fn fill_into<F, R>(
+    polygon_list: Vec<Vec<Point2D<F>>>,
+    options: &Options,
+    rng: &mut R,
+    sink: &mut dyn PathSink<F>,
+) where
+    F: Float + Trig + FromPrimitive,
+    R: Rng + Clone,
+{
+    if options.fill_style == Some(FillStyle::Solid) {
+        set_into(sink, OpSetType::FillPath, |sink| {
+            solid_fill_polygon_into(&polygon_list, options, rng, sink)
+        });
+    } else {
+        pattern_fill_polygon_sets_into(polygon_list, options, rng, sink);
+    }
+}
+
+/// Draws rough dashes or dots along the `polylines` traced by a shape as its stroke set, if
+/// its stroke style asks for them, and tells whether it did. Backends skip their own dash
+/// pattern for these, see [`Options::native_stroke_line_dash`].
+fn dashes_into<F, R>(
+    polylines: impl FnOnce() -> Vec<Vec<Point2D<F>>>,
+    options: &Options,
+    rng: &mut R,
+    sink: &mut dyn PathSink<F>,
+) -> bool
+where
+    F: Float + Trig + FromPrimitive,
+    R: Rng + Clone,
+{
+    match options.stroke_style.unwrap_or_default() {
+        StrokeStyle::Solid => false,
+        StrokeStyle::Dashed | StrokeStyle::Dotted => {
+            let polylines = polylines();
+            set_into(sink, OpSetType::Path, |sink| {
+                dashed_polylines_into(&polylines, options, rng, sink)
+            });
+            true
+        }
+    }
+}
+
+/// Bezier curve through `points`, closed and parameterized as set in the options.
+fn curve_bezier<F: Float>(points: &[Point2D<F>], options: &Options) -> Option<Vec<Point2D<F>>> {
+    let closed = options.curve_closed.unwrap_or(false);
+    match options.curve_parameterization.unwrap_or_default() {
+        CurveParameterization::Uniform if !closed => curve_to_bezier(points, F::zero()),
+        parameterization => catmull_rom_to_bezier(points, F::zero(), closed, parameterization),
+    }
+}
+
+/// The `ring` turned to a positive signed area if it is an `outer` ring, else to a negative
+/// one, so that holes wind against the rings around them.
+fn oriented<F: Float + Trig>(ring: &[Point2D<F>], outer: bool) -> Vec<Point2D<F>> {
+    let area = ring
+        .iter()
+        .zip(ring.iter().cycle().skip(1))
+        .fold(F::zero(), |area, (a, b)| {
+            area + a.to_vector().cross(b.to_vector())
+        });
+    let mut ring = ring.to_vec();
+    if (area < F::zero()) == outer {
+        ring.reverse();
+    }
+    ring
+}
+
+fn polyline<F: Float + Trig>(points: &[Point2D<F>], close: bool) -> Vec<Point2D<F>> {
+    let mut polyline = points.to_vec();
+    if close && points.len() > 2 {
+        polyline.push(points[0]);
+    }
+    polyline
+}
+
+/// Points along the ellipse around `center` from the angle `start` to `stop`, about two
+/// units apart.
+fn ellipse_points<F>(center: Point2D<F>, width: F, height: F, start: F, stop: F) -> Vec<Point2D<F>>
+where
+    F: Float + Trig + FromPrimitive,
+{
+    let (rx, ry) = (width.abs() / _c(2.0), height.abs() / _c(2.0));
+    let (start, stop) = normalize_arc_angles(start, stop);
+    let length = (stop - start) * (rx + ry) / _c(2.0);
+    let steps = (length / _c(2.0))
+        .ceil()
+        .to_usize()
+        .unwrap_or(0)
+        .clamp(8, 2048);
+    (0..=steps)
+        .map(|i| {
+            let angle = start + (stop - start) * F::from(i).unwrap() / F::from(steps).unwrap();
+            Point2D::new(
+                center.x + rx * Float::cos(angle),
+                center.y + ry * Float::sin(angle),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use euclid::{point2, vec2};
+    use palette::Srgba;
+
+    use super::Generator;
+    use crate::core::{
+        ArcMode,
+        FillRule,
+        FillStyle,
+        Op,
+        OpSet,
+        OpSetType,
+        Options,
+        OptionsBuilder,
+        ShapeKind,
+        StrokeStyle,
+    };
+
+    #[test]
+    fn ops_to_path() {
+        let ops = vec![
+            Op::Move(point2(0.0f32, 0.0)),
+            Op::QuadTo { cp: point2(5.0, 5.0), to: point2(10.0, 0.0) },
+            Op::Close,
+            Op::Ellipse { center: point2(20.0, 10.0), radii: vec2(2.0, 1.0) },
+        ];
+        let set = OpSet {
+            op_set_type: OpSetType::Path,
+            ops,
+            size: None,
+            path: None,
+            style: None,
+        };
+        assert_eq!(
+            Generator::ops_to_path(set, None),
+            "M0 0 Q5 5, 10 0 Z M22 10 A2 1 0 1 0 18 10 A2 1 0 1 0 22 10 Z "
+        );
+    }
+
+    #[test]
+    fn zero_sweep_arc() {
+        let options = OptionsBuilder::default()
+            .roughness(0.0)
+            .fill(Srgba::new(1.0, 0.0, 0.0, 1.0))
+            .build()
+            .unwrap();
+        let generator = Generator::default();
+        for fill_style in [FillStyle::Solid, FillStyle::Hachure] {
+            let options = Some(Options { fill_style: Some(fill_style), ..options.clone() });
+            let pie = generator.arc(0.0f32, 0.0, 50.0, 50.0, 1.0, 1.0, ArcMode::Pie, &options);
+            assert_eq!(pie.sets.len(), 1);
+            assert_eq!(pie.sets[0].op_set_type, OpSetType::Path);
+        }
+    }
+
+    #[test]
+    fn solid_arc_keeps_stroke_options() {
+        let options = OptionsBuilder::default()
+            .fill(Srgba::new(1.0, 0.0, 0.0, 1.0))
+            .fill_style(FillStyle::Solid)
+            .stroke_passes(3_u32)
+            .build()
+            .unwrap();
+        let pie = Generator::default().arc(
+            0.0f32,
+            0.0,
+            50.0,
+            50.0,
+            0.0,
+            1.0,
+            ArcMode::Pie,
+            &Some(options),
+        );
+        assert_eq!(pie.sets[0].op_set_type, OpSetType::FillPath);
+        assert_eq!(pie.options.stroke_passes, Some(3));
+        assert_eq!(pie.options.disable_multi_stroke, Some(false));
+    }
+
+    #[test]
+    fn default_options() {
+        let options = OptionsBuilder::default().roughness(0.0).build().unwrap();
+        let generator = Generator::new(options.clone());
+        let drawable = generator.line(0.0f32, 0.0, 10.0, 0.0, &None);
+        assert_eq!(drawable.options.roughness, Some(0.0));
+        assert_eq!(
+            drawable.sets,
+            Generator::default()
+                .line(0.0, 0.0, 10.0, 0.0, &Some(options))
+                .sets
+        );
+    }
+
+    #[test]
+    fn shared_across_threads() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Generator>();
+
+        let generator = Generator::default();
+        let options = Some(OptionsBuilder::default().seed(7_u64).build().unwrap());
+        let here = generator.circle(10.0, 10.0, 15.0, &options);
+        let there = std::thread::scope(|scope| {
+            scope
+                .spawn(|| generator.circle(10.0, 10.0, 15.0, &options))
+                .join()
+                .unwrap()
+        });
+        assert_eq!(here.sets, there.sets);
+    }
+
+    #[test]
+    fn dots_are_native_ellipses() {
+        let options = OptionsBuilder::default()
+            .fill(Srgba::new(1.0, 0.0, 0.0, 1.0))
+            .fill_style(FillStyle::Dots)
+            .fill_weight(2.0)
+            .build()
+            .unwrap();
+        let drawable = Generator::default().rectangle(0.0f32, 0.0, 50.0, 50.0, &Some(options));
+        let dots = &drawable
+            .sets
+            .iter()
+            .find(|set| set.op_set_type == OpSetType::FillSketch)
+            .unwrap()
+            .ops;
+        assert!(!dots.is_empty());
+        assert!(dots
+            .iter()
+            .all(|op| matches!(op, Op::Ellipse { radii, .. } if *radii == vec2(1.0, 1.0))));
+    }
+
+    #[test]
+    fn native_stroke_dashes() {
+        let options = OptionsBuilder::default()
+            .stroke_line_dash(vec![6.0, 3.0])
+            .build()
+            .unwrap();
+        let generator = Generator::default();
+        let solid = generator.rectangle(0.0f32, 0.0, 50.0, 50.0, &Some(options.clone()));
+        assert_eq!(
+            solid.options.native_stroke_line_dash(),
+            Some(&[6.0, 3.0][..])
+        );
+        for stroke_style in [StrokeStyle::Dashed, StrokeStyle::Dotted] {
+            let options = Options {
+                stroke_style: Some(stroke_style),
+                ..options.clone()
+            };
+            // the rough dashes are stroked solid, even by drawables that kept the pattern
+            assert_eq!(options.native_stroke_line_dash(), None);
+            let drawable = generator.rectangle(0.0f32, 0.0, 50.0, 50.0, &Some(options));
+            assert_eq!(drawable.options.native_stroke_line_dash(), None);
+            // the pattern itself stays, as the length of the rough dashes
+            assert_eq!(drawable.options.stroke_line_dash, Some(vec![6.0, 3.0]));
+            assert!(drawable
+                .sets
+                .iter()
+                .any(|set| set.op_set_type == OpSetType::Path && !set.ops.is_empty()));
+        }
+    }
+
+    #[test]
+    fn draw_into_streams_the_sets() {
+        let generator = Generator::default();
+        let shapes = [
+            ShapeKind::Line {
+                start: point2(0.0f32, 0.0),
+                end: point2(50.0, 20.0),
+            },
+            ShapeKind::Ellipse {
+                center: point2(30.0, 30.0),
+                width: 40.0,
+                height: 20.0,
+            },
+            ShapeKind::Arc {
+                center: point2(30.0, 30.0),
+                width: 40.0,
+                height: 40.0,
+                start: 0.0,
+                stop: 2.0,
+                mode: ArcMode::Pie,
+            },
+            ShapeKind::Path { d: "M0 0 Q20 40 40 0 Z".into() },
+        ];
+        for fill_style in [FillStyle::Solid, FillStyle::Hachure, FillStyle::Watercolor] {
+            for stroke_style in [StrokeStyle::Solid, StrokeStyle::Dashed] {
+                let options = Some(
+                    OptionsBuilder::default()
+                        .fill(Srgba::new(1.0, 0.0, 0.0, 1.0))
+                        .fill_style(fill_style)
+                        .stroke_style(stroke_style)
+                        .build()
+                        .unwrap(),
+                );
+                for shape in shapes.iter() {
+                    let drawable = generator.draw(shape, &options);
+                    let mut ops: Vec<Op<f32>> = vec![];
+                    let streamed = generator.draw_into(shape, &options, &mut ops);
+                    assert!(streamed.sets.is_empty());
+                    assert_eq!(streamed.shape, drawable.shape);
+                    let expected: Vec<Op<f32>> = drawable
+                        .sets
+                        .iter()
+                        .flat_map(|set| set.ops.clone())
+                        .collect();
+                    assert_eq!(ops, expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn shape_kinds() {
+        let generator = Generator::default();
+        assert_eq!(
+            generator.circle(10.0f32, 20.0, 30.0, &None).shape,
+            ShapeKind::Circle { center: point2(10.0, 20.0), diameter: 30.0 }
+        );
+        assert_eq!(
+            generator.rectangle(1.0f32, 2.0, 3.0, 4.0, &None).shape,
+            ShapeKind::Rectangle { x: 1.0, y: 2.0, width: 3.0, height: 4.0 }
+        );
+        let cubic = generator.bezier_cubic(
+            point2(0.0f32, 0.0),
+            point2(1.0, 2.0),
+            point2(3.0, 2.0),
+            point2(4.0, 0.0),
+            &None,
+        );
+        assert!(matches!(cubic.shape, ShapeKind::BezierCubic { .. }));
+        let path = generator.path::<f32>("M0 0 L10 0 L10 10 Z".into(), &None);
+        assert_eq!(
+            path.shape,
+            ShapeKind::Path { d: "M0 0 L10 0 L10 10 Z".into() }
+        );
+
+        let options = OptionsBuilder::default()
+            .fill_rule(FillRule::NonZero)
+            .build()
+            .unwrap();
+        assert_eq!(cubic.shape.fill_rule(&cubic.options), FillRule::EvenOdd);
+        assert_eq!(cubic.shape.fill_rule(&options), FillRule::NonZero);
+        let ellipse = generator.ellipse(0.0f32, 0.0, 10.0, 10.0, &None);
+        assert_eq!(ellipse.shape.fill_rule(&ellipse.options), FillRule::NonZero);
+        let even_odd = OptionsBuilder::default()
+            .fill_rule(FillRule::EvenOdd)
+            .build()
+            .unwrap();
+        assert_eq!(ellipse.shape.fill_rule(&even_odd), FillRule::EvenOdd);
+        let holes = generator.polygon_with_holes(
+            &[point2(0.0f32, 0.0), point2(10.0, 0.0), point2(10.0, 10.0)],
+            &[],
+            &None,
+        );
+        assert_eq!(holes.shape.fill_rule(&options), FillRule::NonZero);
+        assert_eq!(holes.shape.fill_rule(&holes.options), FillRule::EvenOdd);
+    }
+
+    #[test]
+    fn polygon_with_holes() {
+        let outer = [
+            point2(0.0f32, 0.0),
+            point2(100.0, 0.0),
+            point2(100.0, 100.0),
+            point2(0.0, 100.0),
+        ];
+        // the hole winds the same way as the outer ring
+        let holes = [vec![
+            point2(30.0, 30.0),
+            point2(70.0, 30.0),
+            point2(70.0, 70.0),
+            point2(30.0, 70.0),
+        ]];
+        for fill_rule in [FillRule::EvenOdd, FillRule::NonZero] {
+            let options = OptionsBuilder::default()
+                .fill(Srgba::new(1.0, 0.0, 0.0, 1.0))
+                .fill_style(FillStyle::Hachure)
+                .fill_rule(fill_rule)
+                .roughness(0.0)
+                .build()
+                .unwrap();
+            let drawable = Generator::default().polygon_with_holes(&outer, &holes, &Some(options));
+
+            let outlines = drawable
+                .sets
+                .iter()
+                .filter(|set| set.op_set_type == OpSetType::Path)
+                .count();
+            assert_eq!(outlines, 2);
+            let fill = drawable
+                .sets
+                .iter()
+                .find(|set| set.op_set_type == OpSetType::FillSketch)
+                .unwrap();
+            assert!(!fill.ops.is_empty());
+            // the scan lines may end up to a gap past the edges they cross
+            for op in fill.ops.iter() {
+                for p in op.points() {
+                    assert!(!(p.x > 34.0 && p.x < 66.0 && p.y > 34.0 && p.y < 66.0));
+                }
+            }
+        }
+    }
+}
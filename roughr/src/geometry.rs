@@ -1,8 +1,10 @@
+use std::cmp::Ordering;
+
 use euclid::default::Point2D;
 use euclid::{Angle, Translation2D, Trig, Vector2D};
 use num_traits::{Float, FromPrimitive};
 
-use crate::core::_c;
+use crate::core::{FillRule, _c};
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct Line<F: Float + Trig> {
@@ -102,12 +104,243 @@ pub fn convert_bezier_quadratic_to_cubic<F: Float + FromPrimitive + Trig>(
     }
 }
 
+/// Checks if `point` lies inside the area covered by `polygon_list`, deciding
+/// overlapping and nested polygons with the given fill rule.
+pub fn point_in_polygons<F: Float + Trig>(
+    point: Point2D<F>,
+    polygon_list: &[Vec<Point2D<F>>],
+    fill_rule: FillRule,
+) -> bool {
+    let mut winding = 0;
+    for polygon in polygon_list.iter().filter(|p| p.len() > 2) {
+        let len = polygon.len();
+        for i in 0..len {
+            let a = polygon[i];
+            let b = polygon[(i + 1) % len];
+            let side = (b.x - a.x) * (point.y - a.y) - (point.x - a.x) * (b.y - a.y);
+            if a.y <= point.y {
+                if b.y > point.y && side > F::zero() {
+                    winding += 1;
+                }
+            } else if b.y <= point.y && side < F::zero() {
+                winding -= 1;
+            }
+        }
+    }
+    match fill_rule {
+        FillRule::EvenOdd => winding % 2 != 0,
+        FillRule::NonZero => winding != 0,
+    }
+}
+
+/// Finds where segment `a`-`b` crosses segment `c`-`d`, returned as the
+/// interpolation factor along `a`-`b`.
+fn segment_intersection<F: Float + Trig>(
+    a: Point2D<F>,
+    b: Point2D<F>,
+    c: Point2D<F>,
+    d: Point2D<F>,
+) -> Option<F> {
+    let r = b - a;
+    let s = d - c;
+    let denominator = r.cross(s);
+    if denominator == F::zero() {
+        return None;
+    }
+    let t = (c - a).cross(s) / denominator;
+    let u = (c - a).cross(r) / denominator;
+    if t > F::zero() && t < F::one() && u >= F::zero() && u <= F::one() {
+        Some(t)
+    } else {
+        None
+    }
+}
+
+/// Cuts a polyline at the boundaries of `polygon_list` and returns the
+/// pieces which lie inside the fill area.
+pub fn clip_polyline<F: Float + Trig + FromPrimitive>(
+    points: &[Point2D<F>],
+    polygon_list: &[Vec<Point2D<F>>],
+    fill_rule: FillRule,
+) -> Vec<Vec<Point2D<F>>> {
+    let mut runs: Vec<Vec<Point2D<F>>> = vec![];
+    let mut current: Vec<Point2D<F>> = vec![];
+    for segment in points.windows(2) {
+        let (a, b) = (segment[0], segment[1]);
+        let mut cuts = vec![F::zero(), F::one()];
+        for polygon in polygon_list.iter().filter(|p| p.len() > 2) {
+            let len = polygon.len();
+            for i in 0..len {
+                if let Some(t) = segment_intersection(a, b, polygon[i], polygon[(i + 1) % len]) {
+                    cuts.push(t);
+                }
+            }
+        }
+        cuts.sort_by(|t1, t2| t1.partial_cmp(t2).unwrap_or(Ordering::Equal));
+
+        for range in cuts.windows(2) {
+            let (t0, t1) = (range[0], range[1]);
+            if t1 - t0 <= F::epsilon() {
+                continue;
+            }
+            let middle = a.lerp(b, (t0 + t1) / _c(2.0));
+            if point_in_polygons(middle, polygon_list, fill_rule) {
+                let start = a.lerp(b, t0);
+                if current.last() != Some(&start) {
+                    if current.len() > 1 {
+                        runs.push(std::mem::take(&mut current));
+                    }
+                    current = vec![start];
+                }
+                current.push(a.lerp(b, t1));
+            } else if !current.is_empty() {
+                if current.len() > 1 {
+                    runs.push(std::mem::take(&mut current));
+                }
+                current.clear();
+            }
+        }
+    }
+    if current.len() > 1 {
+        runs.push(current);
+    }
+    runs
+}
+
+/// Moves the edges of the closed `polygon` outwards by `distance`, or inwards when it is
+/// negative. The corners move along their bisectors, by at most 4 times the distance.
+pub fn offset_polygon<F: Float + Trig + FromPrimitive>(
+    polygon: &[Point2D<F>],
+    distance: F,
+) -> Vec<Point2D<F>> {
+    let mut points = polygon.to_vec();
+    points.dedup();
+    while points.len() > 1 && points.first() == points.last() {
+        points.pop();
+    }
+    let len = points.len();
+    if len < 3 {
+        return points;
+    }
+
+    let mut area = F::zero();
+    for i in 0..len {
+        area = area
+            + points[i]
+                .to_vector()
+                .cross(points[(i + 1) % len].to_vector());
+    }
+    let outwards = |a: Point2D<F>, b: Point2D<F>| {
+        let edge = (b - a).normalize();
+        let normal = Vector2D::new(edge.y, -edge.x);
+        if area < F::zero() {
+            -normal
+        } else {
+            normal
+        }
+    };
+    (0..len)
+        .map(|i| {
+            let previous = outwards(points[(i + len - 1) % len], points[i]);
+            let next = outwards(points[i], points[(i + 1) % len]);
+            let sum = previous + next;
+            let bisector = if sum.length() > F::epsilon() {
+                sum.normalize()
+            } else {
+                previous
+            };
+            points[i] + bisector * (distance / bisector.dot(previous).max(_c(0.25)))
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use euclid::default::Point2D;
+    use euclid::point2;
+
+    use crate::core::FillRule;
     #[test]
     fn line_length() {
         let l = super::Line::from(&[Point2D::new(1.0, 1.0), Point2D::new(2.0, 2.0)]);
         assert_eq!(l.length(), f32::sqrt(2.0));
     }
+
+    #[test]
+    fn point_in_polygons() {
+        let outer = vec![
+            point2(0.0, 0.0),
+            point2(10.0, 0.0),
+            point2(10.0, 10.0),
+            point2(0.0, 10.0),
+        ];
+        let hole = vec![
+            point2(3.0, 3.0),
+            point2(7.0, 3.0),
+            point2(7.0, 7.0),
+            point2(3.0, 7.0),
+        ];
+        let polygons = vec![outer, hole];
+        let in_ring = point2(1.0, 1.0);
+        let in_hole = point2(5.0, 5.0);
+        let outside = point2(11.0, 5.0);
+
+        assert!(super::point_in_polygons(in_ring, &polygons, FillRule::EvenOdd));
+        assert!(!super::point_in_polygons(in_hole, &polygons, FillRule::EvenOdd));
+        assert!(!super::point_in_polygons(outside, &polygons, FillRule::EvenOdd));
+        // both rings share the same orientation so the hole is covered twice
+        assert!(super::point_in_polygons(in_hole, &polygons, FillRule::NonZero));
+        assert!(!super::point_in_polygons(outside, &polygons, FillRule::NonZero));
+    }
+
+    #[test]
+    fn offset_polygon() {
+        let square: [Point2D<f64>; 5] = [
+            point2(0.0, 0.0),
+            point2(10.0, 0.0),
+            point2(10.0, 10.0),
+            point2(0.0, 10.0),
+            point2(0.0, 0.0),
+        ];
+        let expected = [
+            point2(1.0, 1.0),
+            point2(9.0, 1.0),
+            point2(9.0, 9.0),
+            point2(1.0, 9.0),
+        ];
+        let inset = super::offset_polygon(&square, -1.0);
+        for (p, e) in inset.iter().zip(expected.iter()) {
+            assert!((*p - *e).length() < 1e-9);
+        }
+        assert_eq!(inset.len(), 4);
+
+        // the direction does not depend on the orientation of the polygon
+        let reversed: Vec<_> = square.iter().rev().copied().collect();
+        let outset = super::offset_polygon(&reversed, 1.0);
+        assert!(outset.iter().all(|p| p.x.abs() > 0.9 && (p.x - 10.0).abs() > 0.9));
+    }
+
+    #[test]
+    fn clip_polyline() {
+        let square = vec![vec![
+            point2(0.0, 0.0),
+            point2(10.0, 0.0),
+            point2(10.0, 10.0),
+            point2(0.0, 10.0),
+        ]];
+        let zig_zag = [
+            point2(-5.0, 5.0),
+            point2(5.0, 5.0),
+            point2(5.0, 15.0),
+            point2(8.0, 5.0),
+        ];
+        let runs = super::clip_polyline(&zig_zag, &square, FillRule::EvenOdd);
+        assert_eq!(
+            runs,
+            vec![
+                vec![point2(0.0, 5.0), point2(5.0, 5.0), point2(5.0, 10.0)],
+                vec![point2(6.5, 10.0), point2(8.0, 5.0)],
+            ]
+        );
+    }
 }
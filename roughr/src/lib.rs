@@ -25,6 +25,18 @@
 //! roughr = "0.1"
 //! ```
 //!
+//! Enable the `raster` feature to sketch PNG images with `Generator::sketch_image`.
+//!
+//! Enable the `rayon` feature to generate large batches of shapes in parallel with
+//! `Generator::par_generate_batch`.
+//!
+//! Applications redrawing the same shapes every frame can keep them in a
+//! `cache::DrawableCache`, which only generates shapes it has not seen yet.
+//!
+//! Named presets such as `style::Preset::Pencil` or `style::Preset::Blueprint` set up an
+//! `OptionsBuilder` with a rough look and the colors of a `style::Theme`, which further
+//! setters override.
+//!
 //! ## 🔧 Example
 //!
 //! ### Rectangle
@@ -149,6 +161,16 @@
 //! - [x] Dots
 //! - [x] Dashed
 //! - [x] Zigzag-Line
+//! - [x] Stipple
+//! - [x] Wavy
+//! - [x] Scribble
+//! - [x] HatchLayers
+//! - [x] ToneHatch
+//! - [x] Motif
+//! - [x] Flow
+//! - [x] Hilbert
+//! - [x] Serpentine
+//! - [x] Watercolor
 //!
 //! ## 🔭 Examples
 //!
@@ -158,12 +180,18 @@
 #[macro_use]
 extern crate derive_builder;
 
+pub mod batch;
+pub mod cache;
 pub mod core;
 pub mod filler;
 pub mod generator;
 pub mod geometry;
 pub mod points_on_path;
+#[cfg(feature = "raster")]
+pub mod raster;
 pub mod renderer;
+pub mod sink;
+pub mod style;
 
 pub use euclid::Point2D;
 pub use palette::Srgba;
@@ -0,0 +1,370 @@
+//! Turns raster images into rough sketches.
+//!
+//! An image is reduced to a [`LuminanceMap`], whose darkness then drives tone mapped
+//! hatching, stippling and contour strokes made with the regular [`Generator`] shapes.
+//! The result is a list of plain [`Drawable`]s, so it renders on every backend.
+
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::io::Read;
+use std::ops::MulAssign;
+use std::sync::Arc;
+
+use euclid::default::Point2D;
+use euclid::Trig;
+use num_traits::{Float, FromPrimitive};
+
+use crate::core::{Drawable, FillStyle, Options, ToneMap, _c};
+use crate::generator::Generator;
+
+#[derive(Debug)]
+pub enum RasterError {
+    Decoding(png::DecodingError),
+}
+
+impl Display for RasterError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RasterError::Decoding(e) => write!(f, "failed to decode image: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for RasterError {}
+
+impl From<png::DecodingError> for RasterError {
+    fn from(e: png::DecodingError) -> Self {
+        RasterError::Decoding(e)
+    }
+}
+
+/// Grayscale image with luminance values from `0.0`, black, to `1.0`, white.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LuminanceMap {
+    width: usize,
+    height: usize,
+    values: Vec<f32>,
+}
+
+/// Identifies the grid edge from `(x, y)` to `(x + 1, y)` when the last value is `0`, or to
+/// `(x, y + 1)` when it is `1`.
+type GridEdge = (usize, usize, u8);
+
+impl LuminanceMap {
+    /// Creates a map from luminance values stored row by row.
+    pub fn new(width: usize, height: usize, values: Vec<f32>) -> Self {
+        assert_eq!(
+            values.len(),
+            width * height,
+            "luminance values do not match the image size"
+        );
+        LuminanceMap { width, height, values }
+    }
+
+    /// Decodes a PNG image. Transparent pixels are composited over white.
+    pub fn from_png<R: Read>(reader: R) -> Result<Self, RasterError> {
+        let mut decoder = png::Decoder::new(reader);
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info()?;
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buffer)?;
+        let channels = info.color_type.samples();
+
+        let values = buffer[..info.buffer_size()]
+            .chunks_exact(channels)
+            .map(|pixel| {
+                let channel = |i: usize| pixel[i] as f32 / 255.0;
+                let (luminance, alpha) = match channels {
+                    1 => (channel(0), 1.0),
+                    2 => (channel(0), channel(1)),
+                    3 => (
+                        0.2126 * channel(0) + 0.7152 * channel(1) + 0.0722 * channel(2),
+                        1.0,
+                    ),
+                    _ => (
+                        0.2126 * channel(0) + 0.7152 * channel(1) + 0.0722 * channel(2),
+                        channel(3),
+                    ),
+                };
+                luminance * alpha + (1.0 - alpha)
+            })
+            .collect();
+        Ok(LuminanceMap::new(
+            info.width as usize,
+            info.height as usize,
+            values,
+        ))
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Luminance at a position in pixels, interpolated between the pixel centers.
+    pub fn luminance(&self, x: f32, y: f32) -> f32 {
+        if self.width == 0 || self.height == 0 {
+            return 1.0;
+        }
+        let u = (x - 0.5).clamp(0.0, (self.width - 1) as f32);
+        let v = (y - 0.5).clamp(0.0, (self.height - 1) as f32);
+        let (x0, y0) = (u.floor() as usize, v.floor() as usize);
+        let (x1, y1) = ((x0 + 1).min(self.width - 1), (y0 + 1).min(self.height - 1));
+        let (fx, fy) = (u - x0 as f32, v - y0 as f32);
+        let at = |x: usize, y: usize| self.values[y * self.width + x];
+        let top = at(x0, y0) + (at(x1, y0) - at(x0, y0)) * fx;
+        let bottom = at(x0, y1) + (at(x1, y1) - at(x0, y1)) * fx;
+        top + (bottom - top) * fy
+    }
+
+    pub fn darkness(&self, x: f32, y: f32) -> f32 {
+        1.0 - self.luminance(x, y)
+    }
+
+    /// Traces the lines where the luminance crosses `level` with marching squares over a
+    /// grid of `step` pixels. Closed contours repeat their first point at the end.
+    pub fn contours(&self, level: f32, step: f32) -> Vec<Vec<Point2D<f32>>> {
+        let step = step.max(0.5);
+        let columns = (self.width as f32 / step).ceil() as usize;
+        let rows = (self.height as f32 / step).ceil() as usize;
+        if columns == 0 || rows == 0 {
+            return vec![];
+        }
+        let value = |x: usize, y: usize| self.luminance(x as f32 * step, y as f32 * step);
+        let crossing = |(x, y, direction): GridEdge| {
+            let (x1, y1) = if direction == 0 {
+                (x + 1, y)
+            } else {
+                (x, y + 1)
+            };
+            let (v0, v1) = (value(x, y), value(x1, y1));
+            let t = if v1 != v0 {
+                ((level - v0) / (v1 - v0)).clamp(0.0, 1.0)
+            } else {
+                0.5
+            };
+            Point2D::new(
+                (x as f32 + (x1 as f32 - x as f32) * t) * step,
+                (y as f32 + (y1 as f32 - y as f32) * t) * step,
+            )
+        };
+
+        let mut segments: Vec<(GridEdge, GridEdge)> = vec![];
+        for y in 0..rows {
+            for x in 0..columns {
+                let inside = |x: usize, y: usize| value(x, y) < level;
+                let (a, b, c, d) = (
+                    inside(x, y),
+                    inside(x + 1, y),
+                    inside(x + 1, y + 1),
+                    inside(x, y + 1),
+                );
+                let top = (x, y, 0);
+                let right = (x + 1, y, 1);
+                let bottom = (x, y + 1, 0);
+                let left = (x, y, 1);
+                let cut: Vec<GridEdge> = [
+                    (a != b, top),
+                    (b != c, right),
+                    (c != d, bottom),
+                    (d != a, left),
+                ]
+                .iter()
+                .filter(|(crossed, _)| *crossed)
+                .map(|(_, edge)| *edge)
+                .collect();
+                match cut.len() {
+                    2 => segments.push((cut[0], cut[1])),
+                    4 => {
+                        // Saddle, decided by the value at the center of the cell.
+                        let center = self
+                            .luminance((x as f32 + 0.5) * step, (y as f32 + 0.5) * step)
+                            < level;
+                        if a == center {
+                            segments.push((top, right));
+                            segments.push((bottom, left));
+                        } else {
+                            segments.push((left, top));
+                            segments.push((right, bottom));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let mut adjacency: HashMap<GridEdge, Vec<usize>> = HashMap::new();
+        for (i, (e0, e1)) in segments.iter().enumerate() {
+            adjacency.entry(*e0).or_default().push(i);
+            adjacency.entry(*e1).or_default().push(i);
+        }
+        // Open contours are walked from one of their ends, the remaining ones are loops.
+        let open_ends = segments
+            .iter()
+            .flat_map(|(e0, e1)| [*e0, *e1])
+            .filter(|e| adjacency[e].len() == 1);
+        let starts: Vec<GridEdge> = open_ends
+            .chain(segments.iter().map(|(e0, _)| *e0))
+            .collect();
+
+        let mut used = vec![false; segments.len()];
+        let mut contours = vec![];
+        for start in starts {
+            let mut edge = start;
+            let mut chain = vec![edge];
+            while let Some(&s) = adjacency[&edge].iter().find(|&&s| !used[s]) {
+                used[s] = true;
+                let (e0, e1) = segments[s];
+                edge = if e0 == edge { e1 } else { e0 };
+                chain.push(edge);
+            }
+            if chain.len() > 2 {
+                contours.push(chain.into_iter().map(crossing).collect());
+            }
+        }
+        contours
+    }
+}
+
+/// Selects the layers [`Generator::sketch_image`] draws an image with.
+#[derive(Clone, Builder)]
+pub struct ImageSketch {
+    /// Size of an image pixel in the coordinates of the sketch.
+    #[builder(default = "1.0")]
+    pub scale: f32,
+    /// Hatches the image with a cross-hatched [`FillStyle::ToneHatch`] fill.
+    #[builder(default = "true")]
+    pub hatch: bool,
+    /// Covers the image with [`FillStyle::Stipple`] dots, more of them where it is darker.
+    #[builder(default = "false")]
+    pub stipple: bool,
+    /// Strokes the contours of the image at the luminance levels of `edge_levels`.
+    #[builder(default = "false")]
+    pub edges: bool,
+    #[builder(default = "vec![0.5]")]
+    pub edge_levels: Vec<f32>,
+    /// Distance in pixels between the samples the contours are traced from.
+    #[builder(default = "2.0")]
+    pub edge_step: f32,
+}
+
+impl Default for ImageSketch {
+    fn default() -> Self {
+        ImageSketchBuilder::default()
+            .build()
+            .expect("failed to build default image sketch")
+    }
+}
+
+impl Generator {
+    /// Sketches an image with the layers selected in `sketch`. Hatching and stippling use the
+    /// fill color of the options, or their stroke color when no fill is set.
+    pub fn sketch_image<F>(
+        &self,
+        image: &LuminanceMap,
+        sketch: &ImageSketch,
+        options: &Option<Options>,
+    ) -> Vec<Drawable<F>>
+    where
+        F: Float + Trig + FromPrimitive + MulAssign + Display,
+    {
+        let options = options
+            .clone()
+            .unwrap_or_else(|| self.default_options.clone());
+        let scale = sketch.scale;
+        let (width, height) = (image.width() as f32 * scale, image.height() as f32 * scale);
+        let frame = [
+            Point2D::new(_c::<F>(0.0), _c::<F>(0.0)),
+            Point2D::new(_c::<F>(width), _c::<F>(0.0)),
+            Point2D::new(_c::<F>(width), _c::<F>(height)),
+            Point2D::new(_c::<F>(0.0), _c::<F>(height)),
+        ];
+        let shared = Arc::new(image.clone());
+        let tone = ToneMap::Function(Arc::new(move |x, y| shared.darkness(x / scale, y / scale)));
+
+        let mut fill_options = options.clone();
+        fill_options.stroke = None;
+        fill_options.fill = options.fill.or(options.stroke);
+        fill_options.tone = Some(tone);
+
+        let mut drawables = vec![];
+        if sketch.hatch {
+            let mut o = fill_options.clone();
+            o.fill_style = Some(FillStyle::ToneHatch);
+            o.tone_cross_hatch = Some(true);
+            drawables.push(self.polygon(&frame, &Some(o)));
+        }
+        if sketch.stipple {
+            let mut o = fill_options.clone();
+            o.fill_style = Some(FillStyle::Stipple);
+            drawables.push(self.polygon(&frame, &Some(o)));
+        }
+        if sketch.edges {
+            let mut o = options.clone();
+            o.fill = None;
+            let o = Some(o);
+            for level in sketch.edge_levels.iter() {
+                for contour in image.contours(*level, sketch.edge_step) {
+                    let points: Vec<Point2D<F>> = contour
+                        .iter()
+                        .map(|p| Point2D::new(_c::<F>(p.x * scale), _c::<F>(p.y * scale)))
+                        .collect();
+                    drawables.push(self.linear_path(&points, false, &o));
+                }
+            }
+        }
+        drawables
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::LuminanceMap;
+
+    fn disc() -> LuminanceMap {
+        let size = 16;
+        let values = (0..size * size)
+            .map(|i| {
+                let (x, y) = ((i % size) as f32 + 0.5, (i / size) as f32 + 0.5);
+                if (x - 8.0).hypot(y - 8.0) < 5.0 {
+                    0.0
+                } else {
+                    1.0
+                }
+            })
+            .collect();
+        LuminanceMap::new(size, size, values)
+    }
+
+    #[test]
+    fn from_png() {
+        let mut data = vec![];
+        {
+            let mut encoder = png::Encoder::new(&mut data, 2, 1);
+            encoder.set_color(png::ColorType::Rgba);
+            encoder.set_depth(png::BitDepth::Eight);
+            let mut writer = encoder.write_header().unwrap();
+            writer
+                .write_image_data(&[0, 0, 0, 255, 0, 0, 0, 0])
+                .unwrap();
+        }
+        let map = LuminanceMap::from_png(data.as_slice()).unwrap();
+        assert_eq!(map, LuminanceMap::new(2, 1, vec![0.0, 1.0]));
+        assert_eq!(map.luminance(1.0, 0.5), 0.5);
+    }
+
+    #[test]
+    fn contours() {
+        let contours = disc().contours(0.5, 1.0);
+        assert_eq!(contours.len(), 1);
+        let contour = &contours[0];
+        assert_eq!(contour.first(), contour.last());
+        for p in contour.iter() {
+            let radius = (p.x - 8.0).hypot(p.y - 8.0);
+            assert!(radius > 3.5 && radius < 6.5);
+        }
+    }
+}
@@ -1,23 +1,39 @@
 use std::borrow::BorrowMut;
+use std::ops::Range;
 
-use euclid::default::Point2D;
-use euclid::{point2, Trig};
+use euclid::default::{Point2D, Vector2D};
+use euclid::{point2, vec2, Trig};
 use num_traits::{Float, FloatConst, FromPrimitive};
-use svg_path_ops::{absolutize, normalize};
+use points_on_curve::{catmull_rom_segment, CurveParameterization};
+use rand::rngs::StdRng;
+use rand::{Rng, RngCore, SeedableRng};
+use svg_path_ops::{absolutize, normalize_keeping_quadratics};
 use svgtypes::{PathParser, PathSegment};
 
-use super::core::{Options, _c};
-use crate::core::{FillStyle, Op, OpSet, OpSetType, OpType, _cc};
+use super::core::{_c, Options};
+use crate::core::{_cc, ArcMode, FillRule, FillStyle, Op, OpSet, OpSetType, StrokeStyle};
 use crate::filler::get_filler;
+use crate::filler::traits::PatternFiller;
 use crate::filler::FillerType::{
     DashedFiller,
     DotFiller,
+    FlowFiller,
     HatchFiller,
+    HilbertFiller,
+    LayeredHatchFiller,
+    MotifFiller,
     ScanLineHachure,
+    ScribbleFiller,
+    SerpentineFiller,
+    StippleFiller,
+    ToneHatchFiller,
+    WatercolorFiller,
+    WavyFiller,
     ZigZagFiller,
     ZigZagLineFiller,
 };
-use crate::geometry::{convert_bezier_quadratic_to_cubic, BezierQuadratic};
+use crate::geometry::{offset_polygon, point_in_polygons};
+use crate::sink::PathSink;
 
 #[derive(PartialEq, Eq, Debug)]
 pub struct EllipseParams<F: Float> {
@@ -26,6 +42,19 @@ pub struct EllipseParams<F: Float> {
     pub increment: F,
 }
 
+/// Part of the ellipse of `width` by `height` around `x`, `y` swept from `start` to `stop`,
+/// with its ends joined as `mode` says.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ArcParams<F: Float> {
+    pub x: F,
+    pub y: F,
+    pub width: F,
+    pub height: F,
+    pub start: F,
+    pub stop: F,
+    pub mode: ArcMode,
+}
+
 pub struct EllipseResult<F: Float + FromPrimitive + Trig> {
     pub opset: OpSet<F>,
     pub estimated_points: Vec<Point2D<F>>,
@@ -38,77 +67,70 @@ pub struct EllipseResult<F: Float + FromPrimitive + Trig> {
 /// * `x2` - Line end point x coordinate
 /// * `y2` - Line end point y coordinate
 /// * `o`  - Line generation options
+/// * `rng` - Random number generator the line is roughened with
 ///
 /// # Example
 /// Note that result of this call is highly dependent on your selections of
 /// options and random number seed you use
 ///
 /// ```rust
-/// use roughr::core::{Op, OpSetType, OpType, OptionsBuilder};
+/// use euclid::point2;
+/// use roughr::core::{Op, OpSetType, OptionsBuilder};
 /// use roughr::renderer::line;
 ///
-/// let mut o = OptionsBuilder::default().build().unwrap();
-/// let result = line(0.0, 0.0, 1.0, 0.0, &mut o);
+/// let o = OptionsBuilder::default().build().unwrap();
+/// let result = line(0.0, 0.0, 1.0, 0.0, &o, &mut o.rng());
 /// assert_eq!(result.op_set_type, OpSetType::Path);
 /// assert_eq!(result.size, None);
 /// assert_eq!(result.path, None);
 /// assert_eq!(result.ops.len(), 4);
 /// assert_eq!(
 ///     result.ops[0],
-///     Op {
-///         op: OpType::Move,
-///         data: vec![-0.09998378610180225, -0.06502220928668975]
-///     }
+///     Op::Move(point2(-0.09998378610180225, -0.06502220928668975))
 /// );
 /// assert_eq!(
 ///     result.ops[1],
-///     Op {
-///         op: OpType::BCurveTo,
-///         data: vec![
-///             0.3744279434863932,
-///             -0.01609907269477844,
-///             0.6946386914619221,
-///             0.02767372608184813,
-///             1.037581992149353,
-///             0.012261581420898435
-///         ]
+///     Op::BCurveTo {
+///         cp1: point2(0.3744279434863932, -0.01609907269477844),
+///         cp2: point2(0.6946386914619221, 0.02767372608184813),
+///         to: point2(1.037581992149353, 0.012261581420898435)
 ///     }
 /// );
 /// assert_eq!(
 ///     result.ops[2],
-///     Op {
-///         op: OpType::Move,
-///         data: vec![-0.03406156599521637, 0.0372807502746582]
-///     }
+///     Op::Move(point2(-0.03406156599521637, 0.0372807502746582))
 /// );
 /// assert_eq!(
 ///     result.ops[3],
-///     Op {
-///         op: OpType::BCurveTo,
-///         data: vec![
-///             0.21661711813283496,
-///             0.024078675508499146,
-///             0.4552517569317924,
-///             0.01821308374404907,
-///             1.0409734785556792,
-///             0.03352456092834473
-///         ],
+///     Op::BCurveTo {
+///         cp1: point2(0.21661711813283496, 0.024078675508499146),
+///         cp2: point2(0.4552517569317924, 0.01821308374404907),
+///         to: point2(1.0409734785556792, 0.03352456092834473)
 ///     },
 /// );
 /// ```
-pub fn line<F: Float + Trig + FromPrimitive>(
+pub fn line<F: Float + Trig + FromPrimitive, R: Rng + ?Sized>(
     x1: F,
     y1: F,
     x2: F,
     y2: F,
-    o: &mut Options,
+    o: &Options,
+    rng: &mut R,
 ) -> OpSet<F> {
-    OpSet {
-        op_set_type: OpSetType::Path,
-        ops: _double_line(x1, y1, x2, y2, o, false),
-        size: None,
-        path: None,
-    }
+    collect(OpSetType::Path, |ops| line_into(x1, y1, x2, y2, o, rng, ops))
+}
+
+/// Like [`line`], but draws straight into `sink`.
+pub fn line_into<F: Float + Trig + FromPrimitive, R: Rng + ?Sized>(
+    x1: F,
+    y1: F,
+    x2: F,
+    y2: F,
+    o: &Options,
+    rng: &mut R,
+    sink: &mut dyn PathSink<F>,
+) {
+    _overshot_line(point2(x1, y1), point2(x2, y2), o, rng, sink);
 }
 
 /// Constructs a linear path with given points by connecting consecutive points
@@ -120,6 +142,7 @@ pub fn line<F: Float + Trig + FromPrimitive>(
 /// * `points` - 2D Points which forms the path. Consecutive points will be connected to each other
 /// * `close` - If algorithm should connect last point to first point with a line
 /// * `o` - Path generation options.
+/// * `rng` - Random number generator the path is roughened with.
 ///
 /// # Example
 /// Note that result of this call is highly dependent on your selections of
@@ -127,14 +150,15 @@ pub fn line<F: Float + Trig + FromPrimitive>(
 ///
 ///```rust
 /// use euclid::point2;
-/// use roughr::core::{Op, OpSet, OpSetType, OpType, OptionsBuilder};
+/// use roughr::core::{Op, OpSet, OpSetType, OptionsBuilder};
 /// use roughr::renderer::linear_path;
 ///
-/// let mut o = OptionsBuilder::default().build().unwrap();
+/// let o = OptionsBuilder::default().build().unwrap();
 /// let result = linear_path(
 ///     &[point2(0.0f32, 0.0), point2(0.0, 0.1), point2(1.0, 1.0)],
 ///     false,
-///     &mut o,
+///     &o,
+///     &mut o.rng(),
 /// );
 /// assert_eq!(result.op_set_type, OpSetType::Path);
 /// assert_eq!(
@@ -142,201 +166,237 @@ pub fn line<F: Float + Trig + FromPrimitive>(
 ///     OpSet {
 ///         op_set_type: OpSetType::Path,
 ///         ops: vec![
-///             Op {
-///                 op: OpType::Move,
-///                 data: vec![-0.009998378, -0.006502221]
-///             },
-///             Op {
-///                 op: OpType::BCurveTo,
-///                 data: vec![
-///                     0.004064642,
-///                     0.033123452,
-///                     0.0023629116,
-///                     0.07122354,
-///                     0.0037581995,
-///                     0.10122616
-///                 ]
-///             },
-///             Op {
-///                 op: OpType::Move,
-///                 data: vec![-0.0034061566, 0.003728075]
-///             },
-///             Op {
-///                 op: OpType::BCurveTo,
-///                 data: vec![
-///                     -0.00069929345,
-///                     0.023493448,
-///                     0.0010793343,
-///                     0.044991724,
-///                     0.004097348,
-///                     0.10335246
-///                 ]
+///             Op::Move(point2(-0.009998378, -0.006502221)),
+///             Op::BCurveTo {
+///                 cp1: point2(0.004064642, 0.033123452),
+///                 cp2: point2(0.0023629116, 0.07122354),
+///                 to: point2(0.0037581995, 0.10122616)
 ///             },
-///             Op {
-///                 op: OpType::Move,
-///                 data: vec![-0.12339515, -0.013104506]
+///             Op::Move(point2(-0.0034061566, 0.003728075)),
+///             Op::BCurveTo {
+///                 cp1: point2(-0.00069929345, 0.023493448),
+///                 cp2: point2(0.0010793343, 0.044991724),
+///                 to: point2(0.004097348, 0.10335246)
 ///             },
-///             Op {
-///                 op: OpType::BCurveTo,
-///                 data: vec![0.35436878, 0.262468, 0.57661635, 0.6634873, 1.0144088, 1.102317]
+///             Op::Move(point2(-0.12339515, -0.013104506)),
+///             Op::BCurveTo {
+///                 cp1: point2(0.35436878, 0.262468),
+///                 cp2: point2(0.57661635, 0.6634873),
+///                 to: point2(1.0144088, 1.102317)
 ///             },
-///             Op {
-///                 op: OpType::Move,
-///                 data: vec![-0.002887085, 0.049306016]
-///             },
-///             Op {
-///                 op: OpType::BCurveTo,
-///                 data: vec![
-///                     0.25721234, 0.27631992, 0.59522116, 0.53014225, 0.94422996, 0.9684893
-///                 ]
+///             Op::Move(point2(-0.002887085, 0.049306016)),
+///             Op::BCurveTo {
+///                 cp1: point2(0.25721234, 0.27631992),
+///                 cp2: point2(0.59522116, 0.53014225),
+///                 to: point2(0.94422996, 0.9684893)
 ///             }
 ///         ],
 ///         size: None,
-///         path: None
+///         path: None,
+///         style: None
 ///     }
 /// );
 /// ```
-pub fn linear_path<F: Float + Trig + FromPrimitive>(
+pub fn linear_path<F: Float + Trig + FromPrimitive, R: Rng + ?Sized>(
     points: &[Point2D<F>],
     close: bool,
-    o: &mut Options,
+    o: &Options,
+    rng: &mut R,
 ) -> OpSet<F> {
+    collect(OpSetType::Path, |ops| linear_path_into(points, close, o, rng, ops))
+}
+
+/// Like [`linear_path`], but draws straight into `sink`.
+pub fn linear_path_into<F: Float + Trig + FromPrimitive, R: Rng + ?Sized>(
+    points: &[Point2D<F>],
+    close: bool,
+    o: &Options,
+    rng: &mut R,
+    sink: &mut dyn PathSink<F>,
+) {
     let len = points.len();
     if len > 2 {
-        let mut ops: Vec<Op<F>> = Vec::new();
         let mut i = 0;
         while i < (len - 1) {
-            ops.append(&mut _double_line(
-                points[i].x,
-                points[i].y,
-                points[i + 1].x,
-                points[i + 1].y,
-                o,
-                false,
-            ));
+            _overshot_line(points[i], points[i + 1], o, rng, sink);
             i += 1;
         }
         if close {
-            ops.append(&mut _double_line(
-                points[len - 1].x,
-                points[len - 1].y,
-                points[0].x,
-                points[0].y,
-                o,
-                false,
-            ));
-        }
-        OpSet {
-            op_set_type: OpSetType::Path,
-            ops: ops,
-            path: None,
-            size: None,
+            _overshot_line(points[len - 1], points[0], o, rng, sink);
         }
     } else if len == 2 {
-        line(points[0].x, points[0].y, points[1].x, points[1].y, o)
-    } else {
-        OpSet {
-            op_set_type: OpSetType::Path,
-            ops: Vec::new(),
-            path: None,
-            size: None,
-        }
+        line_into(points[0].x, points[0].y, points[1].x, points[1].y, o, rng, sink);
     }
 }
 
-pub fn polygon<F: Float + Trig + FromPrimitive>(
+pub fn polygon<F: Float + Trig + FromPrimitive, R: Rng + ?Sized>(
     points: &[Point2D<F>],
-    o: &mut Options,
+    o: &Options,
+    rng: &mut R,
 ) -> OpSet<F> {
-    linear_path(points, true, o)
+    linear_path(points, true, o, rng)
 }
 
-pub fn rectangle<F: Float + Trig + FromPrimitive>(
+/// Like [`polygon`], but draws straight into `sink`.
+pub fn polygon_into<F: Float + Trig + FromPrimitive, R: Rng + ?Sized>(
+    points: &[Point2D<F>],
+    o: &Options,
+    rng: &mut R,
+    sink: &mut dyn PathSink<F>,
+) {
+    linear_path_into(points, true, o, rng, sink)
+}
+
+pub fn rectangle<F: Float + Trig + FromPrimitive, R: Rng + ?Sized>(
     x: F,
     y: F,
     width: F,
     height: F,
-    o: &mut Options,
+    o: &Options,
+    rng: &mut R,
 ) -> OpSet<F> {
+    collect(OpSetType::Path, |ops| rectangle_into(x, y, width, height, o, rng, ops))
+}
+
+/// Like [`rectangle`], but draws straight into `sink`.
+pub fn rectangle_into<F: Float + Trig + FromPrimitive, R: Rng + ?Sized>(
+    x: F,
+    y: F,
+    width: F,
+    height: F,
+    o: &Options,
+    rng: &mut R,
+    sink: &mut dyn PathSink<F>,
+) {
     let points: Vec<Point2D<F>> = vec![
         Point2D::new(x, y),
         Point2D::new(x + width, y),
         Point2D::new(x + width, y + height),
         Point2D::new(x, y + height),
     ];
-    polygon(&points, o)
+    polygon_into(&points, o, rng, sink)
 }
 
-pub fn bezier_quadratic<F: Float + Trig + FromPrimitive>(
+pub fn bezier_quadratic<F: Float + Trig + FromPrimitive, R: Rng + ?Sized>(
     start: Point2D<F>,
     cp: Point2D<F>,
     end: Point2D<F>,
-    o: &mut Options,
+    o: &Options,
+    rng: &mut R,
 ) -> OpSet<F> {
-    let ops = _bezier_quadratic_to(cp.x, cp.y, end.x, end.y, &start, o);
+    collect(OpSetType::Path, |ops| bezier_quadratic_into(start, cp, end, o, rng, ops))
+}
 
-    OpSet {
-        op_set_type: OpSetType::Path,
-        ops,
-        path: None,
-        size: None,
-    }
+/// Like [`bezier_quadratic`], but draws straight into `sink`.
+pub fn bezier_quadratic_into<F: Float + Trig + FromPrimitive, R: Rng + ?Sized>(
+    start: Point2D<F>,
+    cp: Point2D<F>,
+    end: Point2D<F>,
+    o: &Options,
+    rng: &mut R,
+    sink: &mut dyn PathSink<F>,
+) {
+    _bezier_to(Op::QuadTo { cp, to: end }, &start, o, rng, sink);
 }
 
-pub fn bezier_cubic<F: Float + Trig + FromPrimitive>(
+pub fn bezier_cubic<F: Float + Trig + FromPrimitive, R: Rng + ?Sized>(
     start: Point2D<F>,
     cp1: Point2D<F>,
     cp2: Point2D<F>,
     end: Point2D<F>,
-    o: &mut Options,
+    o: &Options,
+    rng: &mut R,
 ) -> OpSet<F> {
-    let ops = _bezier_to(cp1.x, cp1.y, cp2.x, cp2.y, end.x, end.y, &start, o);
+    collect(OpSetType::Path, |ops| bezier_cubic_into(start, cp1, cp2, end, o, rng, ops))
+}
 
-    OpSet {
-        op_set_type: OpSetType::Path,
-        ops,
-        path: None,
-        size: None,
-    }
+/// Like [`bezier_cubic`], but draws straight into `sink`.
+pub fn bezier_cubic_into<F: Float + Trig + FromPrimitive, R: Rng + ?Sized>(
+    start: Point2D<F>,
+    cp1: Point2D<F>,
+    cp2: Point2D<F>,
+    end: Point2D<F>,
+    o: &Options,
+    rng: &mut R,
+    sink: &mut dyn PathSink<F>,
+) {
+    _bezier_to(Op::BCurveTo { cp1, cp2, to: end }, &start, o, rng, sink);
 }
 
-pub fn curve<F: Float + Trig + FromPrimitive>(points: &[Point2D<F>], o: &mut Options) -> OpSet<F> {
-    let mut o1 = _curve_with_offset(
-        points,
-        _c::<F>(1.0) * _c(1.0 + o.roughness.unwrap_or(0.0) * 0.2),
-        o,
-    );
-    if !o.disable_multi_stroke.unwrap_or(false) {
-        let mut o2 = _curve_with_offset(
+pub fn curve<F: Float + Trig + FromPrimitive, R: Rng + Clone>(
+    points: &[Point2D<F>],
+    o: &Options,
+    rng: &mut R,
+) -> OpSet<F> {
+    collect(OpSetType::Path, |ops| curve_into(points, o, rng, ops))
+}
+
+/// Like [`curve`], but draws straight into `sink`.
+pub fn curve_into<F: Float + Trig + FromPrimitive, R: Rng + Clone>(
+    points: &[Point2D<F>],
+    o: &Options,
+    rng: &mut R,
+    sink: &mut dyn PathSink<F>,
+) {
+    if o.curve_closed.unwrap_or(false) && points.len() > 2 {
+        _double_curve_with(
             points,
-            _c::<F>(1.5) * _c(1.0 + o.roughness.unwrap_or(0.0) * 0.22),
-            &mut clone_options_alter_seed(o),
+            o,
+            rng,
+            false,
+            sink,
+            |points, offset, o, rng, sink| _closed_curve_with_offset(points, offset, o, rng, sink),
         );
-        o1.append(&mut o2);
-    }
-    OpSet {
-        op_set_type: OpSetType::Path,
-        ops: o1,
-        path: None,
-        size: None,
+    } else {
+        _double_curve(points, o, rng, false, sink);
     }
 }
 
-pub fn ellipse<F: Float + Trig + FromPrimitive>(
+pub fn ellipse<F: Float + Trig + FromPrimitive, R: Rng + ?Sized>(
     x: F,
     y: F,
     width: F,
     height: F,
-    o: &mut Options,
+    o: &Options,
+    rng: &mut R,
 ) -> OpSet<F> {
-    let params = generate_ellipse_params(width, height, o);
-    ellipse_with_params(x, y, o, &params).opset
+    collect(OpSetType::Path, |ops| ellipse_into(x, y, width, height, o, rng, ops))
 }
 
-pub fn generate_ellipse_params<F: Float + Trig + FromPrimitive>(
+/// Like [`ellipse`], but draws straight into `sink`.
+pub fn ellipse_into<F: Float + Trig + FromPrimitive, R: Rng + ?Sized>(
+    x: F,
+    y: F,
     width: F,
     height: F,
-    o: &mut Options,
+    o: &Options,
+    rng: &mut R,
+    sink: &mut dyn PathSink<F>,
+) {
+    _ellipse(point2(x, y), width, height, o, rng, sink);
+}
+
+pub(crate) fn _ellipse<F, R, S>(
+    center: Point2D<F>,
+    width: F,
+    height: F,
+    o: &Options,
+    rng: &mut R,
+    sink: &mut S,
+) where
+    F: Float + Trig + FromPrimitive,
+    R: Rng + ?Sized,
+    S: PathSink<F> + ?Sized,
+{
+    let params = generate_ellipse_params(width, height, o, rng);
+    _ellipse_with_params(center, &params, o, rng, sink);
+}
+
+pub fn generate_ellipse_params<F: Float + Trig + FromPrimitive, R: Rng + ?Sized>(
+    width: F,
+    height: F,
+    o: &Options,
+    rng: &mut R,
 ) -> EllipseParams<F> {
     let psq: F = Float::sqrt(
         _c::<F>(f32::PI())
@@ -353,227 +413,532 @@ pub fn generate_ellipse_params<F: Float + Trig + FromPrimitive>(
     let mut rx = Float::abs(width / _c(2.0));
     let mut ry = Float::abs(height / _c(2.0));
     let curve_fit_randomness: F = _c::<F>(1.0) - _c(o.curve_fitting.unwrap_or(0.0));
-    rx = rx + _offset_opt(rx * curve_fit_randomness, o, None);
-    ry = ry + _offset_opt(ry * curve_fit_randomness, o, None);
+    rx = rx + _offset_opt(rx * curve_fit_randomness, o, rng, None);
+    ry = ry + _offset_opt(ry * curve_fit_randomness, o, rng, None);
     EllipseParams { increment, rx, ry }
 }
 
-pub fn ellipse_with_params<F: Float + Trig + FromPrimitive>(
+pub fn ellipse_with_params<F: Float + Trig + FromPrimitive, R: Rng + ?Sized>(
     x: F,
     y: F,
-    o: &mut Options,
+    o: &Options,
+    rng: &mut R,
     ellipse_params: &EllipseParams<F>,
 ) -> EllipseResult<F> {
+    let mut estimated_points = vec![];
+    let opset = collect(OpSetType::Path, |ops| {
+        estimated_points = ellipse_with_params_into(x, y, o, rng, ellipse_params, ops);
+    });
+    EllipseResult { estimated_points, opset }
+}
+
+/// Like [`ellipse_with_params`], but draws straight into `sink` and only returns the
+/// estimated points.
+pub fn ellipse_with_params_into<F: Float + Trig + FromPrimitive, R: Rng + ?Sized>(
+    x: F,
+    y: F,
+    o: &Options,
+    rng: &mut R,
+    ellipse_params: &EllipseParams<F>,
+    sink: &mut dyn PathSink<F>,
+) -> Vec<Point2D<F>> {
+    _ellipse_with_params(point2(x, y), ellipse_params, o, rng, sink)
+}
+
+/// Draws the ellipse into `sink` and returns the points on it the rough curve follows.
+fn _ellipse_with_params<F, R, S>(
+    center: Point2D<F>,
+    ellipse_params: &EllipseParams<F>,
+    o: &Options,
+    rng: &mut R,
+    sink: &mut S,
+) -> Vec<Point2D<F>>
+where
+    F: Float + Trig + FromPrimitive,
+    R: Rng + ?Sized,
+    S: PathSink<F> + ?Sized,
+{
     let ellipse_points = _compute_ellipse_points(
-        ellipse_params.increment,
-        x,
-        y,
-        ellipse_params.rx,
-        ellipse_params.ry,
+        center,
+        ellipse_params,
         _c(1.0),
         ellipse_params.increment
             * _offset(
                 _c(0.1),
-                _offset(_c::<F>(0.4), _c::<F>(1.0), o, None),
+                _offset(_c::<F>(0.4), _c::<F>(1.0), o, rng, None),
                 o,
+                rng,
                 None,
             ),
         o,
+        rng,
     );
     let ap1 = ellipse_points[0].clone();
     let cp1 = ellipse_points[1].clone();
-    let mut o1 = _curve(&ap1, None, o);
-    if (!o.disable_multi_stroke.unwrap_or(false)) && (o.roughness.unwrap_or(0.0) != 0.0) {
-        let inner_ellipse_points = _compute_ellipse_points(
-            ellipse_params.increment,
-            x,
-            y,
-            ellipse_params.rx,
-            ellipse_params.ry,
-            _c::<F>(1.5),
-            _c::<F>(0.0),
-            o,
-        );
-        let ap2 = inner_ellipse_points[0].clone();
-        let _cp2 = inner_ellipse_points[1].clone();
-        let mut o2 = _curve(&ap2, None, o);
-        o1.append(&mut o2);
-    }
-    EllipseResult {
-        estimated_points: cp1,
-        opset: OpSet {
-            op_set_type: OpSetType::Path,
-            ops: o1,
-            size: None,
-            path: None,
-        },
+    _curve(&ap1, None, o, rng, sink);
+    if o.roughness.unwrap_or(0.0) != 0.0 {
+        for pass in 1..pass_count(o, false) {
+            with_pass_roughness(o, pass, |o| {
+                let inner_ellipse_points = _compute_ellipse_points(
+                    center,
+                    ellipse_params,
+                    _c::<F>(1.5),
+                    _c::<F>(0.0),
+                    o,
+                    rng,
+                );
+                _curve(&inner_ellipse_points[0], None, o, rng, sink);
+            });
+        }
     }
+    cp1
 }
 
-pub fn arc<F: Float + Trig + FromPrimitive>(
-    x: F,
-    y: F,
-    width: F,
-    height: F,
-    start: F,
-    stop: F,
-    closed: bool,
+pub fn arc<F: Float + Trig + FromPrimitive, R: Rng + ?Sized>(
+    arc_params: &ArcParams<F>,
     rough_closure: bool,
-    o: &mut Options,
+    o: &Options,
+    rng: &mut R,
 ) -> OpSet<F> {
+    collect(OpSetType::Path, |ops| arc_into(arc_params, rough_closure, o, rng, ops))
+}
+
+/// Like [`arc`], but draws straight into `sink`.
+pub fn arc_into<F: Float + Trig + FromPrimitive, R: Rng + ?Sized>(
+    arc_params: &ArcParams<F>,
+    rough_closure: bool,
+    o: &Options,
+    rng: &mut R,
+    sink: &mut dyn PathSink<F>,
+) {
+    let ArcParams { x, y, width, height, start, stop, mode } = *arc_params;
     let cx = x;
     let cy = y;
     let mut rx = Float::abs(width / _c(2.0));
     let mut ry = Float::abs(height / _c(2.0));
-    rx = rx + _offset_opt(rx * _c(0.01), o, None);
-    ry = ry + _offset_opt(ry * _c(0.01), o, None);
-    let mut strt: F = start;
-    let mut stp: F = stop;
-    while strt < _c(0.0) {
-        strt = strt + _c(f32::PI() * 2.0);
-        stp = stp + _c(f32::PI() * 2.0);
+    rx = rx + _offset_opt(rx * _c(0.01), o, rng, None);
+    ry = ry + _offset_opt(ry * _c(0.01), o, rng, None);
+    let (strt, stp) = normalize_arc_angles(start, stop);
+    let ellipse_inc: F = _c::<F>(f32::PI() * 2.0) / _c(o.curve_step_count.unwrap_or(1.0));
+    // Tiny sweeps are still stepped through coarsely, as the rough start of the curve may lie
+    // far before them.
+    let arc_inc =
+        Float::min(ellipse_inc / _c(2.0), (stp - strt) / _c(2.0)).max(ellipse_inc / _c(16.0));
+    let start_point: Point2D<F> = point2(cx + rx * Float::cos(strt), cy + ry * Float::sin(strt));
+    let end_point: Point2D<F> = point2(cx + rx * Float::cos(stp), cy + ry * Float::sin(stp));
+    // an arc that sweeps no angle has no curve to draw
+    if stp > strt {
+        let ellipse_params = EllipseParams { rx, ry, increment: arc_inc };
+        let center = point2(cx, cy);
+        _arc(
+            center,
+            &ellipse_params,
+            strt..stp,
+            _c(1.0),
+            o,
+            rng,
+            sink,
+        );
+        for pass in 1..pass_count(o, false) {
+            with_pass_roughness(o, pass, |o| {
+                _arc(
+                    center,
+                    &ellipse_params,
+                    strt..stp,
+                    _c(1.5),
+                    o,
+                    rng,
+                    sink,
+                )
+            });
+        }
+    } else if !rough_closure && mode == ArcMode::Pie {
+        sink.move_to(end_point);
     }
-    if (stp - strt) > _c(f32::PI() * 2.0) {
-        strt = _c(0.0);
-        stp = _c(f32::PI() * 2.0);
+    match (mode, rough_closure) {
+        (ArcMode::Open, _) => {}
+        (ArcMode::Chord, true) => {
+            _double_line(end_point, start_point, o, rng, false, sink);
+        }
+        (ArcMode::Chord, false) => {
+            if stp > strt {
+                sink.line_to(start_point);
+            }
+        }
+        (ArcMode::Pie, true) => {
+            _double_line(point2(cx, cy), start_point, o, rng, false, sink);
+            _double_line(point2(cx, cy), end_point, o, rng, false, sink);
+        }
+        (ArcMode::Pie, false) => {
+            sink.line_to(point2(cx, cy));
+            sink.line_to(start_point);
+        }
     }
-    let ellipse_inc: F = _c::<F>(f32::PI() * 2.0) / _c(o.curve_step_count.unwrap_or(1.0));
-    let arc_inc = Float::min(ellipse_inc / _c(2.0), (stp - strt) / _c(2.0));
-    let mut ops = _arc(arc_inc, cx, cy, rx, ry, strt, stp, _c(1.0), o);
-    if !o.disable_multi_stroke.unwrap_or(false) {
-        let mut o2 = _arc(arc_inc, cx, cy, rx, ry, strt, stp, _c(1.5), o);
-        ops.append(&mut o2);
+}
+
+/// Brings the angles of an arc into the order it is drawn in: `start` is moved into one
+/// turn from zero, and `stop` follows it by at most a full turn, going around once more if
+/// it lies before `start`.
+pub(crate) fn normalize_arc_angles<F: Float + Trig + FromPrimitive>(start: F, stop: F) -> (F, F) {
+    let two_pi = _c::<F>(f32::PI() * 2.0);
+    let mut strt = start;
+    let mut stp = stop;
+    while strt < F::zero() {
+        strt = strt + two_pi;
+        stp = stp + two_pi;
     }
-    if closed {
-        if rough_closure {
-            ops.append(&mut _double_line(
-                cx,
-                cy,
-                cx + rx * Float::cos(strt),
-                cy + ry * Float::sin(strt),
-                o,
-                false,
-            ));
-            ops.append(&mut _double_line(
-                cx,
-                cy,
-                cx + rx * Float::cos(stp),
-                cy + ry * Float::sin(stp),
-                o,
-                false,
-            ));
+    while stp < strt {
+        stp = stp + two_pi;
+    }
+    if (stp - strt) > two_pi {
+        strt = F::zero();
+        stp = two_pi;
+    }
+    (strt, stp)
+}
+
+/// Number of points around the outline of a dot in a dotted stroke.
+const DOT_POINTS: usize = 6;
+
+/// Cuts the `polylines` into dashes or dots by arc length, as asked for by `stroke_style`,
+/// and draws each of them as a rough stroke of its own. The pattern is taken from
+/// `stroke_line_dash` and `stroke_line_dash_offset`, or derived from the stroke width. The
+/// position and length of every dash vary a little with the roughness.
+pub fn dashed_polylines<F: Float + Trig + FromPrimitive, R: Rng + Clone>(
+    polylines: &[Vec<Point2D<F>>],
+    o: &Options,
+    rng: &mut R,
+) -> OpSet<F> {
+    collect(OpSetType::Path, |ops| dashed_polylines_into(polylines, o, rng, ops))
+}
+
+/// Like [`dashed_polylines`], but draws straight into `sink`.
+pub fn dashed_polylines_into<F: Float + Trig + FromPrimitive, R: Rng + Clone>(
+    polylines: &[Vec<Point2D<F>>],
+    o: &Options,
+    rng: &mut R,
+    sink: &mut dyn PathSink<F>,
+) {
+    let stroke_width = _c::<F>(o.stroke_width.unwrap_or(1.0).max(0.5));
+    let dotted = o.stroke_style == Some(StrokeStyle::Dotted);
+    let mut pattern: Vec<F> = o
+        .stroke_line_dash
+        .iter()
+        .flatten()
+        .map(|length| _cc::<F>(length.max(0.0)))
+        .collect();
+    if pattern.len() % 2 == 1 {
+        // Like in SVG, an odd number of lengths is repeated to alternate dashes and gaps.
+        pattern = pattern.repeat(2);
+    }
+    let period = pattern.iter().fold(F::zero(), |sum, length| sum + *length);
+    if period < stroke_width {
+        pattern = if dotted {
+            vec![F::zero(), stroke_width * _c(4.0)]
         } else {
-            ops.push(Op { op: OpType::LineTo, data: vec![cx, cy] });
-            ops.push(Op {
-                op: OpType::LineTo,
-                data: vec![cx + rx * Float::cos(strt), cy + ry * Float::sin(strt)],
-            });
+            vec![stroke_width * _c(8.0), stroke_width * _c(5.0)]
+        };
+    } else if dotted {
+        pattern = vec![F::zero(), period];
+    }
+    let period = pattern.iter().fold(F::zero(), |sum, length| sum + *length);
+    let mut offset = _cc::<F>(o.stroke_line_dash_offset.unwrap_or(0.0)) % period;
+    if offset < F::zero() {
+        offset = offset + period;
+    }
+    let variation = _c::<F>((o.roughness.unwrap_or(1.0) * 0.15).min(0.3));
+
+    for polyline in polylines.iter().filter(|polyline| polyline.len() > 1) {
+        let mut distances = vec![F::zero()];
+        for w in polyline.windows(2) {
+            distances.push(distances[distances.len() - 1] + (w[1] - w[0]).length());
+        }
+        let total = distances[distances.len() - 1];
+
+        let mut position = -offset;
+        let mut index = 0;
+        while position < total {
+            let length = pattern[index % pattern.len()];
+            if index % 2 == 0 {
+                // Both jitters are bounded by the neighbouring gap so that dashes never merge.
+                let gap = pattern[(index + 1) % pattern.len()];
+                let jitter = gap.min(length.max(stroke_width)) * variation;
+                let start = position + jitter * (_cc::<F>(rng.gen()) - _c(0.5));
+                if dotted {
+                    if start >= F::zero() && start <= total {
+                        let center = polyline_slice(polyline, &distances, start, start)[0];
+                        _dot(center, stroke_width * _c(0.5), o, rng, sink);
+                    }
+                } else {
+                    let end = start + length + jitter * (_cc::<F>(rng.gen()) * _c(2.0) - F::one());
+                    let (start, end) = (start.max(F::zero()), end.min(total));
+                    if end - start > F::epsilon() {
+                        let dash = polyline_slice(polyline, &distances, start, end);
+                        _dash(&dash, stroke_width * _c(8.0), o, rng, sink);
+                    }
+                }
+            }
+            position = position + length;
+            index += 1;
         }
     }
-    OpSet {
-        op_set_type: OpSetType::Path,
-        ops,
-        path: None,
-        size: None,
+}
+
+/// The part of `polyline` between the arc lengths `from` and `to`, where `distances` holds
+/// the arc length at each of its points.
+fn polyline_slice<F: Float + Trig + FromPrimitive>(
+    polyline: &[Point2D<F>],
+    distances: &[F],
+    from: F,
+    to: F,
+) -> Vec<Point2D<F>> {
+    let at = |distance: F| {
+        let i = distances
+            .partition_point(|d| *d <= distance)
+            .clamp(1, polyline.len() - 1);
+        let length = distances[i] - distances[i - 1];
+        if length <= F::zero() {
+            polyline[i]
+        } else {
+            polyline[i - 1].lerp(polyline[i], (distance - distances[i - 1]) / length)
+        }
+    };
+    let mut points = vec![at(from)];
+    for (p, d) in polyline.iter().zip(distances.iter()) {
+        if *d > from && *d < to {
+            points.push(*p);
+        }
     }
+    points.push(at(to));
+    points
 }
 
-pub fn solid_fill_polygon<F: Float + Trig + FromPrimitive>(
+/// Draws a single dash. It is split at sharp corners, and the points of the pieces are
+/// thinned out to about one every `spacing`, as each of them adds its own wobble.
+fn _dash<F, R, S>(points: &[Point2D<F>], spacing: F, o: &Options, rng: &mut R, sink: &mut S)
+where
+    F: Float + Trig + FromPrimitive,
+    R: Rng + Clone,
+    S: PathSink<F> + ?Sized,
+{
+    let mut pieces: Vec<Vec<Point2D<F>>> = vec![vec![points[0]]];
+    for w in points.windows(3) {
+        let (a, b) = (w[1] - w[0], w[2] - w[1]);
+        let piece = pieces.last_mut().unwrap();
+        if a.dot(b) < a.length() * b.length() * _c(0.7) {
+            piece.push(w[1]);
+            pieces.push(vec![w[1]]);
+        } else if (w[1] - piece[piece.len() - 1]).length() >= spacing
+            && (points[points.len() - 1] - w[1]).length() >= spacing / _c(2.0)
+        {
+            piece.push(w[1]);
+        }
+    }
+    pieces.last_mut().unwrap().push(points[points.len() - 1]);
+
+    for piece in pieces.iter() {
+        if piece.len() == 2 {
+            _double_line(piece[0], piece[1], o, rng, false, sink);
+        } else {
+            _double_curve(piece, o, rng, false, sink);
+        }
+    }
+}
+
+/// Draws a small closed loop around `center`, which a stroke as wide as the diameter fills.
+fn _dot<F, R, S>(center: Point2D<F>, radius: F, o: &Options, rng: &mut R, sink: &mut S)
+where
+    F: Float + Trig + FromPrimitive,
+    R: Rng + ?Sized,
+    S: PathSink<F> + ?Sized,
+{
+    let center = point2(
+        center.x + _offset_opt(radius * _c(0.5), o, rng, None),
+        center.y + _offset_opt(radius * _c(0.5), o, rng, None),
+    );
+    let points: Vec<Point2D<F>> = (0..DOT_POINTS)
+        .map(|i| {
+            let angle =
+                _c::<F>(f32::PI() * 2.0) * F::from(i).unwrap() / F::from(DOT_POINTS).unwrap();
+            let r = radius + _offset_opt(radius * _c(0.2), o, rng, None);
+            center + vec2(Float::cos(angle), Float::sin(angle)) * r
+        })
+        .collect();
+    // The curve is drawn from the second to the second to last point, so the loop is padded
+    // with its neighbouring points at both ends.
+    let mut closed = vec![points[DOT_POINTS - 1]];
+    closed.extend(points.iter());
+    closed.extend([points[0], points[1]]);
+    _curve(&closed, None, o, rng, sink)
+}
+
+pub fn solid_fill_polygon<F: Float + Trig + FromPrimitive, R: Rng + ?Sized>(
     polygon_list: &Vec<Vec<Point2D<F>>>,
-    options: &mut Options,
+    options: &Options,
+    rng: &mut R,
 ) -> OpSet<F> {
-    let mut ops = vec![];
-    for polygon in polygon_list {
+    collect(OpSetType::FillPath, |ops| solid_fill_polygon_into(polygon_list, options, rng, ops))
+}
+
+/// Like [`solid_fill_polygon`], but draws straight into `sink`.
+pub fn solid_fill_polygon_into<F: Float + Trig + FromPrimitive, R: Rng + ?Sized>(
+    polygon_list: &Vec<Vec<Point2D<F>>>,
+    options: &Options,
+    rng: &mut R,
+    sink: &mut dyn PathSink<F>,
+) {
+    let misregistered = misregister_fill(polygon_list, options, rng);
+    for polygon in misregistered.as_ref().unwrap_or(polygon_list) {
         if polygon.len() > 2 {
             let rand_offset = _c(options.max_randomness_offset.unwrap_or(2.0));
             polygon.iter().enumerate().for_each(|(ind, point)| {
                 if ind == 0 {
-                    ops.push(Op {
-                        op: OpType::Move,
-                        data: vec![
-                            point.x + _offset_opt(rand_offset, options, None),
-                            point.y + _offset_opt(rand_offset, options, None),
-                        ],
-                    });
+                    sink.move_to(point2(
+                        point.x + _offset_opt(rand_offset, options, rng, None),
+                        point.y + _offset_opt(rand_offset, options, rng, None),
+                    ));
                 } else {
-                    ops.push(Op {
-                        op: OpType::LineTo,
-                        data: vec![
-                            point.x + _offset_opt(rand_offset, options, None),
-                            point.y + _offset_opt(rand_offset, options, None),
-                        ],
-                    });
+                    sink.line_to(point2(
+                        point.x + _offset_opt(rand_offset, options, rng, None),
+                        point.y + _offset_opt(rand_offset, options, rng, None),
+                    ));
                 }
-            })
+            });
+            sink.close();
         }
     }
-    OpSet {
-        op_set_type: OpSetType::FillPath,
-        ops,
-        size: None,
-        path: None,
+}
+
+/// Offset and inset of the fill against the outline, as set by `fill_offset`,
+/// `fill_misregistration` and `fill_inset`, or `None` if the fill lines up with it.
+pub(crate) fn fill_registration<F: Float + Trig + FromPrimitive, R: Rng + ?Sized>(
+    o: &Options,
+    rng: &mut R,
+) -> Option<(Vector2D<F>, F)> {
+    if o.fill_offset.is_none() && o.fill_inset.is_none() && o.fill_misregistration.is_none() {
+        return None;
+    }
+    let mut offset = o.fill_offset.unwrap_or_default();
+    if let Some(misregistration) = o.fill_misregistration {
+        let angle = rng.gen::<f64>() as f32 * std::f32::consts::TAU;
+        let distance = rng.gen::<f64>() as f32 * misregistration;
+        offset += vec2(angle.cos(), angle.sin()) * distance;
     }
+    Some((
+        vec2(_c(offset.x), _c(offset.y)),
+        _c(o.fill_inset.unwrap_or(0.0)),
+    ))
 }
 
-pub fn rand_offset<F: Float + Trig + FromPrimitive>(x: F, o: &mut Options) -> F {
-    _offset_opt(x, o, None)
+/// Moves and shrinks the fill polygons by the [`fill_registration`], or returns `None` if
+/// the fill lines up with the outline. Holes grow by the inset as the outer rings shrink.
+fn misregister_fill<F: Float + Trig + FromPrimitive, R: Rng + ?Sized>(
+    polygon_list: &[Vec<Point2D<F>>],
+    o: &Options,
+    rng: &mut R,
+) -> Option<Vec<Vec<Point2D<F>>>> {
+    let (offset, inset) = fill_registration::<F, R>(o, rng)?;
+    Some(
+        polygon_list
+            .iter()
+            .map(|polygon| {
+                let depth = match polygon.first() {
+                    Some(first) => polygon_list
+                        .iter()
+                        .filter(|other| {
+                            !std::ptr::eq(*other, polygon)
+                                && point_in_polygons(
+                                    *first,
+                                    std::slice::from_ref(*other),
+                                    FillRule::EvenOdd,
+                                )
+                        })
+                        .count(),
+                    None => 0,
+                };
+                let distance = if depth % 2 == 0 { -inset } else { inset };
+                let polygon = if inset == F::zero() {
+                    polygon.clone()
+                } else {
+                    offset_polygon(polygon, distance)
+                };
+                polygon.into_iter().map(|p| p + offset).collect()
+            })
+            .collect(),
+    )
+}
+
+pub fn rand_offset<F: Float + Trig + FromPrimitive, R: Rng + ?Sized>(
+    x: F,
+    o: &Options,
+    rng: &mut R,
+) -> F {
+    _offset_opt(x, o, rng, None)
 }
 
-pub fn rand_offset_with_range<F: Float + Trig + FromPrimitive>(
+pub fn rand_offset_with_range<F: Float + Trig + FromPrimitive, R: Rng + ?Sized>(
     min: F,
     max: F,
-    o: &mut Options,
+    o: &Options,
+    rng: &mut R,
 ) -> F {
-    _offset(min, max, o, None)
+    _offset(min, max, o, rng, None)
 }
 
-pub fn double_line_fill_ops<F: Float + Trig + FromPrimitive>(
+pub fn double_line_fill_ops<F: Float + Trig + FromPrimitive, R: Rng + ?Sized>(
     x1: F,
     y1: F,
     x2: F,
     y2: F,
-    o: &mut Options,
+    o: &Options,
+    rng: &mut R,
 ) -> Vec<Op<F>> {
-    _double_line(x1, y1, x2, y2, o, true)
+    let mut ops = vec![];
+    _double_line(point2(x1, y1), point2(x2, y2), o, rng, true, &mut ops);
+    ops
 }
 
-fn clone_options_alter_seed(ops: &mut Options) -> Options {
-    let mut result: Options = ops.clone();
-    if let Some(seed) = ops.seed {
-        result.seed = Some(seed + 1);
-    }
-    result
+/// A new random sequence, seeded from the current one.
+fn reseed<R: Rng + ?Sized>(rng: &mut R) -> StdRng {
+    StdRng::seed_from_u64((rng.gen::<f64>() * u64::MAX as f64) as u64)
 }
 
-fn _offset<F: Float + Trig + FromPrimitive>(
+fn _offset<F: Float + Trig + FromPrimitive, R: Rng + ?Sized>(
     min: F,
     max: F,
-    ops: &mut Options,
+    ops: &Options,
+    rng: &mut R,
     roughness_gain: Option<F>,
 ) -> F {
     let rg: F = roughness_gain.unwrap_or_else(|| _c(1.0));
     _c::<F>(ops.roughness.unwrap_or(1.0))
         * rg
-        * ((_c::<F>(ops.random() as f32) * (max - min)) + min)
+        * ((_c::<F>(rng.gen::<f64>() as f32) * (max - min)) + min)
 }
 
-fn _offset_opt<F: Float + Trig + FromPrimitive>(
+fn _offset_opt<F: Float + Trig + FromPrimitive, R: Rng + ?Sized>(
     x: F,
-    ops: &mut Options,
+    ops: &Options,
+    rng: &mut R,
     roughness_gain: Option<F>,
 ) -> F {
-    _offset(-x, x, ops, roughness_gain)
+    _offset(-x, x, ops, rng, roughness_gain)
 }
 
-fn _line<F: Float + Trig + FromPrimitive>(
-    x1: F,
-    y1: F,
-    x2: F,
-    y2: F,
-    o: &mut Options,
+fn _line<F, R, S>(
+    from: Point2D<F>,
+    to: Point2D<F>,
+    o: &Options,
+    rng: &mut R,
     mover: bool,
     overlay: bool,
-) -> Vec<Op<F>> {
+    sink: &mut S,
+) where
+    F: Float + Trig + FromPrimitive,
+    R: Rng + ?Sized,
+    S: PathSink<F> + ?Sized,
+{
+    let (x1, y1, x2, y2) = (from.x, from.y, to.x, to.y);
     let length_sq = (x1 - x2).powi(2) + (y1 - y2).powi(2);
     let length = length_sq.sqrt();
     let roughness_gain;
@@ -590,7 +955,7 @@ fn _line<F: Float + Trig + FromPrimitive>(
         offset = length / _c(10.0);
     }
     let half_offset = offset / _c(2.0);
-    let diverge_point = _c::<F>(0.2) + _c::<F>(o.random() as f32) * _c(0.2);
+    let diverge_point = _c::<F>(0.2) + _c::<F>(rng.gen::<f64>() as f32) * _c(0.2);
     let mut mid_disp_x = _c::<F>(o.bowing.unwrap_or(1.0) as f32)
         * _c(o.max_randomness_offset.unwrap_or(2.0) as f32)
         * (y2 - y1)
@@ -599,251 +964,390 @@ fn _line<F: Float + Trig + FromPrimitive>(
         * _c(o.max_randomness_offset.unwrap_or(2.0) as f32)
         * (x1 - x2)
         / _c(200.0);
-    mid_disp_x = _offset_opt(mid_disp_x, o, Some(roughness_gain));
-    mid_disp_y = _offset_opt(mid_disp_y, o, Some(roughness_gain));
-    let mut ops: Vec<Op<F>> = Vec::new();
+    mid_disp_x = _offset_opt(mid_disp_x, o, rng, Some(roughness_gain));
+    mid_disp_y = _offset_opt(mid_disp_y, o, rng, Some(roughness_gain));
 
     let preserve_vertices = o.preserve_vertices.unwrap_or(false);
     if mover {
         if overlay {
-            ops.push(Op {
-                op: OpType::Move,
-                data: vec![
-                    x1 + if preserve_vertices {
-                        _c(0.0)
-                    } else {
-                        _offset_opt(half_offset, o, Some(roughness_gain))
-                    },
-                    y1 + if preserve_vertices {
-                        _c(0.0)
-                    } else {
-                        _offset_opt(half_offset, o, Some(roughness_gain))
-                    },
-                ],
-            });
+            sink.move_to(point2(
+                x1 + if preserve_vertices {
+                    _c(0.0)
+                } else {
+                    _offset_opt(half_offset, o, rng, Some(roughness_gain))
+                },
+                y1 + if preserve_vertices {
+                    _c(0.0)
+                } else {
+                    _offset_opt(half_offset, o, rng, Some(roughness_gain))
+                },
+            ));
         } else {
-            ops.push(Op {
-                op: OpType::Move,
-                data: vec![
-                    x1 + if preserve_vertices {
-                        _c(0.0)
-                    } else {
-                        _offset_opt(offset, o, Some(roughness_gain))
-                    },
-                    y1 + if preserve_vertices {
-                        _c(0.0)
-                    } else {
-                        _offset_opt(offset, o, Some(roughness_gain))
-                    },
-                ],
-            });
+            sink.move_to(point2(
+                x1 + if preserve_vertices {
+                    _c(0.0)
+                } else {
+                    _offset_opt(offset, o, rng, Some(roughness_gain))
+                },
+                y1 + if preserve_vertices {
+                    _c(0.0)
+                } else {
+                    _offset_opt(offset, o, rng, Some(roughness_gain))
+                },
+            ));
         }
     }
     if overlay {
-        ops.push(Op {
-            op: OpType::BCurveTo,
-            data: vec![
+        sink.curve_to(
+            point2(
                 mid_disp_x
                     + x1
                     + (x2 - x1) * diverge_point
-                    + _offset_opt(half_offset, o, Some(roughness_gain)),
+                    + _offset_opt(half_offset, o, rng, Some(roughness_gain)),
                 mid_disp_y
                     + y1
                     + (y2 - y1) * diverge_point
-                    + _offset_opt(half_offset, o, Some(roughness_gain)),
+                    + _offset_opt(half_offset, o, rng, Some(roughness_gain)),
+            ),
+            point2(
                 mid_disp_x
                     + x1
                     + _c::<F>(2.0) * (x2 - x1) * diverge_point
-                    + _offset_opt(half_offset, o, Some(roughness_gain)),
+                    + _offset_opt(half_offset, o, rng, Some(roughness_gain)),
                 mid_disp_y
                     + y1
                     + _c::<F>(2.0) * (y2 - y1) * diverge_point
-                    + _offset_opt(half_offset, o, Some(roughness_gain)),
+                    + _offset_opt(half_offset, o, rng, Some(roughness_gain)),
+            ),
+            point2(
                 x2 + if preserve_vertices {
                     _c(0.0)
                 } else {
-                    _offset_opt(half_offset, o, Some(roughness_gain))
+                    _offset_opt(half_offset, o, rng, Some(roughness_gain))
                 },
                 y2 + if preserve_vertices {
                     _c(0.0)
                 } else {
-                    _offset_opt(half_offset, o, Some(roughness_gain))
+                    _offset_opt(half_offset, o, rng, Some(roughness_gain))
                 },
-            ],
-        });
+            ),
+        );
     } else {
-        ops.push(Op {
-            op: OpType::BCurveTo,
-            data: vec![
+        sink.curve_to(
+            point2(
                 mid_disp_x
                     + x1
                     + (x2 - x1) * diverge_point
-                    + _offset_opt(offset, o, Some(roughness_gain)),
+                    + _offset_opt(offset, o, rng, Some(roughness_gain)),
                 mid_disp_y
                     + y1
                     + (y2 - y1) * diverge_point
-                    + _offset_opt(offset, o, Some(roughness_gain)),
+                    + _offset_opt(offset, o, rng, Some(roughness_gain)),
+            ),
+            point2(
                 mid_disp_x
                     + x1
                     + _c::<F>(2.0) * (x2 - x1) * diverge_point
-                    + _offset_opt(offset, o, Some(roughness_gain)),
+                    + _offset_opt(offset, o, rng, Some(roughness_gain)),
                 mid_disp_y
                     + y1
                     + _c::<F>(2.0) * (y2 - y1) * diverge_point
-                    + _offset_opt(offset, o, Some(roughness_gain)),
+                    + _offset_opt(offset, o, rng, Some(roughness_gain)),
+            ),
+            point2(
                 x2 + if preserve_vertices {
                     _c(0.0)
                 } else {
-                    _offset_opt(offset, o, Some(roughness_gain))
+                    _offset_opt(offset, o, rng, Some(roughness_gain))
                 },
                 y2 + if preserve_vertices {
                     _c(0.0)
                 } else {
-                    _offset_opt(offset, o, Some(roughness_gain))
+                    _offset_opt(offset, o, rng, Some(roughness_gain))
                 },
-            ],
-        });
+            ),
+        );
     }
-    ops
 }
 
-pub(crate) fn _double_line<F: Float + Trig + FromPrimitive>(
-    x1: F,
-    y1: F,
-    x2: F,
-    y2: F,
-    o: &mut Options,
+/// Draws the stroke from `start` to `end`, extended past both ends by a random length of
+/// up to `overshoot`, so that strokes meeting at a vertex cross each other. Vertices are
+/// kept in place if `preserve_vertices` is set.
+fn _overshot_line<F, R, S>(
+    start: Point2D<F>,
+    end: Point2D<F>,
+    o: &Options,
+    rng: &mut R,
+    sink: &mut S,
+) where
+    F: Float + Trig + FromPrimitive,
+    R: Rng + ?Sized,
+    S: PathSink<F> + ?Sized,
+{
+    let overshoot = o.overshoot.unwrap_or(0.0);
+    let length = (end - start).length();
+    if overshoot <= 0.0 || o.preserve_vertices.unwrap_or(false) || length <= F::zero() {
+        return _double_line(start, end, o, rng, false, sink);
+    }
+    let direction = (end - start) / length;
+    let start = start - direction * _c::<F>(rng.gen::<f64>() as f32 * overshoot);
+    let end = end + direction * _c::<F>(rng.gen::<f64>() as f32 * overshoot);
+    _double_line(start, end, o, rng, false, sink)
+}
+
+pub(crate) fn _double_line<F, R, S>(
+    from: Point2D<F>,
+    to: Point2D<F>,
+    o: &Options,
+    rng: &mut R,
     filling: bool,
-) -> Vec<Op<F>> {
-    let single_stroke = if filling {
-        o.disable_multi_stroke_fill.unwrap_or(false)
+    sink: &mut S,
+) where
+    F: Float + Trig + FromPrimitive,
+    R: Rng + ?Sized,
+    S: PathSink<F> + ?Sized,
+{
+    _line(from, to, o, rng, true, false, sink);
+    for pass in 1..pass_count(o, filling) {
+        with_pass_roughness(o, pass, |o| _line(from, to, o, rng, true, true, sink));
+    }
+}
+
+pub(crate) fn _double_curve<F, R, S>(
+    points: &[Point2D<F>],
+    o: &Options,
+    rng: &mut R,
+    filling: bool,
+    sink: &mut S,
+) where
+    F: Float + Trig + FromPrimitive,
+    R: Rng + Clone,
+    S: PathSink<F> + ?Sized,
+{
+    _double_curve_with(
+        points,
+        o,
+        rng,
+        filling,
+        sink,
+        |points, offset, o, rng, sink| _curve_with_offset(points, offset, o, rng, sink),
+    )
+}
+
+/// Draws the passes of a curve with `draw`, which takes the points, the random offset of
+/// the points, the options, the random number generator and the sink to draw into.
+fn _double_curve_with<F, R, S>(
+    points: &[Point2D<F>],
+    o: &Options,
+    rng: &mut R,
+    filling: bool,
+    sink: &mut S,
+    draw: impl Fn(&[Point2D<F>], F, &Options, &mut dyn RngCore, &mut S),
+) where
+    F: Float + Trig + FromPrimitive,
+    R: Rng + Clone,
+    S: PathSink<F> + ?Sized,
+{
+    draw(
+        points,
+        _c::<F>(1.0) * _c(1.0 + o.roughness.unwrap_or(0.0) * 0.2),
+        o,
+        rng,
+        sink,
+    );
+    for pass in 1..pass_count(o, filling) {
+        with_pass_roughness(o, pass, |o| {
+            // The second pass replays the random sequence, the ones after it need a sequence
+            // of their own.
+            let offset = _c::<F>(1.5) * _c(1.0 + o.roughness.unwrap_or(0.0) * 0.22);
+            if pass == 1 {
+                draw(points, offset, o, &mut rng.clone(), sink)
+            } else {
+                draw(points, offset, o, &mut reseed(rng), sink)
+            }
+        });
+    }
+}
+
+/// Number of times a stroke is drawn, or a fill line if `filling`. Unless set explicitly,
+/// strokes are drawn twice, or once if multi stroke is disabled.
+fn pass_count(o: &Options, filling: bool) -> u32 {
+    let (passes, single_stroke) = if filling {
+        (o.fill_passes, o.disable_multi_stroke_fill.unwrap_or(false))
     } else {
-        o.disable_multi_stroke.unwrap_or(false)
+        (o.stroke_passes, o.disable_multi_stroke.unwrap_or(false))
     };
-    let mut o1 = _line(x1, y1, x2, y2, o, true, false);
-    if single_stroke {
-        o1
-    } else {
-        let mut o2 = _line(x1, y1, x2, y2, o, true, true);
-        o1.append(&mut o2);
-        o1
+    match passes {
+        Some(passes) => passes.max(1),
+        None if single_stroke => 1,
+        None => 2,
+    }
+}
+
+/// Runs `draw` for the `pass`-th pass of a stroke, with the roughness scaled by
+/// `pass_roughness_scale` once for every pass after the first.
+fn with_pass_roughness<T>(o: &Options, pass: u32, draw: impl FnOnce(&Options) -> T) -> T {
+    let scale = o.pass_roughness_scale.unwrap_or(1.0).powi(pass as i32);
+    if scale == 1.0 {
+        return draw(o);
     }
+    let scaled = Options {
+        roughness: o.roughness.map(|r| r * scale),
+        ..o.clone()
+    };
+    draw(&scaled)
 }
 
-pub(crate) fn _curve<F: Float + Trig + FromPrimitive>(
+pub(crate) fn _curve<F, R, S>(
     points: &[Point2D<F>],
     close_point: Option<Point2D<F>>,
-    o: &mut Options,
-) -> Vec<Op<F>> {
+    o: &Options,
+    rng: &mut R,
+    sink: &mut S,
+) where
+    F: Float + Trig + FromPrimitive,
+    R: Rng + ?Sized,
+    S: PathSink<F> + ?Sized,
+{
     let len = points.len();
-    let mut ops: Vec<Op<F>> = vec![];
     if len > 3 {
         let mut b: [[F; 2]; 4] = [[_c(0.0); 2]; 4];
         let s: F = _c::<F>(1.0) - _c(o.curve_tightness.unwrap_or(0.0));
-        ops.push(Op {
-            op: OpType::Move,
-            data: vec![points[1].x, points[1].y],
-        });
+        let parameterization = o.curve_parameterization.unwrap_or_default();
+        sink.move_to(points[1]);
         let mut i = 1;
         while (i + 2) < len {
             let cached_vert_array = points[i];
             b[0] = [cached_vert_array.x, cached_vert_array.y];
-            b[1] = [
-                cached_vert_array.x + (s * points[i + 1].x - s * points[i - 1].x) / _c(6.0),
-                cached_vert_array.y + (s * points[i + 1].y - s * points[i - 1].y) / _c(6.0),
-            ];
-            b[2] = [
-                points[i + 1].x + (s * points[i].x - s * points[i + 2].x) / _c(6.0),
-                points[i + 1].y + (s * points[i].y - s * points[i + 2].y) / _c(6.0),
-            ];
+            if parameterization == CurveParameterization::Uniform {
+                b[1] = [
+                    cached_vert_array.x + (s * points[i + 1].x - s * points[i - 1].x) / _c(6.0),
+                    cached_vert_array.y + (s * points[i + 1].y - s * points[i - 1].y) / _c(6.0),
+                ];
+                b[2] = [
+                    points[i + 1].x + (s * points[i].x - s * points[i + 2].x) / _c(6.0),
+                    points[i + 1].y + (s * points[i].y - s * points[i + 2].y) / _c(6.0),
+                ];
+            } else {
+                let (cp1, cp2) = catmull_rom_segment(
+                    points[i - 1],
+                    points[i],
+                    points[i + 1],
+                    points[i + 2],
+                    _c(o.curve_tightness.unwrap_or(0.0)),
+                    parameterization,
+                );
+                b[1] = [cp1.x, cp1.y];
+                b[2] = [cp2.x, cp2.y];
+            }
             b[3] = [points[i + 1].x, points[i + 1].y];
-            ops.push(Op {
-                op: OpType::BCurveTo,
-                data: vec![b[1][0], b[1][1], b[2][0], b[2][1], b[3][0], b[3][1]],
-            });
+            sink.curve_to(
+                point2(b[1][0], b[1][1]),
+                point2(b[2][0], b[2][1]),
+                point2(b[3][0], b[3][1]),
+            );
             i += 1;
         }
         if let Some(cp) = close_point {
             let ro = _c(o.max_randomness_offset.unwrap_or(2.0));
-            ops.push(Op {
-                op: OpType::LineTo,
-                data: vec![
-                    cp.x + _offset_opt(ro, o, None),
-                    cp.y + _offset_opt(ro, o, None),
-                ],
-            });
+            sink.line_to(point2(
+                cp.x + _offset_opt(ro, o, rng, None),
+                cp.y + _offset_opt(ro, o, rng, None),
+            ));
         }
     } else if len == 3 {
-        ops.push(Op {
-            op: OpType::Move,
-            data: vec![points[1].x, points[1].y],
-        });
-        ops.push(Op {
-            op: OpType::BCurveTo,
-            data: vec![
-                points[1].x,
-                points[1].y,
-                points[2].x,
-                points[2].y,
-                points[2].x,
-                points[2].y,
-            ],
-        });
+        sink.move_to(points[1]);
+        sink.curve_to(points[1], points[2], points[2]);
     } else if len == 2 {
-        ops.append(&mut _double_line(
-            points[0].x,
-            points[0].y,
-            points[1].x,
-            points[1].y,
-            o,
-            false,
-        ));
+        _double_line(points[0], points[1], o, rng, false, sink);
     }
-    ops
 }
 
-fn _curve_with_offset<F: Float + Trig + FromPrimitive>(
+fn _curve_with_offset<F, R, S>(
     points: &[Point2D<F>],
     offset: F,
-    o: &mut Options,
-) -> Vec<Op<F>> {
+    o: &Options,
+    rng: &mut R,
+    sink: &mut S,
+) where
+    F: Float + Trig + FromPrimitive,
+    R: Rng + ?Sized,
+    S: PathSink<F> + ?Sized,
+{
     let mut ps: Vec<Point2D<F>> = vec![
         Point2D::new(
-            points[0].x + _offset_opt(offset, o, None),
-            points[0].y + _offset_opt(offset, o, None),
+            points[0].x + _offset_opt(offset, o, rng, None),
+            points[0].y + _offset_opt(offset, o, rng, None),
         ),
         Point2D::new(
-            points[0].x + _offset_opt(offset, o, None),
-            points[0].y + _offset_opt(offset, o, None),
+            points[0].x + _offset_opt(offset, o, rng, None),
+            points[0].y + _offset_opt(offset, o, rng, None),
         ),
     ];
     let mut i = 1;
     while i < points.len() {
         ps.push(Point2D::new(
-            points[i].x + _offset_opt(offset, o, None),
-            points[i].y + _offset_opt(offset, o, None),
+            points[i].x + _offset_opt(offset, o, rng, None),
+            points[i].y + _offset_opt(offset, o, rng, None),
         ));
         if i == (points.len() - 1) {
             ps.push(Point2D::new(
-                points[i].x + _offset_opt(offset, o, None),
-                points[i].y + _offset_opt(offset, o, None),
+                points[i].x + _offset_opt(offset, o, rng, None),
+                points[i].y + _offset_opt(offset, o, rng, None),
             ));
         }
         i += 1;
     }
-    _curve(&ps, None, o)
+    _curve(&ps, None, o, rng, sink)
+}
+
+/// Like [`_curve_with_offset`], but continues from the last point back to the first one
+/// without a kink. A repeated first point at the end is dropped.
+fn _closed_curve_with_offset<F, R, S>(
+    points: &[Point2D<F>],
+    offset: F,
+    o: &Options,
+    rng: &mut R,
+    sink: &mut S,
+) where
+    F: Float + Trig + FromPrimitive,
+    R: Rng + ?Sized,
+    S: PathSink<F> + ?Sized,
+{
+    let mut points = points.to_vec();
+    if points.len() > 1 && points.first() == points.last() {
+        points.pop();
+    }
+    let len = points.len();
+    let jittered: Vec<Point2D<F>> = points
+        .iter()
+        .map(|p| {
+            Point2D::new(
+                p.x + _offset_opt(offset, o, rng, None),
+                p.y + _offset_opt(offset, o, rng, None),
+            )
+        })
+        .collect();
+    // The curve is drawn from the second to the second to last point, so the loop is padded
+    // with its neighbouring points at both ends.
+    let mut ps = vec![jittered[len - 1]];
+    ps.extend(jittered.iter());
+    ps.extend([jittered[0], jittered[1]]);
+    // the curve ends where it started, closing it joins both ends
+    _curve(&ps, None, o, rng, sink);
+    sink.close();
 }
 
-pub(crate) fn _compute_ellipse_points<F: Float + Trig + FromPrimitive>(
-    increment: F,
-    cx: F,
-    cy: F,
-    rx: F,
-    ry: F,
+pub(crate) fn _compute_ellipse_points<F: Float + Trig + FromPrimitive, R: Rng + ?Sized>(
+    center: Point2D<F>,
+    ellipse_params: &EllipseParams<F>,
     offset: F,
     overlap: F,
-    o: &mut Options,
+    o: &Options,
+    rng: &mut R,
 ) -> Vec<Vec<Point2D<F>>> {
+    let EllipseParams { rx, ry, increment } = *ellipse_params;
+    let (cx, cy) = (center.x, center.y);
     let core_only = o.roughness.unwrap_or(0.0) == 0.0;
     let mut core_points: Vec<Point2D<F>> = Vec::new();
     let mut all_points: Vec<Point2D<F>> = Vec::new();
@@ -871,12 +1375,13 @@ pub(crate) fn _compute_ellipse_points<F: Float + Trig + FromPrimitive>(
             cy + ry * Float::sin(increment_inner),
         ));
     } else {
-        let rad_offset: F = _offset_opt::<F>(_c(0.5), o, None) - (_c::<F>(f32::PI()) / _c(2.0));
+        let rad_offset: F =
+            _offset_opt::<F, _>(_c(0.5), o, rng, None) - (_c::<F>(f32::PI()) / _c(2.0));
         all_points.push(Point2D::new(
-            _offset_opt(offset, o, None)
+            _offset_opt(offset, o, rng, None)
                 + cx
                 + _c::<F>(0.9) * rx * Float::cos(rad_offset - increment),
-            _offset_opt(offset, o, None)
+            _offset_opt(offset, o, rng, None)
                 + cy
                 + _c::<F>(0.9) * ry * Float::sin(rad_offset - increment),
         ));
@@ -884,8 +1389,8 @@ pub(crate) fn _compute_ellipse_points<F: Float + Trig + FromPrimitive>(
         let mut angle = rad_offset;
         while angle < end_angle {
             let p = Point2D::new(
-                _offset_opt(offset, o, None) + cx + rx * Float::cos(angle),
-                _offset_opt(offset, o, None) + cy + ry * Float::sin(angle),
+                _offset_opt(offset, o, rng, None) + cx + rx * Float::cos(angle),
+                _offset_opt(offset, o, rng, None) + cy + ry * Float::sin(angle),
             );
             core_points.push(p);
             all_points.push(p);
@@ -893,26 +1398,26 @@ pub(crate) fn _compute_ellipse_points<F: Float + Trig + FromPrimitive>(
         }
 
         all_points.push(Point2D::new(
-            _offset_opt(offset, o, None)
+            _offset_opt(offset, o, rng, None)
                 + cx
                 + rx * Float::cos(rad_offset + _c::<F>(f32::PI()) * _c(2.0) + overlap * _c(0.5)),
-            _offset_opt(offset, o, None)
+            _offset_opt(offset, o, rng, None)
                 + cy
                 + ry * Float::sin(rad_offset + _c::<F>(f32::PI()) * _c(2.0) + overlap * _c(0.5)),
         ));
         all_points.push(Point2D::new(
-            _offset_opt(offset, o, None)
+            _offset_opt(offset, o, rng, None)
                 + cx
                 + _c::<F>(0.98) * rx * Float::cos(rad_offset + overlap),
-            _offset_opt(offset, o, None)
+            _offset_opt(offset, o, rng, None)
                 + cy
                 + _c::<F>(0.98) * ry * Float::sin(rad_offset + overlap),
         ));
         all_points.push(Point2D::new(
-            _offset_opt(offset, o, None)
+            _offset_opt(offset, o, rng, None)
                 + cx
                 + _c::<F>(0.9) * rx * Float::cos(rad_offset + overlap * _c(0.5)),
-            _offset_opt(offset, o, None)
+            _offset_opt(offset, o, rng, None)
                 + cy
                 + _c::<F>(0.9) * ry * Float::sin(rad_offset + overlap * _c(0.5)),
         ));
@@ -920,27 +1425,36 @@ pub(crate) fn _compute_ellipse_points<F: Float + Trig + FromPrimitive>(
     vec![all_points, core_points]
 }
 
-fn _arc<F: Float + Trig + FromPrimitive>(
-    increment: F,
-    cx: F,
-    cy: F,
-    rx: F,
-    ry: F,
-    strt: F,
-    stp: F,
+fn _arc<F, R, S>(
+    center: Point2D<F>,
+    ellipse_params: &EllipseParams<F>,
+    sweep: Range<F>,
     offset: F,
-    o: &mut Options,
-) -> Vec<Op<F>> {
-    let rad_offset = strt + _offset_opt(_c(0.1), o, None);
+    o: &Options,
+    rng: &mut R,
+    sink: &mut S,
+) where
+    F: Float + Trig + FromPrimitive,
+    R: Rng + ?Sized,
+    S: PathSink<F> + ?Sized,
+{
+    let EllipseParams { rx, ry, increment } = *ellipse_params;
+    let Range { start: strt, end: stp } = sweep;
+    let (cx, cy) = (center.x, center.y);
+    let rad_offset = strt + _offset_opt(_c(0.1), o, rng, None);
     let mut points: Vec<Point2D<F>> = vec![Point2D::new(
-        _offset_opt(offset, o, None) + cx + _c::<F>(0.9) * rx * Float::cos(rad_offset - increment),
-        _offset_opt(offset, o, None) + cy + _c::<F>(0.9) * ry * Float::sin(rad_offset - increment),
+        _offset_opt(offset, o, rng, None)
+            + cx
+            + _c::<F>(0.9) * rx * Float::cos(rad_offset - increment),
+        _offset_opt(offset, o, rng, None)
+            + cy
+            + _c::<F>(0.9) * ry * Float::sin(rad_offset - increment),
     )];
     let mut angle = rad_offset;
     while angle <= stp {
         points.push(Point2D::new(
-            _offset_opt(offset, o, None) + cx + rx * Float::cos(angle),
-            _offset_opt(offset, o, None) + cy + ry * Float::sin(angle),
+            _offset_opt(offset, o, rng, None) + cx + rx * Float::cos(angle),
+            _offset_opt(offset, o, rng, None) + cy + ry * Float::sin(angle),
         ));
         angle = angle + increment;
     }
@@ -952,112 +1466,70 @@ fn _arc<F: Float + Trig + FromPrimitive>(
         cx + rx * Float::cos(stp),
         cy + ry * Float::sin(stp),
     ));
-    _curve(&points, None, o)
+    _curve(&points, None, o, rng, sink)
 }
 
-fn _bezier_quadratic_to<F: Float + Trig + FromPrimitive>(
-    x1: F,
-    y1: F,
-    x: F,
-    y: F,
-    current: &Point2D<F>,
-    o: &mut Options,
-) -> Vec<Op<F>> {
-    // We simply convert the quadratic to a cubic bezier
-
-    let cubic = convert_bezier_quadratic_to_cubic(BezierQuadratic {
-        start: *current,
-        cp: Point2D::new(x1, y1),
-        end: Point2D::new(x, y),
-    });
-
-    _bezier_to(
-        cubic.cp1.x,
-        cubic.cp1.y,
-        cubic.cp2.x,
-        cubic.cp2.y,
-        cubic.end.x,
-        cubic.end.y,
-        &cubic.start,
-        o,
-    )
-}
-
-fn _bezier_to<F: Float + Trig + FromPrimitive>(
-    x1: F,
-    y1: F,
-    x2: F,
-    y2: F,
-    x: F,
-    y: F,
-    current: &Point2D<F>,
-    o: &mut Options,
-) -> Vec<Op<F>> {
-    let mut ops: Vec<Op<F>> = Vec::new();
+/// Rough passes of the quadratic or cubic `curve`, starting at `current`.
+fn _bezier_to<F, R, S>(curve: Op<F>, current: &Point2D<F>, o: &Options, rng: &mut R, sink: &mut S)
+where
+    F: Float + Trig + FromPrimitive,
+    R: Rng + ?Sized,
+    S: PathSink<F> + ?Sized,
+{
+    let end = curve.end_point().expect("curves end at a point");
     let ros = [
         _c(o.max_randomness_offset.unwrap_or(2.0)),
         _c(o.max_randomness_offset.unwrap_or(2.0) + 0.3),
     ];
-    let mut f: Point2D<F>;
-    let iterations = if o.disable_multi_stroke.unwrap_or(false) {
-        1
-    } else {
-        2
-    };
+    let iterations = pass_count(o, false);
     let preserve_vertices = o.preserve_vertices.unwrap_or(false);
     let mut i = 0;
     while i < iterations {
-        if i == 0 {
-            ops.push(Op { op: OpType::Move, data: vec![current.x, current.y] });
-        } else {
-            ops.push(Op {
-                op: OpType::Move,
-                data: vec![
+        let ro = ros[(i as usize).min(1)];
+        with_pass_roughness(o, i, |o| {
+            let from = if i == 0 {
+                *current
+            } else {
+                point2(
                     current.x
                         + (if preserve_vertices {
                             _c(0.0)
                         } else {
-                            _offset_opt(ros[0], o, None)
+                            _offset_opt(ros[0], o, rng, None)
                         }),
                     current.y
                         + (if preserve_vertices {
                             _c(0.0)
                         } else {
-                            _offset_opt(ros[0], o, None)
+                            _offset_opt(ros[0], o, rng, None)
                         }),
-                ],
-            });
-        }
-        f = if preserve_vertices {
-            Point2D::new(x, y)
-        } else {
-            Point2D::new(
-                x + _offset_opt(ros[i], o, None),
-                y + _offset_opt(ros[i], o, None),
-            )
-        };
-        ops.push(Op {
-            op: OpType::BCurveTo,
-            data: vec![
-                x1 + _offset_opt(ros[i], o, None),
-                y1 + _offset_opt(ros[i], o, None),
-                x2 + _offset_opt(ros[i], o, None),
-                y2 + _offset_opt(ros[i], o, None),
-                f.x,
-                f.y,
-            ],
+                )
+            };
+            sink.move_to(from);
+            let mut jitter = |p: Point2D<F>| {
+                point2(
+                    p.x + _offset_opt(ro, o, rng, None),
+                    p.y + _offset_opt(ro, o, rng, None),
+                )
+            };
+            let to = if preserve_vertices { end } else { jitter(end) };
+            match curve {
+                Op::QuadTo { cp, .. } => sink.quad_to(from, jitter(cp), to),
+                Op::BCurveTo { cp1, cp2, .. } => sink.curve_to(jitter(cp1), jitter(cp2), to),
+                _ => unreachable!("only curves are drawn as beziers"),
+            }
         });
         i += 1;
     }
-    ops
 }
 
-pub fn pattern_fill_polygons<F, P>(polygon_list: P, o: &mut Options) -> OpSet<F>
+fn pattern_filler<'a, F, P, R>(o: &Options) -> Box<dyn PatternFiller<F, P, R> + 'a>
 where
-    F: Float + Trig + FromPrimitive,
+    F: Float + Trig + FromPrimitive + 'a,
     P: BorrowMut<Vec<Vec<Point2D<F>>>>,
+    R: Rng + Clone,
 {
-    let filler = if let Some(fill_style) = o.fill_style.as_ref() {
+    if let Some(fill_style) = o.fill_style.as_ref() {
         match fill_style {
             FillStyle::Hachure => get_filler(ScanLineHachure),
             FillStyle::Dashed => get_filler(DashedFiller),
@@ -1065,176 +1537,314 @@ where
             FillStyle::CrossHatch => get_filler(HatchFiller),
             FillStyle::ZigZag => get_filler(ZigZagFiller),
             FillStyle::ZigZagLine => get_filler(ZigZagLineFiller),
+            FillStyle::Stipple => get_filler(StippleFiller),
+            FillStyle::Wavy => get_filler(WavyFiller),
+            FillStyle::Scribble => get_filler(ScribbleFiller),
+            FillStyle::HatchLayers => get_filler(LayeredHatchFiller),
+            FillStyle::ToneHatch => get_filler(ToneHatchFiller),
+            FillStyle::Motif => get_filler(MotifFiller),
+            FillStyle::Flow => get_filler(FlowFiller),
+            FillStyle::Hilbert => get_filler(HilbertFiller),
+            FillStyle::Serpentine => get_filler(SerpentineFiller),
+            FillStyle::Watercolor => get_filler(WatercolorFiller),
             _ => get_filler(ScanLineHachure),
         }
     } else {
         get_filler(ScanLineHachure)
-    };
-    filler.fill_polygons(polygon_list, o)
+    }
 }
 
-pub fn pattern_fill_arc<F>(
-    x: F,
-    y: F,
-    width: F,
-    height: F,
-    start: F,
-    stop: F,
-    o: &mut Options,
-) -> OpSet<F>
+pub fn pattern_fill_polygons<F, P, R>(mut polygon_list: P, o: &Options, rng: &mut R) -> OpSet<F>
 where
-    F: Float + FromPrimitive + Trig,
+    F: Float + Trig + FromPrimitive,
+    P: BorrowMut<Vec<Vec<Point2D<F>>>>,
+    R: Rng + Clone,
 {
-    let cx = x;
-    let cy = y;
-    let mut rx = F::abs(width / _c(2.0));
-    let mut ry = F::abs(height / _c(2.0));
-
-    rx = rx + _offset_opt(rx * _c(0.01), o, None);
-    ry = ry + _offset_opt(ry * _c(0.01), o, None);
+    match misregister_fill(polygon_list.borrow_mut(), o, rng) {
+        Some(misregistered) => pattern_filler(o).fill_polygons(misregistered, o, rng),
+        None => pattern_filler(o).fill_polygons(polygon_list, o, rng),
+    }
+}
 
-    let mut strt = start;
-    let mut stp = stop;
-    let two_pi = _c::<F>(f32::PI()) * _c::<F>(2.0);
+/// Like [`pattern_fill_polygons`], but draws the fill straight into `sink`.
+pub fn pattern_fill_polygons_into<F, P, R>(
+    mut polygon_list: P,
+    o: &Options,
+    rng: &mut R,
+    sink: &mut dyn PathSink<F>,
+) where
+    F: Float + Trig + FromPrimitive,
+    P: BorrowMut<Vec<Vec<Point2D<F>>>>,
+    R: Rng + Clone,
+{
+    match misregister_fill(polygon_list.borrow_mut(), o, rng) {
+        Some(misregistered) => pattern_filler(o).fill_polygons_into(misregistered, o, rng, sink),
+        None => pattern_filler(o).fill_polygons_into(polygon_list, o, rng, sink),
+    }
+}
 
-    while strt < _c(0.0) {
-        strt = strt + two_pi;
-        stp = stp + two_pi;
+/// Like [`pattern_fill_polygons`], but keeps the layers of multi layer fill styles
+/// apart, so that each one can be drawn with its own [`crate::core::OpSetStyle`].
+pub fn pattern_fill_polygon_sets<F, P, R>(
+    mut polygon_list: P,
+    o: &Options,
+    rng: &mut R,
+) -> Vec<OpSet<F>>
+where
+    F: Float + Trig + FromPrimitive,
+    P: BorrowMut<Vec<Vec<Point2D<F>>>>,
+    R: Rng + Clone,
+{
+    match misregister_fill(polygon_list.borrow_mut(), o, rng) {
+        Some(misregistered) => pattern_filler(o).fill_polygon_sets(misregistered, o, rng),
+        None => pattern_filler(o).fill_polygon_sets(polygon_list, o, rng),
     }
+}
 
-    if (stp - strt) > two_pi {
-        strt = F::zero();
-        stp = two_pi;
+/// Like [`pattern_fill_polygon_sets`], but streams each set into `sink`, between
+/// [`PathSink::begin_set`] and [`PathSink::end_set`].
+pub fn pattern_fill_polygon_sets_into<F, P, R>(
+    mut polygon_list: P,
+    o: &Options,
+    rng: &mut R,
+    sink: &mut dyn PathSink<F>,
+) where
+    F: Float + Trig + FromPrimitive,
+    P: BorrowMut<Vec<Vec<Point2D<F>>>>,
+    R: Rng + Clone,
+{
+    match misregister_fill(polygon_list.borrow_mut(), o, rng) {
+        Some(misregistered) => {
+            pattern_filler(o).fill_polygon_sets_into(misregistered, o, rng, sink)
+        }
+        None => pattern_filler(o).fill_polygon_sets_into(polygon_list, o, rng, sink),
     }
+}
 
-    let increment = (stp / strt) / o.curve_step_count.map(|a| _c(a)).unwrap_or_else(|| _c(1.0));
-    let mut points: Vec<Point2D<F>> = vec![];
+pub fn pattern_fill_arc<F, R>(arc_params: &ArcParams<F>, o: &Options, rng: &mut R) -> OpSet<F>
+where
+    F: Float + FromPrimitive + Trig,
+    R: Rng + Clone,
+{
+    let points = arc_fill_points(arc_params, o, rng);
+    pattern_fill_polygons(vec![points], o, rng)
+}
 
-    let mut angle = strt;
+/// Like [`pattern_fill_arc`], but draws straight into `sink`.
+pub fn pattern_fill_arc_into<F, R>(
+    arc_params: &ArcParams<F>,
+    o: &Options,
+    rng: &mut R,
+    sink: &mut dyn PathSink<F>,
+) where
+    F: Float + FromPrimitive + Trig,
+    R: Rng + Clone,
+{
+    let points = arc_fill_points(arc_params, o, rng);
+    pattern_fill_polygons_into(vec![points], o, rng, sink)
+}
 
-    while angle <= stp {
-        points.push(point2(
-            cx + rx * Float::cos(angle),
-            cy + ry * Float::sin(angle),
-        ));
-        angle = angle + increment;
+/// Like [`pattern_fill_arc`], but returns one op set per layer of the fill style.
+pub fn pattern_fill_arc_sets<F, R>(
+    arc_params: &ArcParams<F>,
+    o: &Options,
+    rng: &mut R,
+) -> Vec<OpSet<F>>
+where
+    F: Float + FromPrimitive + Trig,
+    R: Rng + Clone,
+{
+    let points = arc_fill_points(arc_params, o, rng);
+    pattern_fill_polygon_sets(vec![points], o, rng)
+}
+
+/// Like [`pattern_fill_arc_sets`], but streams each set into `sink`, between
+/// [`PathSink::begin_set`] and [`PathSink::end_set`].
+pub fn pattern_fill_arc_sets_into<F, R>(
+    arc_params: &ArcParams<F>,
+    o: &Options,
+    rng: &mut R,
+    sink: &mut dyn PathSink<F>,
+) where
+    F: Float + FromPrimitive + Trig,
+    R: Rng + Clone,
+{
+    let points = arc_fill_points(arc_params, o, rng);
+    pattern_fill_polygon_sets_into(vec![points], o, rng, sink)
+}
+
+/// Outline of the area an arc covers in the given mode: the curve, closed through the
+/// center for pies. Open arcs cover the same area as chords.
+fn arc_fill_points<F, R>(arc_params: &ArcParams<F>, o: &Options, rng: &mut R) -> Vec<Point2D<F>>
+where
+    F: Float + FromPrimitive + Trig,
+    R: Rng + ?Sized,
+{
+    let ArcParams { x, y, width, height, start, stop, mode } = *arc_params;
+    let cx = x;
+    let cy = y;
+    let mut rx = F::abs(width / _c(2.0));
+    let mut ry = F::abs(height / _c(2.0));
+
+    rx = rx + _offset_opt(rx * _c(0.01), o, rng, None);
+    ry = ry + _offset_opt(ry * _c(0.01), o, rng, None);
+
+    let (strt, stp) = normalize_arc_angles(start, stop);
+    // As fine as the rough outline, which takes half the steps of a full ellipse.
+    let step = _c::<F>(f32::PI() / o.curve_step_count.unwrap_or(1.0).max(1.0));
+    let steps = ((stp - strt) / step).ceil().to_usize().unwrap_or(1).max(1);
+    let increment = (stp - strt) / _c(steps as f32);
+    let mut points: Vec<Point2D<F>> = (0..=steps)
+        .map(|i| {
+            let angle = strt + increment * _c(i as f32);
+            point2(cx + rx * Float::cos(angle), cy + ry * Float::sin(angle))
+        })
+        .collect();
+
+    if mode == ArcMode::Pie {
+        points.push(point2(cx, cy));
     }
+    points
+}
 
-    points.push(point2(cx + rx * Float::cos(stp), cy + ry * Float::sin(stp)));
-    points.push(point2(cx, cy));
-    pattern_fill_polygons(vec![points], o)
+pub fn svg_path<F, R>(path: String, o: &Options, rng: &mut R) -> OpSet<F>
+where
+    F: Float + FromPrimitive + Trig,
+    R: Rng + ?Sized,
+{
+    collect(OpSetType::Path, |ops| svg_path_into(path, o, rng, ops))
 }
 
-pub fn svg_path<F>(path: String, o: &mut Options) -> OpSet<F>
+/// Like [`svg_path`], but draws straight into `sink`.
+pub fn svg_path_into<F, R>(path: String, o: &Options, rng: &mut R, sink: &mut dyn PathSink<F>)
 where
     F: Float + FromPrimitive + Trig,
+    R: Rng + ?Sized,
 {
-    let mut ops = vec![];
-    let mut first = Point2D::new(_c::<F>(0.0), _c::<F>(0.0));
-    let mut current = Point2D::new(_c::<F>(0.0), _c::<F>(0.0));
     let path_parser = PathParser::from(path.as_ref());
     let path_segments: Vec<PathSegment> = path_parser.flatten().collect();
-    let normalized_segments = normalize(absolutize(path_segments.iter()));
+    let normalized_segments = normalize_keeping_quadratics(absolutize(path_segments.iter()));
 
-    opset_from_path(o, ops, first, current, normalized_segments)
+    path_segments_into(o, rng, sink, normalized_segments)
 }
 
-pub fn svg_segments<F>(path_segments: Vec<PathSegment>, o: &mut Options) -> OpSet<F>
+pub fn svg_segments<F, R>(path_segments: Vec<PathSegment>, o: &Options, rng: &mut R) -> OpSet<F>
 where
     F: Float + FromPrimitive + Trig,
+    R: Rng + ?Sized,
 {
-    let mut ops = vec![];
-    let mut first = Point2D::new(_c::<F>(0.0), _c::<F>(0.0));
-    let mut current = Point2D::new(_c::<F>(0.0), _c::<F>(0.0));
-    let normalized_segments = normalize(absolutize(path_segments.iter()));
+    collect(OpSetType::Path, |ops| svg_segments_into(path_segments, o, rng, ops))
+}
+
+/// Like [`svg_segments`], but draws straight into `sink`.
+pub fn svg_segments_into<F, R>(
+    path_segments: Vec<PathSegment>,
+    o: &Options,
+    rng: &mut R,
+    sink: &mut dyn PathSink<F>,
+) where
+    F: Float + FromPrimitive + Trig,
+    R: Rng + ?Sized,
+{
+    let normalized_segments = normalize_keeping_quadratics(absolutize(path_segments.iter()));
 
-    opset_from_path(o, ops, first, current, normalized_segments)
+    path_segments_into(o, rng, sink, normalized_segments)
 }
 
-fn opset_from_path<F>(
-    o: &mut Options,
-    mut ops: Vec<Op<F>>,
-    mut first: euclid::Point2D<F, euclid::UnknownUnit>,
-    mut current: euclid::Point2D<F, euclid::UnknownUnit>,
+fn path_segments_into<F, R>(
+    o: &Options,
+    rng: &mut R,
+    sink: &mut dyn PathSink<F>,
     normalized_segments: impl Iterator<Item = PathSegment>,
-) -> OpSet<F>
-where
+) where
     F: Float + FromPrimitive + Trig,
+    R: Rng + ?Sized,
 {
+    let mut first = Point2D::new(_c::<F>(0.0), _c::<F>(0.0));
+    let mut current = Point2D::new(_c::<F>(0.0), _c::<F>(0.0));
     for segment in normalized_segments {
         match segment {
             PathSegment::MoveTo { abs: true, x, y } => {
                 let ro = _c::<F>(1.0) * _c::<F>(o.max_randomness_offset.unwrap_or(2.0));
                 let pv = o.preserve_vertices.unwrap_or(false);
-                ops.push(Op {
-                    op: OpType::Move,
-                    data: vec![
-                        if pv {
-                            _cc::<F>(x)
-                        } else {
-                            _cc::<F>(x) + _offset_opt(ro, o, None)
-                        },
-                        if pv {
-                            _cc::<F>(y)
-                        } else {
-                            _cc::<F>(y) + _offset_opt(ro, o, None)
-                        },
-                    ],
-                });
+                sink.move_to(point2(
+                    if pv {
+                        _cc::<F>(x)
+                    } else {
+                        _cc::<F>(x) + _offset_opt(ro, o, rng, None)
+                    },
+                    if pv {
+                        _cc::<F>(y)
+                    } else {
+                        _cc::<F>(y) + _offset_opt(ro, o, rng, None)
+                    },
+                ));
                 current = Point2D::new(_cc::<F>(x), _cc::<F>(y));
                 first = Point2D::new(_cc::<F>(x), _cc::<F>(y));
             }
             PathSegment::LineTo { abs: true, x, y } => {
-                ops.extend(_double_line(
-                    current.x,
-                    current.y,
-                    _cc::<F>(x),
-                    _cc::<F>(y),
-                    o,
-                    false,
-                ));
+                let to = point2(_cc::<F>(x), _cc::<F>(y));
+                _double_line(current, to, o, rng, false, sink);
                 current = Point2D::new(_cc::<F>(x), _cc::<F>(y));
             }
             PathSegment::CurveTo { abs: true, x1, y1, x2, y2, x, y } => {
-                ops.extend(_bezier_to(
-                    _cc::<F>(x1),
-                    _cc::<F>(y1),
-                    _cc::<F>(x2),
-                    _cc::<F>(y2),
-                    _cc::<F>(x),
-                    _cc::<F>(y),
-                    &current,
-                    o,
-                ));
+                let curve = Op::BCurveTo {
+                    cp1: point2(_cc::<F>(x1), _cc::<F>(y1)),
+                    cp2: point2(_cc::<F>(x2), _cc::<F>(y2)),
+                    to: point2(_cc::<F>(x), _cc::<F>(y)),
+                };
+                _bezier_to(curve, &current, o, rng, sink);
+                current = Point2D::new(_cc::<F>(x), _cc::<F>(y));
+            }
+            PathSegment::Quadratic { abs: true, x1, y1, x, y } => {
+                let curve = Op::QuadTo {
+                    cp: point2(_cc::<F>(x1), _cc::<F>(y1)),
+                    to: point2(_cc::<F>(x), _cc::<F>(y)),
+                };
+                _bezier_to(curve, &current, o, rng, sink);
                 current = Point2D::new(_cc::<F>(x), _cc::<F>(y));
             }
             PathSegment::ClosePath { abs: true } => {
-                ops.extend(_double_line(
-                    current.x, current.y, first.x, first.y, o, false,
-                ));
+                _double_line(current, first, o, rng, false, sink);
                 current = Point2D::new(first.x, first.y);
             }
             _ => panic!("Unexpected segment type"),
         }
     }
-    OpSet {
-        op_set_type: OpSetType::Path,
-        ops,
-        size: None,
-        path: None,
-    }
+}
+
+/// Collects the ops `draw` streams into a set of `op_set_type`.
+fn collect<F: Float + Trig>(
+    op_set_type: OpSetType,
+    draw: impl FnOnce(&mut Vec<Op<F>>),
+) -> OpSet<F> {
+    let mut ops = vec![];
+    draw(&mut ops);
+    OpSet { op_set_type, ops, size: None, path: None, style: None }
 }
 
 #[cfg(test)]
 mod test {
-    use euclid::point2;
+    use euclid::default::Point2D;
+    use euclid::{point2, vec2};
     use plotlib::page::Page;
     use plotlib::repr::Plot;
     use plotlib::style::{PointMarker, PointStyle};
     use plotlib::view::ContinuousView;
 
-    use super::{EllipseParams, _compute_ellipse_points, _curve};
-    use crate::core::{Op, OpSet, OpSetType, OpType, Options, OptionsBuilder};
+    use super::{_compute_ellipse_points, _curve, ArcParams, EllipseParams};
+    use crate::core::{
+        ArcMode,
+        CurveParameterization,
+        FillStyle,
+        Op,
+        OpSet,
+        OpSetType,
+        OpType,
+        Options,
+        OptionsBuilder,
+        StrokeStyle,
+    };
 
     fn get_default_options() -> Options {
         OptionsBuilder::default()
@@ -1245,10 +1855,12 @@ mod test {
 
     #[test]
     fn linear_path() {
+        let o = get_default_options();
         let result = super::linear_path(
             &[point2(0.0f32, 0.0), point2(0.0, 0.1), point2(1.0, 1.0)],
             false,
-            &mut get_default_options(),
+            &o,
+            &mut o.rng(),
         );
         assert_eq!(result.op_set_type, OpSetType::Path);
         assert_eq!(
@@ -1256,59 +1868,34 @@ mod test {
             OpSet {
                 op_set_type: OpSetType::Path,
                 ops: vec![
-                    Op {
-                        op: OpType::Move,
-                        data: vec![-0.009998378, -0.006502221]
-                    },
-                    Op {
-                        op: OpType::BCurveTo,
-                        data: vec![
-                            0.004064642,
-                            0.033123452,
-                            0.0023629116,
-                            0.07122354,
-                            0.0037581995,
-                            0.10122616
-                        ]
-                    },
-                    Op {
-                        op: OpType::Move,
-                        data: vec![-0.0034061566, 0.003728075]
-                    },
-                    Op {
-                        op: OpType::BCurveTo,
-                        data: vec![
-                            -0.00069929345,
-                            0.023493448,
-                            0.0010793343,
-                            0.044991724,
-                            0.004097348,
-                            0.10335246
-                        ]
+                    Op::Move(point2(-0.009998378, -0.006502221)),
+                    Op::BCurveTo {
+                        cp1: point2(0.004064642, 0.033123452),
+                        cp2: point2(0.0023629116, 0.07122354),
+                        to: point2(0.0037581995, 0.10122616)
                     },
-                    Op {
-                        op: OpType::Move,
-                        data: vec![-0.12339515, -0.013104506]
+                    Op::Move(point2(-0.0034061566, 0.003728075)),
+                    Op::BCurveTo {
+                        cp1: point2(-0.00069929345, 0.023493448),
+                        cp2: point2(0.0010793343, 0.044991724),
+                        to: point2(0.004097348, 0.10335246)
                     },
-                    Op {
-                        op: OpType::BCurveTo,
-                        data: vec![
-                            0.35436878, 0.262468, 0.57661635, 0.6634873, 1.0144088, 1.102317
-                        ]
+                    Op::Move(point2(-0.12339515, -0.013104506)),
+                    Op::BCurveTo {
+                        cp1: point2(0.35436878, 0.262468),
+                        cp2: point2(0.57661635, 0.6634873),
+                        to: point2(1.0144088, 1.102317)
                     },
-                    Op {
-                        op: OpType::Move,
-                        data: vec![-0.002887085, 0.049306016]
-                    },
-                    Op {
-                        op: OpType::BCurveTo,
-                        data: vec![
-                            0.25721234, 0.27631992, 0.59522116, 0.53014225, 0.94422996, 0.9684893
-                        ]
+                    Op::Move(point2(-0.002887085, 0.049306016)),
+                    Op::BCurveTo {
+                        cp1: point2(0.25721234, 0.27631992),
+                        cp2: point2(0.59522116, 0.53014225),
+                        to: point2(0.94422996, 0.9684893)
                     }
                 ],
                 size: None,
-                path: None
+                path: None,
+                style: None
             }
         );
     }
@@ -1316,6 +1903,7 @@ mod test {
     #[test]
     #[ignore = "failing due to randomness"]
     fn ellipse_with_params() {
+        let o = get_default_options();
         let expected_estimated_points = vec![
             point2(0.6818724507954145, -0.24215675845215262),
             point2(1.3682071413206485, 0.7930465114686116),
@@ -1331,7 +1919,8 @@ mod test {
         let result = super::ellipse_with_params(
             0.1,
             0.1,
-            &mut get_default_options(),
+            &o,
+            &mut o.rng(),
             &EllipseParams {
                 rx: 0.486848765998615,
                 ry: 0.4755334706420514,
@@ -1345,6 +1934,7 @@ mod test {
     #[test]
     #[ignore = "failing due to randommness"]
     fn compute_ellipse_points() {
+        let o = get_default_options();
         let expected = vec![
             vec![
                 point2(1.0710641633603797, 0.6343339196221436),
@@ -1482,21 +2072,21 @@ mod test {
             ],
         ];
         let result = _compute_ellipse_points(
-            0.1,
-            1.0,
-            1.0,
-            0.5,
-            0.5,
+            point2(1.0, 1.0),
+            &EllipseParams { rx: 0.5, ry: 0.5, increment: 0.1 },
             0.1,
             0.1,
-            &mut get_default_options(),
+            &o,
+            &mut o.rng(),
         );
         assert_eq!(expected, result);
     }
 
     #[test]
     fn curve() {
-        let result = _curve(
+        let o = get_default_options();
+        let mut result: Vec<Op<f64>> = vec![];
+        _curve(
             &[
                 point2(0.0, 0.0),
                 point2(1.0, 1.0),
@@ -1504,23 +2094,341 @@ mod test {
                 point2(-1.0, -1.0),
             ],
             None,
-            &mut get_default_options(),
+            &o,
+            &mut o.rng(),
+            &mut result,
         );
-        assert_eq!(result[0].op, OpType::Move);
-        assert_eq!(result[0].data, vec![1.0, 1.0]);
+        assert_eq!(result[0], Op::Move(point2(1.0, 1.0)));
 
-        assert_eq!(result[1].op, OpType::BCurveTo);
         assert_eq!(
-            result[1].data,
+            result[1],
+            Op::BCurveTo {
+                cp1: point2(1.3333333333333333, 1.0),
+                cp2: point2(2.3333333333333335, 0.3333333333333333),
+                to: point2(2.0, 0.0)
+            }
+        );
+    }
+
+    #[test]
+    fn overshoot() {
+        let mut o = OptionsBuilder::default()
+            .roughness(0.0)
+            .stroke_passes(1)
+            .overshoot(5.0)
+            .build()
+            .unwrap();
+        let result = super::rectangle(0.0, 0.0, 100.0, 50.0, &o, &mut o.rng());
+        // the top edge starts left of the corner and ends right of the next one
+        let start = result.ops[0].end_point().unwrap();
+        let end = result.ops[1].end_point().unwrap();
+        assert!(start.x <= 0.0 && start.x >= -5.0);
+        assert!(end.x >= 100.0 && end.x <= 105.0);
+        assert_eq!(end.y, 0.0);
+        assert!(end.x > 100.0 || start.x < 0.0);
+
+        o.preserve_vertices = Some(true);
+        let result = super::rectangle(0.0, 0.0, 100.0, 50.0, &o, &mut o.rng());
+        assert_eq!(result.ops[0], Op::Move(point2(0.0, 0.0)));
+        assert_eq!(result.ops[1].end_point(), Some(point2(100.0, 0.0)));
+    }
+
+    #[test]
+    fn stroke_passes() {
+        let ops = |o: &Options| super::line(0.0, 0.0, 50.0, 20.0, o, &mut o.rng()).ops;
+        let default = ops(&get_default_options());
+        let mut two = get_default_options();
+        two.stroke_passes = Some(2);
+        assert_eq!(ops(&two), default);
+
+        let mut single = get_default_options();
+        single.stroke_passes = Some(1);
+        assert_eq!(ops(&single).len(), 2);
+
+        let mut overworked = get_default_options();
+        overworked.stroke_passes = Some(4);
+        overworked.pass_roughness_scale = Some(1.5);
+        let result = ops(&overworked);
+        assert_eq!(result.len(), 8);
+        assert_eq!(result[..2], default[..2]);
+        assert_eq!(overworked.roughness, Some(1.0));
+    }
+
+    #[test]
+    fn quadratic_curves() {
+        let o = get_default_options();
+        let types = |ops: Vec<Op<f32>>| ops.iter().map(|op| op.op_type()).collect::<Vec<_>>();
+        let quadratic = [OpType::Move, OpType::QuadTo, OpType::Move, OpType::QuadTo];
+        let ops = super::bezier_quadratic(
+            point2(0.0f32, 0.0),
+            point2(25.0, 25.0),
+            point2(50.0, 0.0),
+            &o,
+            &mut o.rng(),
+        )
+        .ops;
+        assert_eq!(types(ops), quadratic);
+
+        let ops = super::svg_path("M0 0 Q25 25 50 0".into(), &o, &mut o.rng()).ops;
+        assert_eq!(types(ops)[1..], quadratic);
+        let ops = super::svg_path("M0 0 C25 25 25 25 50 0".into(), &o, &mut o.rng()).ops;
+        assert!(types(ops).iter().all(|op| *op != OpType::QuadTo));
+    }
+
+    #[test]
+    fn arc_fill_points() {
+        use std::f64::consts::PI;
+
+        let o = OptionsBuilder::default().roughness(0.0).build().unwrap();
+        let mut rng = o.rng();
+        let on_ellipse =
+            |p: &Point2D<f64>| ((p.x - 50.0) / 40.0).powi(2) + ((p.y - 50.0) / 20.0).powi(2) - 1.0;
+        let angle = |p: &Point2D<f64>| ((p.y - 50.0) / 20.0).atan2((p.x - 50.0) / 40.0);
+        let cases = [
+            (0.0, PI / 2.0, PI / 2.0),
+            (-PI / 2.0, PI / 2.0, PI),
+            (PI, PI / 2.0, 1.5 * PI),
+            (PI / 4.0, PI / 4.0, 0.0),
+            (0.0, 3.0 * PI, 2.0 * PI),
+        ];
+        for (start, stop, sweep) in cases {
+            let params = ArcParams {
+                x: 50.0,
+                y: 50.0,
+                width: 80.0,
+                height: 40.0,
+                start,
+                stop,
+                mode: ArcMode::Pie,
+            };
+            let pie = super::arc_fill_points(&params, &o, &mut rng);
+            assert_eq!(pie[pie.len() - 1], point2(50.0, 50.0));
+            let curve = &pie[..pie.len() - 1];
+            assert!(curve.len() >= 2);
+            assert!(curve.iter().all(|p| on_ellipse(p).abs() < 1e-9));
+            let at = |angle: f64| point2(50.0 + 40.0 * angle.cos(), 50.0 + 20.0 * angle.sin());
+            // angles are normalized with turns of f32 precision
+            assert!((curve[0] - at(start)).length() < 1e-4);
+            assert!((curve[curve.len() - 1] - at(start + sweep)).length() < 1e-4);
+
+            // the curve goes forward from start to stop without doubling back
+            let mut covered = 0.0;
+            for w in curve.windows(2) {
+                let step = (angle(&w[1]) - angle(&w[0])).rem_euclid(2.0 * PI);
+                covered += if step > PI { step - 2.0 * PI } else { step };
+            }
+            assert!((covered - sweep).abs() < 1e-4);
+
+            let params = ArcParams { mode: ArcMode::Chord, ..params };
+            let chord = super::arc_fill_points(&params, &o, &mut rng);
+            assert_eq!(chord, curve);
+        }
+    }
+
+    #[test]
+    fn arc_modes() {
+        use std::f32::consts::PI;
+
+        let sweep = |stop: f32, mode: ArcMode, rough_closure: bool| {
+            let o = OptionsBuilder::default()
+                .roughness(0.0)
+                .stroke_passes(1)
+                .build()
+                .unwrap();
+            let params = ArcParams {
+                x: 0.0f32,
+                y: 0.0,
+                width: 20.0,
+                height: 20.0,
+                start: 0.0,
+                stop,
+                mode,
+            };
+            super::arc(&params, rough_closure, &o, &mut o.rng()).ops
+        };
+        let arc = |mode: ArcMode, rough_closure: bool| sweep(PI / 2.0, mode, rough_closure);
+        let open = arc(ArcMode::Open, true);
+        assert_eq!(
+            open.iter()
+                .filter(|op| op.op_type() == OpType::Move)
+                .count(),
+            1
+        );
+
+        let chord = arc(ArcMode::Chord, true);
+        assert_eq!(chord[..open.len()], open[..]);
+        // the chord goes back from the end of the curve to its start
+        let from = chord[open.len()].end_point().unwrap();
+        assert!(from.x.abs() < 1e-4 && from.y == 10.0);
+        let to = chord[chord.len() - 1].end_point().unwrap();
+        assert!((to.x - 10.0).abs() < 1e-4 && to.y.abs() < 1e-4);
+        let chord = arc(ArcMode::Chord, false);
+        assert_eq!(chord[open.len()..].len(), 1);
+
+        let pie = arc(ArcMode::Pie, false);
+        assert_eq!(pie[open.len()], Op::LineTo(point2(0.0, 0.0)));
+        assert_eq!(pie[pie.len() - 1], Op::LineTo(point2(10.0, 0.0)));
+
+        // arcs that sweep no angle are only their closing lines
+        assert!(sweep(0.0, ArcMode::Open, true).is_empty());
+        assert!(sweep(0.0, ArcMode::Chord, false).is_empty());
+        assert_eq!(
+            sweep(0.0, ArcMode::Pie, false),
             vec![
-                1.3333333333333333,
-                1.0,
-                2.3333333333333335,
-                0.3333333333333333,
-                2.0,
-                0.0
+                Op::Move(point2(10.0, 0.0)),
+                Op::LineTo(point2(0.0, 0.0)),
+                Op::LineTo(point2(10.0, 0.0)),
             ]
         );
+        let pie = sweep(0.0, ArcMode::Pie, true);
+        assert_eq!(
+            pie.iter().filter(|op| op.op_type() == OpType::Move).count(),
+            2
+        );
+        assert!(!sweep(1e-6, ArcMode::Open, true).is_empty());
+        let o = OptionsBuilder::default().roughness(5.0).build().unwrap();
+        let params = ArcParams {
+            x: 0.0f32,
+            y: 0.0,
+            width: 20.0,
+            height: 20.0,
+            start: 1.0,
+            stop: 1.0 + 1e-6,
+            mode: ArcMode::Pie,
+        };
+        for seed in 0..32 {
+            let o = Options { seed: Some(seed), ..o.clone() };
+            let ops = super::arc(&params, true, &o, &mut o.rng()).ops;
+            assert!(ops.len() < 64);
+        }
+    }
+
+    #[test]
+    fn closed_curve() {
+        let points = [
+            point2(0.0f64, 0.0),
+            point2(40.0, 0.0),
+            point2(50.0, 30.0),
+            point2(10.0, 40.0),
+        ];
+        let mut o = OptionsBuilder::default()
+            .curve_closed(true)
+            .stroke_passes(1)
+            .build()
+            .unwrap();
+        let ops = super::curve(&points, &o, &mut o.rng()).ops;
+        assert_eq!(ops.len(), 2 + points.len());
+        assert_eq!(ops[ops.len() - 1], Op::Close);
+        let start: Point2D<f64> = ops[0].end_point().unwrap();
+        let Op::BCurveTo { cp2, to, .. } = ops[ops.len() - 2] else {
+            panic!("the curve does not end in a bezier");
+        };
+        let Op::BCurveTo { cp1, .. } = ops[1] else {
+            panic!("the curve does not start with a bezier");
+        };
+        assert_eq!(to, start);
+        // no kink where the end meets the start
+        let incoming = start - cp2;
+        let outgoing = cp1 - start;
+        assert!(incoming.cross(outgoing).abs() < 1e-9);
+        assert!(incoming.dot(outgoing) > 0.0);
+
+        o.curve_parameterization = Some(CurveParameterization::Centripetal);
+        let centripetal = super::curve(&points, &o, &mut o.rng()).ops;
+        assert_eq!(centripetal.len(), ops.len());
+        assert_ne!(centripetal, ops);
+    }
+
+    #[test]
+    fn dashed_polylines() {
+        let line = vec![vec![point2(0.0f32, 0.0), point2(100.0, 0.0)]];
+        let mut o = OptionsBuilder::default()
+            .stroke_style(StrokeStyle::Dashed)
+            .stroke_line_dash(vec![10.0, 5.0])
+            .roughness(0.0)
+            .build()
+            .unwrap();
+        let result = super::dashed_polylines(&line, &o, &mut o.rng());
+        let moves: Vec<&Op<f32>> = result
+            .ops
+            .iter()
+            .filter(|op| op.op_type() == OpType::Move)
+            .collect();
+        // dashes start every 15 units and each is drawn twice
+        assert_eq!(moves.len(), 7 * 2);
+        let third = moves[2].end_point().unwrap();
+        assert!((third.x - 15.0).abs() < 1e-4 && third.y == 0.0);
+        assert!(result
+            .ops
+            .iter()
+            .flat_map(|op| op.points())
+            .all(|p| p.x >= 0.0 && p.x <= 100.0 && p.y == 0.0));
+
+        o.stroke_style = Some(StrokeStyle::Dotted);
+        o.stroke_line_dash = None;
+        o.stroke_line_dash_offset = Some(-2.0);
+        o.roughness = Some(1.0);
+        let result = super::dashed_polylines(&line, &o, &mut o.rng());
+        let dots: Vec<&Op<f32>> = result
+            .ops
+            .iter()
+            .filter(|op| op.op_type() == OpType::Move)
+            .collect();
+        // a dot every 4 stroke widths, starting at 2
+        assert_eq!(dots.len(), 25);
+        assert!(dots.iter().all(|op| op.end_point().unwrap().y.abs() < 1.0));
+    }
+
+    #[test]
+    fn solid_fill_polygon_misregistered() {
+        let square = vec![
+            point2(0.0, 0.0),
+            point2(10.0, 0.0),
+            point2(10.0, 10.0),
+            point2(0.0, 10.0),
+        ];
+        let hole = vec![
+            point2(4.0, 4.0),
+            point2(6.0, 4.0),
+            point2(6.0, 6.0),
+            point2(4.0, 6.0),
+        ];
+        let o = OptionsBuilder::default()
+            .max_randomness_offset(0.0)
+            .fill_offset(vec2(5.0, 0.0))
+            .fill_inset(1.0)
+            .build()
+            .unwrap();
+        let result = super::solid_fill_polygon(&vec![square, hole], &o, &mut o.rng());
+        let points: Vec<Option<Point2D<f64>>> =
+            result.ops.iter().map(|op| op.end_point()).collect();
+
+        assert_eq!(points[0], Some(point2(6.0, 1.0)));
+        assert_eq!(points[2], Some(point2(14.0, 9.0)));
+        assert_eq!(result.ops[4], Op::Close);
+        // the hole grows while the outer ring shrinks
+        assert_eq!(points[5], Some(point2(8.0, 3.0)));
+        assert_eq!(points[7], Some(point2(12.0, 7.0)));
+    }
+
+    #[test]
+    fn streamed_pattern_fill() {
+        let square = vec![
+            point2(0.0, 0.0),
+            point2(40.0, 0.0),
+            point2(40.0, 40.0),
+            point2(0.0, 40.0),
+        ];
+        for fill_style in [FillStyle::Hachure, FillStyle::Dots, FillStyle::Watercolor] {
+            let o = OptionsBuilder::default()
+                .fill_style(fill_style)
+                .build()
+                .unwrap();
+            let set = super::pattern_fill_polygons(vec![square.clone()], &o, &mut o.rng());
+            let mut ops: Vec<Op<f64>> = vec![];
+            super::pattern_fill_polygons_into(vec![square.clone()], &o, &mut o.rng(), &mut ops);
+            assert_eq!(ops, set.ops);
+        }
     }
 
     #[test]
@@ -0,0 +1,355 @@
+//! Streaming of generated ops into arbitrary path builders.
+//!
+//! A [`PathSink`] receives the path commands of a drawing one set at a time, so backends
+//! can build their native paths and exporters can write out large drawings without
+//! converting a whole [`Drawable`] first.
+
+use std::fmt::{Display, Write};
+
+use euclid::default::{Point2D, Vector2D};
+use euclid::Trig;
+use num_traits::{Float, FromPrimitive};
+
+use crate::core::{_c, Drawable, Op, OpSet, OpSetStyle, OpSetType};
+
+/// Receiver of path commands.
+///
+/// Only the basic commands have to be implemented. Quadratic curves and ellipses fall back
+/// to cubic curves unless the sink has a native primitive for them.
+pub trait PathSink<F: Float + Trig> {
+    /// Called before the ops of each set, with how the set is meant to be drawn.
+    fn begin_set(&mut self, _op_set_type: &OpSetType, _style: Option<&OpSetStyle>) {}
+
+    fn move_to(&mut self, to: Point2D<F>);
+
+    fn line_to(&mut self, to: Point2D<F>);
+
+    fn curve_to(&mut self, cp1: Point2D<F>, cp2: Point2D<F>, to: Point2D<F>);
+
+    /// Quadratic curve starting at `from`, the current point of the path.
+    fn quad_to(&mut self, from: Point2D<F>, cp: Point2D<F>, to: Point2D<F>) {
+        let two_thirds = (F::one() + F::one()) / (F::one() + F::one() + F::one());
+        self.curve_to(from.lerp(cp, two_thirds), to.lerp(cp, two_thirds), to);
+    }
+
+    fn close(&mut self);
+
+    /// A whole ellipse as its own closed subpath.
+    fn ellipse(&mut self, center: Point2D<F>, radii: Vector2D<F>) {
+        // control point distance for quarter circles
+        let k = F::from(0.552_284_749_830_793_4).unwrap();
+        let (rx, ry) = (radii.x, radii.y);
+        let at = |x: F, y: F| Point2D::new(center.x + x, center.y + y);
+        self.move_to(at(rx, F::zero()));
+        self.curve_to(at(rx, ry * k), at(rx * k, ry), at(F::zero(), ry));
+        self.curve_to(at(-rx * k, ry), at(-rx, ry * k), at(-rx, F::zero()));
+        self.curve_to(at(-rx, -ry * k), at(-rx * k, -ry), at(F::zero(), -ry));
+        self.curve_to(at(rx * k, -ry), at(rx, -ry * k), at(rx, F::zero()));
+        self.close();
+    }
+
+    /// Called after the ops of each set.
+    fn end_set(&mut self) {}
+}
+
+/// Collects the streamed commands back into ops.
+impl<F: Float + Trig> PathSink<F> for Vec<Op<F>> {
+    fn move_to(&mut self, to: Point2D<F>) {
+        self.push(Op::Move(to));
+    }
+
+    fn line_to(&mut self, to: Point2D<F>) {
+        self.push(Op::LineTo(to));
+    }
+
+    fn curve_to(&mut self, cp1: Point2D<F>, cp2: Point2D<F>, to: Point2D<F>) {
+        self.push(Op::BCurveTo { cp1, cp2, to });
+    }
+
+    fn quad_to(&mut self, _from: Point2D<F>, cp: Point2D<F>, to: Point2D<F>) {
+        self.push(Op::QuadTo { cp, to });
+    }
+
+    fn close(&mut self) {
+        self.push(Op::Close);
+    }
+
+    fn ellipse(&mut self, center: Point2D<F>, radii: Vector2D<F>) {
+        self.push(Op::Ellipse { center, radii });
+    }
+}
+
+/// Collects the streamed sets back into op sets. Ops streamed outside of a set go into the
+/// last set, or into a new [`OpSetType::Path`] set if there is none yet.
+impl<F: Float + Trig> PathSink<F> for Vec<OpSet<F>> {
+    fn begin_set(&mut self, op_set_type: &OpSetType, style: Option<&OpSetStyle>) {
+        self.push(OpSet {
+            op_set_type: op_set_type.clone(),
+            ops: vec![],
+            size: None,
+            path: None,
+            style: style.cloned(),
+        });
+    }
+
+    fn move_to(&mut self, to: Point2D<F>) {
+        current_ops(self).move_to(to);
+    }
+
+    fn line_to(&mut self, to: Point2D<F>) {
+        current_ops(self).line_to(to);
+    }
+
+    fn curve_to(&mut self, cp1: Point2D<F>, cp2: Point2D<F>, to: Point2D<F>) {
+        current_ops(self).curve_to(cp1, cp2, to);
+    }
+
+    fn quad_to(&mut self, from: Point2D<F>, cp: Point2D<F>, to: Point2D<F>) {
+        current_ops(self).quad_to(from, cp, to);
+    }
+
+    fn close(&mut self) {
+        current_ops(self).close();
+    }
+
+    fn ellipse(&mut self, center: Point2D<F>, radii: Vector2D<F>) {
+        current_ops(self).ellipse(center, radii);
+    }
+}
+
+fn current_ops<F: Float + Trig>(sets: &mut Vec<OpSet<F>>) -> &mut Vec<Op<F>> {
+    if sets.is_empty() {
+        sets.begin_set(&OpSetType::Path, None);
+    }
+    &mut sets.last_mut().unwrap().ops
+}
+
+/// Replays `ops` into `sink`, without any set boundaries.
+pub fn stream_ops<'a, F, S>(ops: impl IntoIterator<Item = &'a Op<F>>, sink: &mut S)
+where
+    F: Float + Trig + 'a,
+    S: PathSink<F> + ?Sized,
+{
+    let mut start = Point2D::origin();
+    let mut current = Point2D::origin();
+    for op in ops {
+        match *op {
+            Op::Move(to) => {
+                sink.move_to(to);
+                start = to;
+                current = to;
+            }
+            Op::LineTo(to) => {
+                sink.line_to(to);
+                current = to;
+            }
+            Op::BCurveTo { cp1, cp2, to } => {
+                sink.curve_to(cp1, cp2, to);
+                current = to;
+            }
+            Op::QuadTo { cp, to } => {
+                sink.quad_to(current, cp, to);
+                current = to;
+            }
+            Op::Close => {
+                sink.close();
+                current = start;
+            }
+            Op::Ellipse { center, radii } => {
+                sink.ellipse(center, radii);
+                // the ellipse is a closed subpath of its own, ending where it started
+                start = Point2D::new(center.x + radii.x, center.y);
+                current = start;
+            }
+        }
+    }
+}
+
+impl<F: Float + Trig> OpSet<F> {
+    /// Replays the ops of the set into `sink`, between [`PathSink::begin_set`] and
+    /// [`PathSink::end_set`].
+    pub fn stream_into<S: PathSink<F> + ?Sized>(&self, sink: &mut S) {
+        sink.begin_set(&self.op_set_type, self.style.as_ref());
+        stream_ops(&self.ops, sink);
+        sink.end_set();
+    }
+}
+
+impl<F: Float + Trig> Drawable<F> {
+    /// Replays all sets of the drawable into `sink`, in drawing order.
+    pub fn stream_into<S: PathSink<F> + ?Sized>(&self, sink: &mut S) {
+        for set in self.sets.iter() {
+            set.stream_into(sink);
+        }
+    }
+}
+
+/// Writes streamed commands as svg path data, with every set as a separate line.
+pub struct SvgPathWriter<W: Write> {
+    out: W,
+    fixed_decimals: Option<u32>,
+    error: Option<std::fmt::Error>,
+}
+
+impl<W: Write> SvgPathWriter<W> {
+    pub fn new(out: W) -> Self {
+        SvgPathWriter { out, fixed_decimals: None, error: None }
+    }
+
+    /// Rounds all written coordinates to `decimals` places.
+    pub fn with_fixed_decimals(mut self, decimals: u32) -> Self {
+        self.fixed_decimals = Some(decimals);
+        self
+    }
+
+    /// Gives back the writer, or the first error it returned.
+    pub fn finish(self) -> Result<W, std::fmt::Error> {
+        match self.error {
+            Some(error) => Err(error),
+            None => Ok(self.out),
+        }
+    }
+
+    fn write<F: Float + FromPrimitive + Display>(&mut self, command: char, points: &[Point2D<F>]) {
+        if self.error.is_some() {
+            return;
+        }
+        let round = |v: F| match self.fixed_decimals {
+            Some(fd) => {
+                let pow = _c::<F>(10.0).powi(fd as i32);
+                (v * pow).round() / pow
+            }
+            None => v,
+        };
+        let mut result = write!(self.out, "{}", command);
+        for p in points {
+            result = result.and_then(|_| write!(self.out, "{} {} ", round(p.x), round(p.y)));
+        }
+        self.error = result.err();
+    }
+}
+
+impl<F, W> PathSink<F> for SvgPathWriter<W>
+where
+    F: Float + Trig + FromPrimitive + Display,
+    W: Write,
+{
+    fn move_to(&mut self, to: Point2D<F>) {
+        self.write('M', &[to]);
+    }
+
+    fn line_to(&mut self, to: Point2D<F>) {
+        self.write('L', &[to]);
+    }
+
+    fn curve_to(&mut self, cp1: Point2D<F>, cp2: Point2D<F>, to: Point2D<F>) {
+        self.write('C', &[cp1, cp2, to]);
+    }
+
+    fn quad_to(&mut self, _from: Point2D<F>, cp: Point2D<F>, to: Point2D<F>) {
+        self.write('Q', &[cp, to]);
+    }
+
+    fn close(&mut self) {
+        self.write::<F>('Z', &[]);
+    }
+
+    fn end_set(&mut self) {
+        if self.error.is_none() {
+            self.error = self.out.write_char('\n').err();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use euclid::default::Point2D;
+    use euclid::{point2, vec2};
+
+    use super::{stream_ops, PathSink, SvgPathWriter};
+    use crate::core::{Op, OpSet, OpSetType};
+
+    #[test]
+    fn ops_round_trip() {
+        let ops = vec![
+            Op::Move(point2(0.0f64, 0.0)),
+            Op::LineTo(point2(10.0, 0.0)),
+            Op::QuadTo { cp: point2(15.0, 5.0), to: point2(10.0, 10.0) },
+            Op::BCurveTo {
+                cp1: point2(5.0, 15.0),
+                cp2: point2(0.0, 15.0),
+                to: point2(0.0, 10.0),
+            },
+            Op::Close,
+            Op::Ellipse { center: point2(30.0, 30.0), radii: vec2(2.0, 1.0) },
+        ];
+        let mut collected: Vec<Op<f64>> = vec![];
+        stream_ops(&ops, &mut collected);
+        assert_eq!(collected, ops);
+    }
+
+    /// Sink relying on the provided fallbacks for quadratic curves and ellipses.
+    struct CubicsOnly(Vec<Op<f64>>);
+
+    impl PathSink<f64> for CubicsOnly {
+        fn move_to(&mut self, to: Point2D<f64>) {
+            self.0.move_to(to);
+        }
+
+        fn line_to(&mut self, to: Point2D<f64>) {
+            self.0.line_to(to);
+        }
+
+        fn curve_to(&mut self, cp1: Point2D<f64>, cp2: Point2D<f64>, to: Point2D<f64>) {
+            self.0.curve_to(cp1, cp2, to);
+        }
+
+        fn close(&mut self) {
+            self.0.close();
+        }
+    }
+
+    #[test]
+    fn fallbacks() {
+        let ops = [
+            Op::Move(point2(0.0, 0.0)),
+            Op::QuadTo { cp: point2(3.0, 3.0), to: point2(6.0, 0.0) },
+            Op::Ellipse { center: point2(10.0, 10.0), radii: vec2(2.0, 1.0) },
+        ];
+        let mut sink = CubicsOnly(vec![]);
+        stream_ops(&ops, &mut sink);
+        assert_eq!(
+            sink.0[1],
+            Op::BCurveTo {
+                cp1: point2(2.0, 2.0),
+                cp2: point2(4.0, 2.0),
+                to: point2(6.0, 0.0)
+            }
+        );
+        // an ellipse is a move, four quarter curves and a close
+        assert_eq!(sink.0.len(), 2 + 6);
+        assert_eq!(sink.0[2], Op::Move(point2(12.0, 10.0)));
+        assert_eq!(sink.0[4].end_point(), Some(point2(8.0, 10.0)));
+        assert_eq!(sink.0[6].end_point(), Some(point2(12.0, 10.0)));
+        assert_eq!(sink.0[7], Op::Close);
+    }
+
+    #[test]
+    fn svg_path_writer() {
+        let set = OpSet {
+            op_set_type: OpSetType::Path,
+            ops: vec![
+                Op::Move(point2(0.0f32, 0.0)),
+                Op::LineTo(point2(1.0 / 3.0, 2.0)),
+                Op::Close,
+            ],
+            size: None,
+            path: None,
+            style: None,
+        };
+        let mut writer = SvgPathWriter::new(String::new()).with_fixed_decimals(2);
+        set.stream_into(&mut writer);
+        set.stream_into(&mut writer);
+        assert_eq!(writer.finish().unwrap(), "M0 0 L0.33 2 Z\nM0 0 L0.33 2 Z\n");
+    }
+}
@@ -0,0 +1,329 @@
+//! Named style presets and color themes.
+//!
+//! A [`Preset`] sets how rough a drawing looks, a [`Theme`] which colors it is drawn in.
+//! Both end up in an [`OptionsBuilder`], so any option set on it afterwards overrides them:
+//!
+//! ```rust
+//! use roughr::style::{Preset, Theme};
+//!
+//! let options = Preset::Crayon
+//!     .themed(&Theme::dark())
+//!     .stroke_width(4.0)
+//!     .build()
+//!     .unwrap();
+//! assert_eq!(options.stroke, Some(Theme::dark().primary));
+//! assert_eq!(options.stroke_width, Some(4.0));
+//! ```
+
+use palette::{Hsla, IntoColor, Srgba};
+
+use crate::core::{FillStyle, Options, OptionsBuilder};
+
+/// Semantic color roles of a [`Theme`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Role {
+    /// Outlines.
+    Primary,
+    /// Fills.
+    Secondary,
+    /// Shapes that should stand out.
+    Accent,
+    /// The surface drawn on.
+    Background,
+}
+
+/// Colors for the semantic roles of a drawing.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Theme {
+    pub primary: Srgba,
+    pub secondary: Srgba,
+    pub accent: Srgba,
+    pub background: Srgba,
+}
+
+fn rgb(red: u8, green: u8, blue: u8) -> Srgba {
+    Srgba::from_components((red, green, blue, 255u8)).into_format()
+}
+
+fn lightness(color: Srgba) -> f32 {
+    let hsla: Hsla = color.into_color();
+    hsla.lightness
+}
+
+/// `color` with its lightness flipped, keeping hue and saturation.
+fn flip_lightness(color: Srgba) -> Srgba {
+    let mut hsla: Hsla = color.into_color();
+    hsla.lightness = 1.0 - hsla.lightness;
+    hsla.into_color()
+}
+
+impl Theme {
+    pub const NAMES: [&'static str; 4] = ["light", "dark", "blueprint", "chalkboard"];
+
+    /// Brown lines and cream fills on a sea green background, the colors of the examples.
+    pub fn light() -> Self {
+        Theme {
+            primary: rgb(114, 87, 82),
+            secondary: rgb(254, 246, 201),
+            accent: rgb(214, 96, 77),
+            background: rgb(150, 192, 183),
+        }
+    }
+
+    /// The light theme in dark mode.
+    pub fn dark() -> Self {
+        Theme::light().dark_mode()
+    }
+
+    /// White lines on blueprint blue.
+    pub fn blueprint() -> Self {
+        Theme {
+            primary: rgb(240, 246, 255),
+            secondary: rgb(86, 138, 201),
+            accent: rgb(255, 214, 102),
+            background: rgb(30, 78, 140),
+        }
+    }
+
+    /// Chalk on a green board.
+    pub fn chalkboard() -> Self {
+        Theme {
+            primary: rgb(238, 238, 230),
+            secondary: rgb(190, 214, 196),
+            accent: rgb(246, 196, 204),
+            background: rgb(46, 72, 60),
+        }
+    }
+
+    /// The theme called `name`, one of [`Theme::NAMES`].
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "light" => Some(Theme::light()),
+            "dark" => Some(Theme::dark()),
+            "blueprint" => Some(Theme::blueprint()),
+            "chalkboard" => Some(Theme::chalkboard()),
+            _ => None,
+        }
+    }
+
+    /// Variant of the theme for dark mode, with the lightness of every color flipped so that
+    /// the background turns dark and the lines drawn on it light. Themes with a dark
+    /// background, like the blueprint and chalkboard ones, are their own dark variant.
+    pub fn dark_mode(&self) -> Self {
+        if lightness(self.background) <= 0.5 {
+            return *self;
+        }
+        Theme {
+            primary: flip_lightness(self.primary),
+            secondary: flip_lightness(self.secondary),
+            accent: flip_lightness(self.accent),
+            background: flip_lightness(self.background),
+        }
+    }
+
+    pub fn color(&self, role: Role) -> Srgba {
+        match role {
+            Role::Primary => self.primary,
+            Role::Secondary => self.secondary,
+            Role::Accent => self.accent,
+            Role::Background => self.background,
+        }
+    }
+
+    /// Strokes `builder` in the primary color and fills it in the secondary one.
+    pub fn apply<'a>(&self, builder: &'a mut OptionsBuilder) -> &'a mut OptionsBuilder {
+        builder.stroke(self.primary).fill(self.secondary)
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::light()
+    }
+}
+
+/// Named combinations of roughness, bowing, stroke passes and fill style, each with a
+/// theme of its own.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Preset {
+    /// Thin, slightly wobbly lines with light hachure.
+    Pencil,
+    /// Bold single strokes over solid fills.
+    Marker,
+    /// Heavy, rough strokes drawn over and over, filled with scribbles.
+    Crayon,
+    /// Neat cross hatched drawings on blueprint paper.
+    Blueprint,
+    /// Thick, rough chalk lines on a dark board.
+    Chalkboard,
+    /// Barely rough single strokes that keep their corners.
+    Technical,
+}
+
+impl Preset {
+    pub const ALL: [Preset; 6] = [
+        Preset::Pencil,
+        Preset::Marker,
+        Preset::Crayon,
+        Preset::Blueprint,
+        Preset::Chalkboard,
+        Preset::Technical,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Preset::Pencil => "pencil",
+            Preset::Marker => "marker",
+            Preset::Crayon => "crayon",
+            Preset::Blueprint => "blueprint",
+            Preset::Chalkboard => "chalkboard",
+            Preset::Technical => "technical",
+        }
+    }
+
+    /// The preset called `name`, as returned by [`Preset::name`].
+    pub fn from_name(name: &str) -> Option<Self> {
+        Preset::ALL.into_iter().find(|preset| preset.name() == name)
+    }
+
+    pub fn theme(&self) -> Theme {
+        match self {
+            Preset::Blueprint => Theme::blueprint(),
+            Preset::Chalkboard => Theme::chalkboard(),
+            _ => Theme::light(),
+        }
+    }
+
+    /// Options builder set up with the preset in its own theme.
+    pub fn builder(&self) -> OptionsBuilder {
+        self.themed(&self.theme())
+    }
+
+    /// Options builder set up with the preset in the colors of `theme`.
+    pub fn themed(&self, theme: &Theme) -> OptionsBuilder {
+        let mut builder = OptionsBuilder::default();
+        theme.apply(&mut builder);
+        match self {
+            Preset::Pencil => builder
+                .roughness(0.8)
+                .bowing(1.0)
+                .stroke_width(1.0)
+                .stroke_passes(2)
+                .fill_style(FillStyle::Hachure)
+                .fill_weight(0.5)
+                .hachure_gap(4.0),
+            Preset::Marker => builder
+                .roughness(1.5)
+                .bowing(0.5)
+                .stroke_width(3.0)
+                .stroke_passes(1)
+                .fill_style(FillStyle::Solid),
+            Preset::Crayon => builder
+                .roughness(2.5)
+                .bowing(2.0)
+                .stroke_width(2.0)
+                .stroke_passes(3)
+                .fill_style(FillStyle::Scribble)
+                .fill_weight(1.5),
+            Preset::Blueprint => builder
+                .roughness(0.5)
+                .bowing(0.5)
+                .stroke_width(1.0)
+                .stroke_passes(2)
+                .fill_style(FillStyle::CrossHatch)
+                .fill_weight(0.5)
+                .hachure_gap(6.0),
+            Preset::Chalkboard => builder
+                .roughness(2.0)
+                .bowing(1.0)
+                .stroke_width(2.5)
+                .stroke_passes(2)
+                .fill_style(FillStyle::Hachure)
+                .fill_weight(2.0)
+                .hachure_gap(8.0)
+                .hachure_line_wobble(0.5),
+            Preset::Technical => builder
+                .roughness(0.2)
+                .bowing(0.2)
+                .stroke_width(1.0)
+                .stroke_passes(1)
+                .fill_style(FillStyle::Hachure)
+                .fill_weight(0.5)
+                .hachure_gap(5.0)
+                .preserve_vertices(true),
+        };
+        builder
+    }
+
+    pub fn options(&self) -> Options {
+        self.builder()
+            .build()
+            .expect("failed to build preset options")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{lightness, Preset, Role, Theme};
+    use crate::core::FillStyle;
+    use crate::generator::Generator;
+
+    #[test]
+    fn names() {
+        for preset in Preset::ALL {
+            assert_eq!(Preset::from_name(preset.name()), Some(preset));
+        }
+        assert_eq!(Preset::from_name("charcoal"), None);
+        for name in Theme::NAMES {
+            assert!(Theme::from_name(name).is_some());
+        }
+        assert_eq!(Theme::from_name("dark"), Some(Theme::dark()));
+    }
+
+    #[test]
+    fn dark_mode() {
+        for name in Theme::NAMES {
+            let theme = Theme::from_name(name).unwrap();
+            let dark = theme.dark_mode();
+            assert!(lightness(dark.background) <= 0.5, "{}", name);
+            assert!(
+                lightness(dark.primary) > lightness(dark.background),
+                "{}",
+                name
+            );
+            assert_eq!(dark.dark_mode(), dark);
+        }
+        assert_eq!(Theme::blueprint().dark_mode(), Theme::blueprint());
+        assert_ne!(Theme::dark(), Theme::light());
+    }
+
+    #[test]
+    fn overrides() {
+        let options = Preset::Blueprint.options();
+        assert_eq!(options.stroke, Some(Theme::blueprint().primary));
+        assert_eq!(options.fill, Some(Theme::blueprint().secondary));
+        assert_eq!(options.fill_style, Some(FillStyle::CrossHatch));
+
+        let dark = Theme::dark();
+        let options = Preset::Blueprint
+            .themed(&dark)
+            .stroke(dark.color(Role::Accent))
+            .roughness(3.0)
+            .build()
+            .unwrap();
+        assert_eq!(options.stroke, Some(dark.accent));
+        assert_eq!(options.fill, Some(dark.secondary));
+        assert_eq!(options.roughness, Some(3.0));
+        assert_eq!(options.bowing, Some(0.5));
+    }
+
+    #[test]
+    fn presets_draw() {
+        let generator = Generator::default();
+        for preset in Preset::ALL {
+            let drawable = generator.rectangle(0.0, 0.0, 40.0, 30.0, &Some(preset.options()));
+            assert_eq!(drawable.sets.len(), 2, "{}", preset.name());
+            assert!(drawable.sets.iter().all(|set| !set.ops.is_empty()));
+        }
+    }
+}
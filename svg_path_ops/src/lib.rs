@@ -333,6 +333,22 @@ pub fn print_line_segment(segment: &PathSegment) {
 /// It also simplifies the cases to consider when modifying these segments.
 pub fn normalize(
     path_segments: impl Iterator<Item = impl Borrow<PathSegment>>,
+) -> impl Iterator<Item = PathSegment> {
+    normalize_segments(path_segments, false)
+}
+
+/// Like [`normalize`], but keeps quadratic curves as Q segments instead of raising them to cubics, for
+/// platforms that draw quadratic curves natively. Smooth quadratics become Q segments with the
+/// reflected control point.
+pub fn normalize_keeping_quadratics(
+    path_segments: impl Iterator<Item = impl Borrow<PathSegment>>,
+) -> impl Iterator<Item = PathSegment> {
+    normalize_segments(path_segments, true)
+}
+
+fn normalize_segments(
+    path_segments: impl Iterator<Item = impl Borrow<PathSegment>>,
+    keep_quadratics: bool,
 ) -> impl Iterator<Item = PathSegment> {
     let mut out = vec![];
 
@@ -413,38 +429,14 @@ pub fn normalize(
                     x1 = cx;
                     y1 = cy;
                 }
-                let cx1 = cx + 2.0 * (x1 - cx) / 3.0;
-                let cy1 = cy + 2.0 * (y1 - cy) / 3.0;
-                let cx2 = x + 2.0 * (x1 - x) / 3.0;
-                let cy2 = y + 2.0 * (y1 - y) / 3.0;
-                out.push(PathSegment::CurveTo {
-                    abs: true,
-                    x1: cx1,
-                    y1: cy1,
-                    x2: cx2,
-                    y2: cy2,
-                    x,
-                    y,
-                });
+                out.push(quadratic_segment(cx, cy, x1, y1, x, y, keep_quadratics));
                 lcx = x1;
                 lcy = y1;
                 cx = x;
                 cy = y;
             }
             PathSegment::Quadratic { abs: true, x1, y1, x, y } => {
-                let cx1 = cx + 2.0 * (x1 - cx) / 3.0;
-                let cy1 = cy + 2.0 * (y1 - cy) / 3.0;
-                let cx2 = x + 2.0 * (x1 - x) / 3.0;
-                let cy2 = y + 2.0 * (y1 - y) / 3.0;
-                out.push(PathSegment::CurveTo {
-                    abs: true,
-                    x1: cx1,
-                    y1: cy1,
-                    x2: cx2,
-                    y2: cy2,
-                    x,
-                    y,
-                });
+                out.push(quadratic_segment(cx, cy, x1, y1, x, y, keep_quadratics));
                 lcx = x1;
                 lcy = y1;
                 cx = x;
@@ -522,6 +514,34 @@ pub fn normalize(
     out.into_iter()
 }
 
+/// The quadratic curve from `cx`, `cy` to `x`, `y`, as a cubic unless it is kept.
+fn quadratic_segment(
+    cx: f64,
+    cy: f64,
+    x1: f64,
+    y1: f64,
+    x: f64,
+    y: f64,
+    keep_quadratic: bool,
+) -> PathSegment {
+    if keep_quadratic {
+        return PathSegment::Quadratic { abs: true, x1, y1, x, y };
+    }
+    let cx1 = cx + 2.0 * (x1 - cx) / 3.0;
+    let cy1 = cy + 2.0 * (y1 - cy) / 3.0;
+    let cx2 = x + 2.0 * (x1 - x) / 3.0;
+    let cy2 = y + 2.0 * (y1 - y) / 3.0;
+    PathSegment::CurveTo {
+        abs: true,
+        x1: cx1,
+        y1: cy1,
+        x2: cx2,
+        y2: cy2,
+        x,
+        y,
+    }
+}
+
 fn rotate(x: f64, y: f64, angle_rad: f64) -> (f64, f64) {
     let rotated_x = x * angle_rad.cos() - y * angle_rad.sin();
     let rotated_y = x * angle_rad.sin() + y * angle_rad.cos();
@@ -1226,4 +1246,29 @@ mod test {
             ]
         );
     }
+
+    #[test]
+    pub fn normalize_keeping_quadratics() {
+        let path_segments: Vec<PathSegment> = PathParser::from("M0 0q5 5 10 0T20 0h10")
+            .flatten()
+            .collect();
+        let normalized: Vec<PathSegment> =
+            super::normalize_keeping_quadratics(absolutize(path_segments.iter())).collect();
+        assert_eq!(
+            normalized,
+            vec![
+                PathSegment::MoveTo { abs: true, x: 0.0, y: 0.0 },
+                PathSegment::Quadratic { abs: true, x1: 5.0, y1: 5.0, x: 10.0, y: 0.0 },
+                PathSegment::Quadratic { abs: true, x1: 15.0, y1: -5.0, x: 20.0, y: 0.0 },
+                PathSegment::LineTo { abs: true, x: 30.0, y: 0.0 },
+            ]
+        );
+        let cubics = super::normalize(absolutize(path_segments.iter()));
+        assert_eq!(
+            cubics
+                .filter(|segment| matches!(segment, PathSegment::CurveTo { .. }))
+                .count(),
+            2
+        );
+    }
 }